@@ -0,0 +1,99 @@
+// The core local interruptor, the memory mapped registers behind the machine timer
+// and software interrupts of the hart, at the address it has on most platforms.
+pub const BASE: u64 = 0x0200_0000;
+pub const SIZE: u64 = 0x1_0000;
+
+// Offsets of the registers, all of them are little endian.
+const MSIP: u64 = 0x0000;
+const MTIMECMP: u64 = 0x4000;
+const MTIME: u64 = 0xbff8;
+
+pub struct Clint {
+    // Only the bit 0 of msip is writable, it is the machine software interrupt.
+    msip: u32,
+
+    // The machine timer interrupt is pending while mtime is at or past mtimecmp.
+    mtimecmp: u64,
+
+    // The timer counts the steps of the machine, including the ones spent waiting
+    // for an interrupt.
+    mtime: u64,
+}
+
+impl Default for Clint {
+    fn default() -> Self {
+        // The timer does not go off until mtimecmp is set.
+        Clint {
+            msip: 0,
+            mtimecmp: u64::MAX,
+            mtime: 0,
+        }
+    }
+}
+
+impl Clint {
+    // Whether the physical address belongs to the CLINT.
+    pub fn contains(addr: u64) -> bool {
+        (BASE..BASE + SIZE).contains(&addr)
+    }
+
+    // Read the byte at the physical address, the gaps between the registers read
+    // as zero.
+    pub fn read(&self, addr: u64) -> u8 {
+        match addr - BASE {
+            offset @ MSIP..=0x0003 => byte(self.msip as u64, offset - MSIP),
+            offset @ MTIMECMP..=0x4007 => byte(self.mtimecmp, offset - MTIMECMP),
+            offset @ MTIME..=0xbfff => byte(self.mtime, offset - MTIME),
+            _ => 0,
+        }
+    }
+
+    // Write the byte at the physical address, the writes to the gaps between the
+    // registers are ignored.
+    pub fn write(&mut self, addr: u64, val: u8) {
+        match addr - BASE {
+            offset @ MSIP..=0x0003 => {
+                self.msip = with_byte(self.msip as u64, offset - MSIP, val) as u32 & 1;
+            }
+            offset @ MTIMECMP..=0x4007 => {
+                self.mtimecmp = with_byte(self.mtimecmp, offset - MTIMECMP, val);
+            }
+            offset @ MTIME..=0xbfff => {
+                self.mtime = with_byte(self.mtime, offset - MTIME, val);
+            }
+            _ => {}
+        }
+    }
+
+    // Advance the timer by a step.
+    pub fn tick(&mut self) {
+        self.mtime = self.mtime.wrapping_add(1);
+    }
+
+    // The current value of the timer.
+    pub fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
+    // Whether the machine software interrupt is pending.
+    pub fn msip(&self) -> bool {
+        self.msip & 1 != 0
+    }
+
+    // Whether the machine timer interrupt is pending.
+    pub fn mtip(&self) -> bool {
+        self.mtime >= self.mtimecmp
+    }
+}
+
+// The byte of a register at an offset.
+fn byte(val: u64, offset: u64) -> u8 {
+    val.to_le_bytes()[offset as usize]
+}
+
+// The register with the byte at an offset replaced.
+fn with_byte(val: u64, offset: u64, byte: u8) -> u64 {
+    let mut bytes = val.to_le_bytes();
+    bytes[offset as usize] = byte;
+    u64::from_le_bytes(bytes)
+}
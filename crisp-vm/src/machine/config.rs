@@ -0,0 +1,210 @@
+// The options a machine is built with, they stay the same for its whole run.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    // The width of the integer registers in bits (XLEN), 32 or 64. A 64 bit machine is
+    // RV64IMAC with the supervisor and user modes, without virtual memory, as there is
+    // no Sv39 yet, and without the F and D extensions, so, mstatus.FS stays Off. The
+    // optional extensions it does not have yet are turned off, see Extensions::for_xlen.
+    pub xlen: u32,
+
+    // Whether the base ISA is RV32E, the embedded one that only has the 16 registers
+    // x0 to x15.
+    pub rve: bool,
+
+    // What the loads and stores that are not aligned to their size do.
+    pub misaligned: Misaligned,
+
+    // Whether a debugger is attached, the breakpoints stop the machine for it instead
+    // of trapping.
+    pub debugger: bool,
+
+    // The optional extensions that are turned on.
+    pub extensions: Extensions,
+
+    // The size of a vector register in bits (VLEN), a power of two from 32 to 65536.
+    pub vlen: u32,
+
+    // The size of a cache block in bytes, the unit of the cache block operations, a
+    // power of two from 4 to 4096.
+    pub cache_block: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            xlen: 32,
+            rve: false,
+            misaligned: Misaligned::default(),
+            debugger: false,
+            extensions: Extensions::default(),
+            vlen: 128,
+            cache_block: 64,
+        }
+    }
+}
+
+// The ways of handling the misaligned loads and stores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Misaligned {
+    // Raise the address misaligned exceptions, leaving the access to be emulated by
+    // the firmware.
+    Trap,
+
+    // Perform the access byte by byte as hardware that supports them would.
+    #[default]
+    Emulate,
+}
+
+// The optional extensions, the instructions of the ones that are turned off are
+// illegal. All of them are on by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extensions {
+    // Zba, the address generation instructions.
+    pub zba: bool,
+
+    // Zbb, the basic bit manipulation instructions.
+    pub zbb: bool,
+
+    // Zbc, the carry-less multiplications.
+    pub zbc: bool,
+
+    // Zbs, the single bit instructions.
+    pub zbs: bool,
+
+    // Zbkb, the bit manipulation instructions for cryptography.
+    pub zbkb: bool,
+
+    // Zbkc, the carry-less multiplications for cryptography.
+    pub zbkc: bool,
+
+    // Zbkx, the crossbar permutations.
+    pub zbkx: bool,
+
+    // Zknd, the AES decryption instructions.
+    pub zknd: bool,
+
+    // Zkne, the AES encryption instructions.
+    pub zkne: bool,
+
+    // Zknh, the SHA-2 hash function instructions.
+    pub zknh: bool,
+
+    // Zkr, the seed CSR of the entropy source.
+    pub zkr: bool,
+
+    // Zve32x, the vector instructions on integer elements of up to 32 bits.
+    pub zve32x: bool,
+
+    // Zicond, the conditional zero instructions.
+    pub zicond: bool,
+
+    // Zicbom, the cache block management instructions.
+    pub zicbom: bool,
+
+    // Zicboz, the cache block zero instruction.
+    pub zicboz: bool,
+
+    // Zawrs, the wait on reservation set instructions.
+    pub zawrs: bool,
+
+    // Zacas, the atomic compare and swap of words and doublewords.
+    pub zacas: bool,
+
+    // Zabha, the atomics on bytes and halfwords, compare and swap included along
+    // with Zacas.
+    pub zabha: bool,
+
+    // Zfh, the half precision floating point instructions.
+    pub zfh: bool,
+
+    // Zfhmin, the half precision loads, stores, moves and conversions alone, which
+    // Zfh includes.
+    pub zfhmin: bool,
+
+    // Zfa, the additional floating point instructions, on half precision values along
+    // with Zfh.
+    pub zfa: bool,
+
+    // Zcmp, the compressed pushes, pops and register pair moves. Along with Zcmt, it
+    // takes the encodings of the compressed double precision loads and stores (Zcd),
+    // which are illegal while either of them is on, so, both are off by default.
+    pub zcmp: bool,
+
+    // Zcmt, the compressed table jumps through jvt.
+    pub zcmt: bool,
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Extensions {
+            zba: true,
+            zbb: true,
+            zbc: true,
+            zbs: true,
+            zbkb: true,
+            zbkc: true,
+            zbkx: true,
+            zknd: true,
+            zkne: true,
+            zknh: true,
+            zkr: true,
+            zve32x: true,
+            zicond: true,
+            zicbom: true,
+            zicboz: true,
+            zawrs: true,
+            zacas: true,
+            zabha: true,
+            zfh: true,
+            zfhmin: true,
+            zfa: true,
+            zcmp: false,
+            zcmt: false,
+        }
+    }
+}
+
+impl Extensions {
+    // The extensions a machine of the XLEN has out of the ones that are turned on. The
+    // conditional zero, cache block, wait on reservation set, entropy source and byte
+    // and halfword atomic extensions are the only ones there on RV64 so far, the rest
+    // of them either need the F extension or differ between RV32 and RV64. Each one
+    // that is turned on but not there is turned off with a warning.
+    pub fn for_xlen(mut self, xlen: u32) -> Self {
+        if xlen == 32 {
+            return self;
+        }
+
+        for (name, on) in [
+            ("zba", &mut self.zba),
+            ("zbb", &mut self.zbb),
+            ("zbc", &mut self.zbc),
+            ("zbs", &mut self.zbs),
+            ("zbkb", &mut self.zbkb),
+            ("zbkc", &mut self.zbkc),
+            ("zbkx", &mut self.zbkx),
+            ("zknd", &mut self.zknd),
+            ("zkne", &mut self.zkne),
+            ("zknh", &mut self.zknh),
+            ("zve32x", &mut self.zve32x),
+            ("zacas", &mut self.zacas),
+            ("zfh", &mut self.zfh),
+            ("zfhmin", &mut self.zfhmin),
+            ("zfa", &mut self.zfa),
+            ("zcmp", &mut self.zcmp),
+            ("zcmt", &mut self.zcmt),
+        ] {
+            if *on {
+                log::warn!(
+                    target: "config",
+                    "{} is not there on RV{}, turning it off",
+                    name,
+                    xlen
+                );
+                *on = false;
+            }
+        }
+
+        self
+    }
+}
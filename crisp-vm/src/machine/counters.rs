@@ -0,0 +1,97 @@
+use crate::machine::{
+    csr::{self, Csrs},
+    state::Privilege,
+};
+
+// The counters by their index, which is also their bit in mcountinhibit, mcounteren
+// and scounteren. The rest of the indices are the hpmcounters.
+pub const CYCLE: u16 = 0;
+pub const TIME: u16 = 1;
+pub const INSTRET: u16 = 2;
+const COUNTERS: u16 = 32;
+
+// The events an hpmcounter can be set to count by writing their number to its
+// mhpmevent, 0 counts nothing.
+pub const EVENT_LOAD: u32 = 1;
+pub const EVENT_STORE: u32 = 2;
+pub const EVENT_BRANCH_TAKEN: u32 = 3;
+pub const EVENT_TRAP: u32 = 4;
+const EVENTS: u32 = 5;
+
+// The bit of an event in the set of events of a step.
+pub const fn event(number: u32) -> u32 {
+    1 << number
+}
+
+// The value of a counter in the 64 bits made of its low and high halves.
+pub fn get(csrs: &Csrs, index: u16) -> u64 {
+    let low = csrs.get(csr::MCYCLE + index) as u64;
+    let high = csrs.get(csr::MCYCLEH + index) as u64;
+    (high << 32) | low
+}
+
+// Set the value of a counter, both of its halves.
+fn set(csrs: &mut Csrs, index: u16, val: u64) {
+    csrs.set(csr::MCYCLE + index, val as u32);
+    csrs.set(csr::MCYCLEH + index, (val >> 32) as u32);
+}
+
+// Counts a step of the machine. mcycle counts every step, minstret the ones that
+// retire an instruction and the hpmcounters the ones with the event they are set
+// to. A counter is left alone while it is inhibited, and when the step wrote to it
+// so that the value written is the one the next instruction sees.
+pub fn count(csrs: &mut Csrs, retired: bool, events: u32, written: u32) {
+    let inhibit = csrs.get(csr::MCOUNTINHIBIT);
+
+    for index in (0..COUNTERS).filter(|&index| index != TIME) {
+        let bit = 1 << index;
+        if (inhibit | written) & bit != 0 {
+            continue;
+        }
+
+        let counts = match index {
+            CYCLE => true,
+            INSTRET => retired,
+            index => events & event(csrs.get(csr::MHPMEVENT3 + index - 3)) != 0,
+        };
+
+        if counts {
+            let val = get(csrs, index);
+            set(csrs, index, val.wrapping_add(1));
+        }
+    }
+}
+
+// Legalizes a write to an mhpmevent CSR, an unknown event counts nothing.
+pub fn legalize_event(event: u32) -> u32 {
+    if event < EVENTS { event } else { 0 }
+}
+
+// The index of the machine counter behind the address of an mcycle, minstret or
+// mhpmcounter CSR or their high halves, if it is one.
+pub fn machine_counter(addr: u16) -> Option<u16> {
+    match addr {
+        csr::MCYCLE..=csr::MHPMCOUNTER31 => Some(addr - csr::MCYCLE),
+        csr::MCYCLEH..=csr::MHPMCOUNTER31H => Some(addr - csr::MCYCLEH),
+        _ => None,
+    }
+}
+
+// Whether the privilege level can read the user level view of a counter at the
+// address. Supervisor mode needs its bit set in mcounteren, and user mode in
+// scounteren as well. The rest of the CSRs are not counters and are left alone.
+pub fn is_accessible(csrs: &Csrs, addr: u16, privilege: Privilege) -> bool {
+    let bit = match addr {
+        csr::CYCLE..=csr::HPMCOUNTER31 => 1 << (addr - csr::CYCLE),
+        csr::CYCLEH..=csr::HPMCOUNTER31H => 1 << (addr - csr::CYCLEH),
+        _ => return true,
+    };
+
+    let mcounteren = csrs.get(csr::MCOUNTEREN);
+    let scounteren = csrs.get(csr::SCOUNTEREN);
+    match privilege {
+        Privilege::Machine => true,
+        Privilege::Supervisor => mcounteren & bit != 0,
+        Privilege::User => mcounteren & scounteren & bit != 0,
+    }
+}
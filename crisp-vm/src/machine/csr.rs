@@ -0,0 +1,603 @@
+use crate::machine::{counters, pmp, vector};
+
+// Addresses of the implemented control and status registers.
+pub const FFLAGS: u16 = 0x001;
+pub const FRM: u16 = 0x002;
+pub const FCSR: u16 = 0x003;
+
+pub const VSTART: u16 = 0x008;
+pub const VXSAT: u16 = 0x009;
+pub const VXRM: u16 = 0x00A;
+pub const VCSR: u16 = 0x00F;
+
+pub const SEED: u16 = 0x015;
+pub const JVT: u16 = 0x017;
+
+pub const MVENDORID: u16 = 0xF11;
+pub const MARCHID: u16 = 0xF12;
+pub const MIMPID: u16 = 0xF13;
+pub const MHARTID: u16 = 0xF14;
+pub const MCONFIGPTR: u16 = 0xF15;
+
+pub const CYCLE: u16 = 0xC00;
+pub const TIME: u16 = 0xC01;
+pub const HPMCOUNTER31: u16 = 0xC1F;
+pub const CYCLEH: u16 = 0xC80;
+pub const TIMEH: u16 = 0xC81;
+pub const HPMCOUNTER31H: u16 = 0xC9F;
+
+pub const VL: u16 = 0xC20;
+pub const VTYPE: u16 = 0xC21;
+pub const VLENB: u16 = 0xC22;
+
+pub const SSTATUS: u16 = 0x100;
+pub const SIE: u16 = 0x104;
+pub const STVEC: u16 = 0x105;
+pub const SCOUNTEREN: u16 = 0x106;
+
+pub const SENVCFG: u16 = 0x10A;
+
+pub const SSCRATCH: u16 = 0x140;
+pub const SEPC: u16 = 0x141;
+pub const SCAUSE: u16 = 0x142;
+pub const STVAL: u16 = 0x143;
+pub const SIP: u16 = 0x144;
+
+pub const SATP: u16 = 0x180;
+
+pub const MSTATUS: u16 = 0x300;
+pub const MISA: u16 = 0x301;
+pub const MEDELEG: u16 = 0x302;
+pub const MIDELEG: u16 = 0x303;
+pub const MIE: u16 = 0x304;
+pub const MTVEC: u16 = 0x305;
+pub const MCOUNTEREN: u16 = 0x306;
+pub const MSTATUSH: u16 = 0x310;
+
+pub const MENVCFG: u16 = 0x30A;
+pub const MENVCFGH: u16 = 0x31A;
+
+pub const MSECCFG: u16 = 0x747;
+
+pub const MCOUNTINHIBIT: u16 = 0x320;
+pub const MHPMEVENT3: u16 = 0x323;
+pub const MHPMEVENT31: u16 = 0x33F;
+
+pub const MSCRATCH: u16 = 0x340;
+pub const MEPC: u16 = 0x341;
+pub const MCAUSE: u16 = 0x342;
+pub const MTVAL: u16 = 0x343;
+pub const MIP: u16 = 0x344;
+
+pub const PMPCFG0: u16 = 0x3A0;
+const PMPCFG1: u16 = 0x3A1;
+const PMPCFG2: u16 = 0x3A2;
+pub const PMPCFG3: u16 = 0x3A3;
+pub const PMPADDR0: u16 = 0x3B0;
+pub const PMPADDR15: u16 = 0x3BF;
+
+pub const MCYCLE: u16 = 0xB00;
+pub const MHPMCOUNTER31: u16 = 0xB1F;
+pub const MCYCLEH: u16 = 0xB80;
+pub const MHPMCOUNTER31H: u16 = 0xB9F;
+
+// Fields of mstatus.
+pub const MSTATUS_SIE: u32 = 1 << 1;
+pub const MSTATUS_MIE: u32 = 1 << 3;
+pub const MSTATUS_SPIE: u32 = 1 << 5;
+pub const MSTATUS_MPIE: u32 = 1 << 7;
+pub const MSTATUS_SPP: u32 = 1 << 8;
+pub const MSTATUS_VS: u32 = 0b11 << 9;
+pub const MSTATUS_MPP: u32 = 0b11 << 11;
+pub const MSTATUS_FS: u32 = 0b11 << 13;
+pub const MSTATUS_MPRV: u32 = 1 << 17;
+pub const MSTATUS_SUM: u32 = 1 << 18;
+pub const MSTATUS_MXR: u32 = 1 << 19;
+pub const MSTATUS_TVM: u32 = 1 << 20;
+pub const MSTATUS_TW: u32 = 1 << 21;
+pub const MSTATUS_TSR: u32 = 1 << 22;
+pub const MSTATUS_SD: u32 = 1 << 31;
+
+// The XLEN of user (UXL) and supervisor mode (SXL) in mstatus, which are only there
+// on RV64 and hardwired to 64 bits (2).
+const MSTATUS_UXL: u64 = 0b11 << 32;
+const MSTATUS_SXL: u64 = 0b11 << 34;
+const MSTATUS_XL64: u64 = (0b10 << 32) | (0b10 << 34);
+
+// SD is the top bit of mstatus, bit 63 on RV64 where bit 31 is reserved.
+const MSTATUS_SD_RV64: u64 = 1 << 63;
+
+// The fields of mstatus that are visible through sstatus.
+const SSTATUS_MASK: u64 = (MSTATUS_SIE
+    | MSTATUS_SPIE
+    | MSTATUS_SPP
+    | MSTATUS_VS
+    | MSTATUS_FS
+    | MSTATUS_SUM
+    | MSTATUS_MXR
+    | MSTATUS_SD) as u64;
+
+// Fields of fcsr, the accrued exception flags and the dynamic rounding mode.
+const FCSR_FFLAGS: u64 = 0b11111;
+const FCSR_FRM: u64 = 0b111 << 5;
+
+// Fields of vcsr, the accrued saturation flag and the fixed point rounding mode.
+const VCSR_VXSAT: u64 = 0b1;
+const VCSR_VXRM: u64 = 0b11 << 1;
+
+// The mask of the CSRs that stay 32 bits wide whatever XLEN is.
+const WORD: u64 = u32::MAX as u64;
+
+// Fields of satp, the Sv32 mode along with the physical page number of the root page
+// table.
+pub const SATP_MODE: u32 = 1 << 31;
+pub const SATP_PPN: u32 = 0x003f_ffff;
+
+// Supervisor and machine software, timer and external interrupt bits of mie and mip.
+pub const MIP_SSIP: u32 = 1 << 1;
+pub const MIP_MSIP: u32 = 1 << 3;
+pub const MIP_STIP: u32 = 1 << 5;
+pub const MIP_MTIP: u32 = 1 << 7;
+pub const MIP_SEIP: u32 = 1 << 9;
+pub const MIP_MEIP: u32 = 1 << 11;
+
+// Whether user and supervisor mode can access the seed CSR.
+pub const MSECCFG_USEED: u32 = 1 << 8;
+pub const MSECCFG_SSEED: u32 = 1 << 9;
+
+// The base of the jump table in jvt, the mode below it is hardwired to 0, the only
+// mode there is.
+pub const JVT_BASE: u32 = !0x3f;
+
+// Fields of menvcfg and senvcfg that enable the cache block operations for the lower
+// privilege levels, invalidate (CBIE), clean and flush (CBCFE) and zero (CBZE). The
+// value 0b10 of CBIE is reserved.
+pub const ENVCFG_CBIE: u32 = 0b11 << 4;
+pub const ENVCFG_CBCFE: u32 = 1 << 6;
+pub const ENVCFG_CBZE: u32 = 1 << 7;
+const ENVCFG_MASK: u64 = (ENVCFG_CBIE | ENVCFG_CBCFE | ENVCFG_CBZE) as u64;
+
+// The exceptions that can be delegated, all of them but the environment call from
+// machine mode and the reserved codes 10 and 14.
+const MEDELEG_MASK: u64 = 0xb3ff;
+
+// The supervisor interrupts are the only ones that can be delegated.
+const MIDELEG_MASK: u64 = (MIP_SSIP | MIP_STIP | MIP_SEIP) as u64;
+
+// The bit of the C extension in misa, turning it off makes the instructions 4 byte
+// aligned.
+pub const MISA_C: u32 = ext('C');
+
+// MXL of 1 for a 32 bit machine along with the bits of the supported extensions.
+const MISA_VALUE: u64 = ((1 << 30)
+    | ext('I')
+    | ext('M')
+    | ext('A')
+    | ext('F')
+    | ext('D')
+    | ext('C')
+    | ext('S')
+    | ext('U')) as u64;
+
+// MXL of 2 for a 64 bit machine, which has neither the F nor the D extension yet.
+const MISA_VALUE_RV64: u64 =
+    (2 << 62) | (ext('I') | ext('M') | ext('A') | ext('C') | ext('S') | ext('U')) as u64;
+
+// The bits of the floating point extensions in misa, mstatus.FS is only there along
+// with one of them.
+const MISA_FP: u64 = (ext('F') | ext('D')) as u64;
+
+// Describes where a CSR is stored and the bits of it that can be read or written.
+// Bits outside of the write mask keep their value, which lets the hardwired bits of
+// a CSR hold their legal values for good.
+#[derive(Debug, Clone, Copy)]
+struct Spec {
+    // The address of the register that backs this CSR. Views of other CSRs share the
+    // storage of the register they are a view of.
+    storage: u16,
+    read: u64,
+    write: u64,
+
+    // Where the field a view exposes starts in its storage, the masks are in the
+    // layout of the storage.
+    shift: u32,
+}
+
+impl Spec {
+    const fn new(storage: u16, read: u64, write: u64) -> Self {
+        Spec {
+            storage,
+            read,
+            write,
+            shift: 0,
+        }
+    }
+
+    const fn shifted(self, shift: u32) -> Self {
+        Spec { shift, ..self }
+    }
+}
+
+// Returns the spec of a CSR if it is implemented.
+fn lookup(addr: u16) -> Option<Spec> {
+    let spec = match addr {
+        // Floating point control and status, fflags and frm are views of fcsr.
+        FFLAGS => Spec::new(FCSR, FCSR_FFLAGS, FCSR_FFLAGS),
+        FRM => Spec::new(FCSR, FCSR_FRM, FCSR_FRM).shifted(5),
+        FCSR => Spec::new(addr, FCSR_FFLAGS | FCSR_FRM, FCSR_FFLAGS | FCSR_FRM),
+
+        // Vector control and status, vxsat and vxrm are views of vcsr. vstart holds
+        // any element index of the largest VLEN. vl, vtype and vlenb are read only,
+        // they are set by the vector unit.
+        VSTART => Spec::new(addr, WORD, 0xffff),
+        VXSAT => Spec::new(VCSR, VCSR_VXSAT, VCSR_VXSAT),
+        VXRM => Spec::new(VCSR, VCSR_VXRM, VCSR_VXRM).shifted(1),
+        VCSR => Spec::new(addr, VCSR_VXSAT | VCSR_VXRM, VCSR_VXSAT | VCSR_VXRM),
+        VL | VTYPE | VLENB => Spec::new(addr, WORD, 0),
+
+        // The entropy source, every read of seed returns fresh bits from the state of
+        // the hart, it only has a slot here for the access checks.
+        SEED => Spec::new(addr, 0, 0),
+
+        // The jump table of Zcmt.
+        JVT => Spec::new(addr, JVT_BASE as u64, JVT_BASE as u64),
+
+        // Machine information registers, all of them are read only zero.
+        MVENDORID | MARCHID | MIMPID | MHARTID | MCONFIGPTR => Spec::new(addr, 0, 0),
+
+        // Supervisor trap setup, sstatus and sie are views of mstatus and mie.
+        SSTATUS => Spec::new(MSTATUS, SSTATUS_MASK, SSTATUS_MASK),
+        SIE => Spec::new(MIE, MIDELEG_MASK, MIDELEG_MASK),
+        STVEC => Spec::new(addr, u64::MAX, !0b10),
+        SCOUNTEREN => Spec::new(addr, WORD, WORD),
+
+        // Supervisor configuration, only the cache block operations can be configured.
+        SENVCFG => Spec::new(addr, ENVCFG_MASK, ENVCFG_MASK),
+
+        // Supervisor trap handling, sip is a view of mip where only the software
+        // interrupt can be raised or cleared.
+        SSCRATCH => Spec::new(addr, u64::MAX, u64::MAX),
+        SEPC => Spec::new(addr, u64::MAX, !0b1),
+        SCAUSE => Spec::new(addr, u64::MAX, u64::MAX),
+        STVAL => Spec::new(addr, u64::MAX, u64::MAX),
+        SIP => Spec::new(MIP, MIDELEG_MASK, MIP_SSIP as u64),
+
+        // Both the bare and the Sv32 modes are supported. There are no address space
+        // ids, so, the ASID field is hardwired to zero. Only the bare mode is there on
+        // RV64.
+        SATP => Spec::new(addr, WORD, (SATP_MODE | SATP_PPN) as u64),
+
+        // Machine trap setup.
+        MSTATUS => Spec::new(
+            addr,
+            u64::MAX,
+            SSTATUS_MASK
+                | (MSTATUS_MIE
+                    | MSTATUS_MPIE
+                    | MSTATUS_MPP
+                    | MSTATUS_MPRV
+                    | MSTATUS_FS
+                    | MSTATUS_VS
+                    | MSTATUS_TVM
+                    | MSTATUS_TW
+                    | MSTATUS_TSR) as u64,
+        ),
+        MSTATUSH => Spec::new(addr, 0, 0),
+        // misa is WARL, only the C extension can be turned off, writes to the rest of
+        // the extensions are ignored.
+        MISA => Spec::new(addr, u64::MAX, MISA_C as u64),
+        MEDELEG => Spec::new(addr, u64::MAX, MEDELEG_MASK),
+        MIDELEG => Spec::new(addr, u64::MAX, MIDELEG_MASK),
+        MIE => Spec::new(
+            addr,
+            u64::MAX,
+            MIDELEG_MASK | (MIP_MSIP | MIP_MTIP | MIP_MEIP) as u64,
+        ),
+        // Only the direct (0) and vectored (1) modes are legal.
+        MTVEC => Spec::new(addr, u64::MAX, !0b10),
+        MCOUNTEREN => Spec::new(addr, WORD, WORD),
+
+        // Machine configuration, only the cache block operations can be configured.
+        MENVCFG => Spec::new(addr, ENVCFG_MASK, ENVCFG_MASK),
+        MENVCFGH => Spec::new(addr, 0, 0),
+
+        // Machine counter setup, time can not be inhibited. The events of the
+        // hpmcounters are legalized on writes.
+        MCOUNTINHIBIT => Spec::new(addr, WORD, WORD & !0b10),
+        MHPMEVENT3..=MHPMEVENT31 => Spec::new(addr, WORD, WORD),
+
+        // Machine security configuration, only the access to seed can be configured.
+        MSECCFG => Spec::new(
+            addr,
+            (MSECCFG_USEED | MSECCFG_SSEED) as u64,
+            (MSECCFG_USEED | MSECCFG_SSEED) as u64,
+        ),
+
+        // Machine trap handling.
+        MSCRATCH => Spec::new(addr, u64::MAX, u64::MAX),
+        // Instructions are at least 2 byte aligned with the C extension.
+        MEPC => Spec::new(addr, u64::MAX, !0b1),
+        MCAUSE => Spec::new(addr, u64::MAX, u64::MAX),
+        MTVAL => Spec::new(addr, u64::MAX, u64::MAX),
+        // The machine pending bits are driven by the interrupt sources and are read
+        // only, the supervisor ones can be raised by machine mode software.
+        MIP => Spec::new(addr, u64::MAX, MIDELEG_MASK),
+
+        // Machine memory protection, the locks and the WARL fields of the entries are
+        // handled on writes.
+        PMPCFG0..=PMPCFG3 => Spec::new(addr, WORD, WORD),
+        PMPADDR0..=PMPADDR15 => Spec::new(addr, WORD, WORD),
+
+        // Machine counters, there is no machine level time CSR.
+        MCYCLE..=MHPMCOUNTER31 | MCYCLEH..=MHPMCOUNTER31H if addr & 0x7f != 1 => {
+            Spec::new(addr, WORD, WORD)
+        }
+
+        // The user level counters are read only views of the machine ones. time is a
+        // view of the mtime of the CLINT, kept in the unused slot of the machine
+        // counters.
+        CYCLE..=HPMCOUNTER31 | CYCLEH..=HPMCOUNTER31H => Spec::new(addr - 0x100, WORD, 0),
+
+        _ => return None,
+    };
+
+    Some(spec)
+}
+
+// The CSR file of a hart. It only knows which CSRs exist and how their values can
+// change, the access checks based on the privilege level are up to the caller. The
+// values are XLEN bits wide.
+pub struct Csrs {
+    values: [u64; 4096],
+    xlen: u32,
+
+    // Whether there is a vector unit, mstatus.VS is only there along with it.
+    vector: bool,
+}
+
+impl Default for Csrs {
+    fn default() -> Self {
+        let mut values = [0; 4096];
+
+        values[MISA as usize] = MISA_VALUE;
+        // An mret without any setup stays in machine mode.
+        values[MSTATUS as usize] = MSTATUS_MPP as u64;
+        // The vector unit starts without a legal setting.
+        values[VTYPE as usize] = vector::VTYPE_VILL as u64;
+
+        Csrs {
+            values,
+            xlen: 32,
+            vector: true,
+        }
+    }
+}
+
+impl Csrs {
+    // Read the value of a CSR, None if it is not implemented. On RV64 the counters,
+    // pmpcfg0 and pmpcfg2 hold the CSR above them as their upper half, which is a CSR
+    // of its own on RV32.
+    pub fn read(&self, addr: u16) -> Option<u64> {
+        let spec = self.spec(addr)?;
+        let val = (self.values[spec.storage as usize] & spec.read) >> spec.shift;
+
+        match self.upper_half(addr) {
+            Some(upper) => Some(val | self.read(upper)? << 32),
+            None => Some(val),
+        }
+    }
+
+    // Write the value of a CSR while leaving the bits outside of its write mask as
+    // they are. Returns None if the CSR is not implemented.
+    pub fn write(&mut self, addr: u16, val: u64) -> Option<()> {
+        let spec = self.spec(addr)?;
+
+        if let Some(upper) = self.upper_half(addr) {
+            self.write(upper, val >> 32)?;
+        }
+
+        // The address of a locked PMP entry ignores writes.
+        if matches!(addr, PMPADDR0..=PMPADDR15) && pmp::is_addr_locked(self, addr - PMPADDR0) {
+            return Some(());
+        }
+
+        let sd = match self.xlen {
+            32 => MSTATUS_SD as u64,
+            _ => MSTATUS_SD_RV64,
+        };
+
+        let current = &mut self.values[spec.storage as usize];
+        let mut val = (*current & !spec.write) | ((val << spec.shift) & spec.write);
+
+        // MPP is WARL, writing the reserved privilege level (0b10) to it keeps the
+        // previous one.
+        let mpp = MSTATUS_MPP as u64;
+        if spec.storage == MSTATUS && val & mpp == 0b10 << 11 {
+            val = (val & !mpp) | (*current & mpp);
+        }
+
+        // CBIE is WARL, writing the reserved value to it keeps the previous one.
+        let cbie = ENVCFG_CBIE as u64;
+        if matches!(addr, MENVCFG | SENVCFG) && val & cbie == 0b10 << 4 {
+            val = (val & !cbie) | (*current & cbie);
+        }
+
+        // SD is read only and sums up whether any of the extension states is dirty.
+        if spec.storage == MSTATUS {
+            let (fs, vs) = (MSTATUS_FS as u64, MSTATUS_VS as u64);
+            val &= !(MSTATUS_SD as u64 | MSTATUS_SD_RV64);
+            if val & fs == fs || val & vs == vs {
+                val |= sd;
+            }
+        }
+
+        if matches!(addr, PMPCFG0..=PMPCFG3) {
+            val = pmp::legalize_cfg(*current as u32, val as u32) as u64;
+        }
+
+        if matches!(addr, MHPMEVENT3..=MHPMEVENT31) {
+            val = counters::legalize_event(val as u32) as u64;
+        }
+
+        *current = val;
+
+        Some(())
+    }
+
+    // Get the lower 32 bits of a CSR the machine itself relies on, where all of the
+    // fields it looks at are. An unimplemented CSR reads as zero.
+    pub fn get(&self, addr: u16) -> u32 {
+        self.get_x(addr) as u32
+    }
+
+    // Set a CSR the machine itself relies on to a 32 bit value, writes to an
+    // unimplemented CSR are ignored.
+    pub fn set(&mut self, addr: u16, val: u32) {
+        self.set_x(addr, val as u64);
+    }
+
+    // Get the whole XLEN bits of a CSR the machine itself relies on.
+    pub fn get_x(&self, addr: u16) -> u64 {
+        self.read(addr).unwrap_or_default()
+    }
+
+    // Set the whole XLEN bits of a CSR the machine itself relies on.
+    pub fn set_x(&mut self, addr: u16, val: u64) {
+        self.write(addr, val);
+    }
+
+    // Set the XLEN of the hart, which misa and the XLEN fields of mstatus report.
+    pub fn set_xlen(&mut self, xlen: u32) {
+        let (misa, xl) = match xlen {
+            64 => (MISA_VALUE_RV64, MSTATUS_XL64),
+            _ => (MISA_VALUE, 0),
+        };
+
+        let status = &mut self.values[MSTATUS as usize];
+        *status = (*status & !(MSTATUS_UXL | MSTATUS_SXL)) | xl;
+        self.values[MISA as usize] = misa;
+        self.xlen = xlen;
+    }
+
+    // Make the base ISA RV32E, which misa reports with the E bit in place of the I one.
+    pub fn set_rve(&mut self) {
+        let misa = &mut self.values[MISA as usize];
+        *misa = (*misa & !(ext('I') as u64)) | ext('E') as u64;
+    }
+
+    // Raise or clear interrupt pending bits in mip, including the read only ones that
+    // are driven by the interrupt sources.
+    pub fn set_pending(&mut self, bits: u32, pending: bool) {
+        let mip = &mut self.values[MIP as usize];
+        if pending {
+            *mip |= bits as u64;
+        } else {
+            *mip &= !(bits as u64);
+        }
+    }
+
+    // Set vl and vtype, which are read only for the instructions other than the ones
+    // that configure the vector unit.
+    pub fn set_vector_config(&mut self, vl: u32, vtype: u32) {
+        self.values[VL as usize] = vl as u64;
+        self.values[VTYPE as usize] = vtype as u64;
+    }
+
+    // Set whether the machine has a vector unit and vlenb, the size of its vector
+    // registers.
+    pub fn set_vector_unit(&mut self, vector: bool, vlenb: u32) {
+        self.values[VLENB as usize] = vlenb as u64;
+        self.vector = vector;
+    }
+
+    // Set the value of the time CSR, which mirrors mtime.
+    pub fn set_time(&mut self, time: u64) {
+        self.values[(TIME - 0x100) as usize] = time & WORD;
+        self.values[(TIMEH - 0x100) as usize] = time >> 32;
+    }
+
+    // The spec of a CSR for the current state of the CSR file. sie and sip only
+    // expose the interrupts that are delegated to supervisor mode, and mepc and sepc
+    // hide the bit 1 of their address while the instructions are 4 byte aligned. FS
+    // and VS are read only zero unless misa has a floating point extension and the
+    // machine a vector unit. On RV32 every CSR is 32 bits wide, on RV64 satp is stuck
+    // in the bare mode as there is no Sv39.
+    fn spec(&self, addr: u16) -> Option<Spec> {
+        let mut spec = lookup(addr)?;
+
+        if matches!(addr, MEPC | SEPC) && self.values[MISA as usize] & MISA_C as u64 == 0 {
+            spec.read &= !0b10;
+        }
+
+        if matches!(addr, SIE | SIP) {
+            let mideleg = self.values[MIDELEG as usize];
+            spec.read &= mideleg;
+            spec.write &= mideleg;
+        }
+
+        if matches!(addr, MSTATUS | SSTATUS) {
+            if self.values[MISA as usize] & MISA_FP == 0 {
+                spec.write &= !(MSTATUS_FS as u64);
+            }
+
+            if !self.vector {
+                spec.write &= !(MSTATUS_VS as u64);
+            }
+        }
+
+        match (self.xlen, addr) {
+            (32, _) => {
+                spec.read &= WORD;
+                spec.write &= WORD;
+            }
+            (_, SATP) => spec.write = 0,
+            (_, SSTATUS) => {
+                spec.read = (spec.read & !(MSTATUS_SD as u64)) | MSTATUS_SD_RV64 | MSTATUS_UXL;
+            }
+            _ => {}
+        }
+
+        Some(spec)
+    }
+
+    // The CSR that holds the upper half of a CSR on RV64, for the ones that are split
+    // in two on RV32.
+    fn upper_half(&self, addr: u16) -> Option<u16> {
+        match addr {
+            _ if self.xlen != 64 => None,
+            CYCLE..=HPMCOUNTER31 | MCYCLE..=MHPMCOUNTER31 => Some(addr + 0x80),
+            PMPCFG0 | PMPCFG2 => Some(addr + 1),
+            _ => None,
+        }
+    }
+
+    // Whether a CSR is the upper half of one that is split in two on RV32, it is not
+    // there on RV64.
+    pub fn is_upper_half(&self, addr: u16) -> bool {
+        self.xlen == 64
+            && matches!(
+                addr,
+                MSTATUSH
+                    | MENVCFGH
+                    | PMPCFG1
+                    | PMPCFG3
+                    | CYCLEH..=HPMCOUNTER31H
+                    | MCYCLEH..=MHPMCOUNTER31H
+            )
+    }
+}
+
+// The misa bit for an extension.
+const fn ext(name: char) -> u32 {
+    1 << (name as u32 - 'A' as u32)
+}
+
+// Whether a CSR is read only according to its address.
+pub fn is_read_only(addr: u16) -> bool {
+    (addr >> 10) & 0b11 == 0b11
+}
+
+// The lowest privilege level that can access a CSR according to its address.
+pub fn privilege(addr: u16) -> u8 {
+    ((addr >> 8) & 0b11) as u8
+}
@@ -0,0 +1,470 @@
+use crate::machine::{
+    config::Extensions,
+    instructions::{Inst, decode::Error},
+};
+
+// Expands a 16 bit compressed (C extension) instruction into the 32 bit instruction
+// it is an alias of. The immediates are re-encoded into the raw widths used by the
+// 32 bit instructions so that they can be executed as is. The double precision loads
+// and stores give way to Zcmp and Zcmt, whose instructions have no 32 bit form. On
+// RV64 the single precision loads and stores are the doubleword ones instead, C.JAL
+// is C.ADDIW and the shifts take a 6 bit shamt.
+pub fn decode(inst: u16, xlen: u32, extensions: &Extensions) -> Result<Inst, Error> {
+    log::debug!(target: "pipe", "decoding compressed val:{:x}", inst);
+
+    let inst = inst as u32;
+    let f3 = select(inst, 13, 3);
+    let zcd = !extensions.zcmp && !extensions.zcmt;
+    let rv64 = xlen == 64;
+
+    match (inst & 0b11, f3) {
+        // Quadrant 0.
+        // The all zero instruction is defined to be illegal.
+        (0b00, 0b000) if inst == 0 => Err(Error::UnknownInst(inst)),
+
+        // C.ADDI4SPN - addi rd', x2, nzuimm[9:2]
+        (0b00, 0b000) => {
+            let imm = (select(inst, 11, 2) << 4)
+                | (select(inst, 7, 4) << 6)
+                | (select(inst, 6, 1) << 2)
+                | (select(inst, 5, 1) << 3);
+
+            match imm {
+                0 => Err(Error::UnknownInst(inst)),
+                imm => Ok(Inst::ADDI {
+                    rd: reg_low(inst, 2),
+                    rs1: 2,
+                    imm: imm as u16,
+                }),
+            }
+        }
+
+        // C.FLD - fld rd', offset[7:3](rs1')
+        (0b00, 0b001) if zcd => Ok(Inst::FLD {
+            rd: reg_low(inst, 2),
+            rs1: reg_low(inst, 7),
+            imm: offset_d(inst) as u16,
+        }),
+
+        // C.LW - lw rd', offset[6:2](rs1')
+        (0b00, 0b010) => Ok(Inst::LW {
+            rd: reg_low(inst, 2),
+            rs1: reg_low(inst, 7),
+            imm: offset_w(inst) as u16,
+        }),
+
+        // C.LD - ld rd', offset[7:3](rs1')
+        (0b00, 0b011) if rv64 => Ok(Inst::LD {
+            rd: reg_low(inst, 2),
+            rs1: reg_low(inst, 7),
+            imm: offset_d(inst) as u16,
+        }),
+
+        // C.FLW - flw rd', offset[6:2](rs1')
+        (0b00, 0b011) => Ok(Inst::FLW {
+            rd: reg_low(inst, 2),
+            rs1: reg_low(inst, 7),
+            imm: offset_w(inst) as u16,
+        }),
+
+        // C.FSD - fsd rs2', offset[7:3](rs1')
+        (0b00, 0b101) if zcd => Ok(Inst::FSD {
+            rs1: reg_low(inst, 7),
+            rs2: reg_low(inst, 2),
+            imm: offset_d(inst) as u16,
+        }),
+
+        // C.SW - sw rs2', offset[6:2](rs1')
+        (0b00, 0b110) => Ok(Inst::SW {
+            rs1: reg_low(inst, 7),
+            rs2: reg_low(inst, 2),
+            imm: offset_w(inst) as u16,
+        }),
+
+        // C.SD - sd rs2', offset[7:3](rs1')
+        (0b00, 0b111) if rv64 => Ok(Inst::SD {
+            rs1: reg_low(inst, 7),
+            rs2: reg_low(inst, 2),
+            imm: offset_d(inst) as u16,
+        }),
+
+        // C.FSW - fsw rs2', offset[6:2](rs1')
+        (0b00, 0b111) => Ok(Inst::FSW {
+            rs1: reg_low(inst, 7),
+            rs2: reg_low(inst, 2),
+            imm: offset_w(inst) as u16,
+        }),
+
+        // Quadrant 1.
+        // C.ADDI - addi rd, rd, nzimm[5:0]
+        // A rd of 0 is C.NOP and a zero immediate is a hint, both of them are harmless
+        // to execute as is.
+        (0b01, 0b000) => {
+            let rd = select(inst, 7, 5) as u8;
+            Ok(Inst::ADDI {
+                rd,
+                rs1: rd,
+                imm: resize(imm_ci(inst), 6, 12) as u16,
+            })
+        }
+
+        // C.ADDIW - addiw rd, rd, imm[5:0]
+        // A rd of 0 is reserved.
+        (0b01, 0b001) if rv64 => match select(inst, 7, 5) as u8 {
+            0 => Err(Error::UnknownInst(inst)),
+            rd => Ok(Inst::ADDIW {
+                rd,
+                rs1: rd,
+                imm: resize(imm_ci(inst), 6, 12) as u16,
+            }),
+        },
+
+        // C.JAL - jal x1, offset[11:1]
+        (0b01, 0b001) => Ok(Inst::JAL {
+            rd: 1,
+            imm: resize(offset_j(inst), 12, 21),
+        }),
+
+        // C.LI - addi rd, x0, imm[5:0]
+        (0b01, 0b010) => Ok(Inst::ADDI {
+            rd: select(inst, 7, 5) as u8,
+            rs1: 0,
+            imm: resize(imm_ci(inst), 6, 12) as u16,
+        }),
+
+        // C.ADDI16SP - addi x2, x2, nzimm[9:4]
+        (0b01, 0b011) if select(inst, 7, 5) == 2 => {
+            let imm = (select(inst, 12, 1) << 9)
+                | (select(inst, 6, 1) << 4)
+                | (select(inst, 5, 1) << 6)
+                | (select(inst, 3, 2) << 7)
+                | (select(inst, 2, 1) << 5);
+
+            match imm {
+                0 => Err(Error::UnknownInst(inst)),
+                imm => Ok(Inst::ADDI {
+                    rd: 2,
+                    rs1: 2,
+                    imm: resize(imm, 10, 12) as u16,
+                }),
+            }
+        }
+
+        // C.LUI - lui rd, nzimm[17:12]
+        (0b01, 0b011) => match imm_ci(inst) {
+            0 => Err(Error::UnknownInst(inst)),
+            imm => Ok(Inst::LUI {
+                rd: select(inst, 7, 5) as u8,
+                imm: resize(imm, 6, 20) << 12,
+            }),
+        },
+
+        (0b01, 0b100) => {
+            let rd = reg_low(inst, 7);
+            let rs2 = reg_low(inst, 2);
+            let shamt = imm_ci(inst) as u8;
+
+            match (select(inst, 10, 2), select(inst, 12, 1), select(inst, 5, 2)) {
+                // C.SRLI - srli rd', rd', shamt[5:0]
+                // shamt[5] must be zero on RV32.
+                (0b00, 0, _) => Ok(Inst::SRLI { rd, rs1: rd, shamt }),
+                (0b00, 1, _) if rv64 => Ok(Inst::SRLI { rd, rs1: rd, shamt }),
+
+                // C.SRAI - srai rd', rd', shamt[5:0]
+                (0b01, 0, _) => Ok(Inst::SRAI { rd, rs1: rd, shamt }),
+                (0b01, 1, _) if rv64 => Ok(Inst::SRAI { rd, rs1: rd, shamt }),
+
+                // C.ANDI - andi rd', rd', imm[5:0]
+                (0b10, _, _) => Ok(Inst::ANDI {
+                    rd,
+                    rs1: rd,
+                    imm: resize(imm_ci(inst), 6, 12) as u16,
+                }),
+
+                // C.SUB, C.XOR, C.OR & C.AND - op rd', rd', rs2'
+                (0b11, 0, 0b00) => Ok(Inst::SUB { rd, rs1: rd, rs2 }),
+                (0b11, 0, 0b01) => Ok(Inst::XOR { rd, rs1: rd, rs2 }),
+                (0b11, 0, 0b10) => Ok(Inst::OR { rd, rs1: rd, rs2 }),
+                (0b11, 0, 0b11) => Ok(Inst::AND { rd, rs1: rd, rs2 }),
+
+                // C.SUBW & C.ADDW - op rd', rd', rs2'
+                (0b11, 1, 0b00) if rv64 => Ok(Inst::SUBW { rd, rs1: rd, rs2 }),
+                (0b11, 1, 0b01) if rv64 => Ok(Inst::ADDW { rd, rs1: rd, rs2 }),
+
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // C.J - jal x0, offset[11:1]
+        (0b01, 0b101) => Ok(Inst::JAL {
+            rd: 0,
+            imm: resize(offset_j(inst), 12, 21),
+        }),
+
+        // C.BEQZ - beq rs1', x0, offset[8:1]
+        (0b01, 0b110) => Ok(Inst::BEQ {
+            rs1: reg_low(inst, 7),
+            rs2: 0,
+            imm: resize(offset_b(inst), 9, 13) as u16,
+        }),
+
+        // C.BNEZ - bne rs1', x0, offset[8:1]
+        (0b01, 0b111) => Ok(Inst::BNE {
+            rs1: reg_low(inst, 7),
+            rs2: 0,
+            imm: resize(offset_b(inst), 9, 13) as u16,
+        }),
+
+        // Quadrant 2.
+        // C.SLLI - slli rd, rd, shamt[5:0]
+        // shamt[5] must be zero on RV32.
+        (0b10, 0b000) if rv64 || select(inst, 12, 1) == 0 => {
+            let rd = select(inst, 7, 5) as u8;
+            Ok(Inst::SLLI {
+                rd,
+                rs1: rd,
+                shamt: imm_ci(inst) as u8,
+            })
+        }
+
+        // C.FLDSP - fld rd, offset[8:3](x2)
+        (0b10, 0b001) if zcd => Ok(Inst::FLD {
+            rd: select(inst, 7, 5) as u8,
+            rs1: 2,
+            imm: offset_ldsp(inst) as u16,
+        }),
+
+        // C.LWSP - lw rd, offset[7:2](x2)
+        (0b10, 0b010) => match select(inst, 7, 5) as u8 {
+            0 => Err(Error::UnknownInst(inst)),
+            rd => Ok(Inst::LW {
+                rd,
+                rs1: 2,
+                imm: offset_lwsp(inst) as u16,
+            }),
+        },
+
+        // C.LDSP - ld rd, offset[8:3](x2)
+        (0b10, 0b011) if rv64 => match select(inst, 7, 5) as u8 {
+            0 => Err(Error::UnknownInst(inst)),
+            rd => Ok(Inst::LD {
+                rd,
+                rs1: 2,
+                imm: offset_ldsp(inst) as u16,
+            }),
+        },
+
+        // C.FLWSP - flw rd, offset[7:2](x2)
+        (0b10, 0b011) => Ok(Inst::FLW {
+            rd: select(inst, 7, 5) as u8,
+            rs1: 2,
+            imm: offset_lwsp(inst) as u16,
+        }),
+
+        (0b10, 0b100) => {
+            let rs1 = select(inst, 7, 5) as u8;
+            let rs2 = select(inst, 2, 5) as u8;
+
+            match (select(inst, 12, 1), rs1, rs2) {
+                // C.JR - jalr x0, 0(rs1)
+                (0, 0, 0) => Err(Error::UnknownInst(inst)),
+                (0, rs1, 0) => Ok(Inst::JALR { rd: 0, rs1, imm: 0 }),
+
+                // C.MV - add rd, x0, rs2
+                (0, rd, rs2) => Ok(Inst::ADD { rd, rs1: 0, rs2 }),
+
+                // C.EBREAK
+                (1, 0, 0) => Ok(Inst::EBREAK),
+
+                // C.JALR - jalr x1, 0(rs1)
+                (1, rs1, 0) => Ok(Inst::JALR { rd: 1, rs1, imm: 0 }),
+
+                // C.ADD - add rd, rd, rs2
+                (_, rd, rs2) => Ok(Inst::ADD { rd, rs1: rd, rs2 }),
+            }
+        }
+
+        // C.FSDSP - fsd rs2, offset[8:3](x2)
+        (0b10, 0b101) if zcd => Ok(Inst::FSD {
+            rs1: 2,
+            rs2: select(inst, 2, 5) as u8,
+            imm: offset_sdsp(inst) as u16,
+        }),
+
+        (0b10, 0b101) => decode_zcm(inst, extensions),
+
+        // C.SWSP - sw rs2, offset[7:2](x2)
+        (0b10, 0b110) => Ok(Inst::SW {
+            rs1: 2,
+            rs2: select(inst, 2, 5) as u8,
+            imm: offset_swsp(inst) as u16,
+        }),
+
+        // C.SDSP - sd rs2, offset[8:3](x2)
+        (0b10, 0b111) if rv64 => Ok(Inst::SD {
+            rs1: 2,
+            rs2: select(inst, 2, 5) as u8,
+            imm: offset_sdsp(inst) as u16,
+        }),
+
+        // C.FSWSP - fsw rs2, offset[7:2](x2)
+        (0b10, 0b111) => Ok(Inst::FSW {
+            rs1: 2,
+            rs2: select(inst, 2, 5) as u8,
+            imm: offset_swsp(inst) as u16,
+        }),
+
+        _ => Err(Error::UnknownInst(inst)),
+    }
+}
+
+// Decodes the Zcmp and Zcmt instructions, which sit where C.FSDSP is without them.
+fn decode_zcm(inst: u32, extensions: &Extensions) -> Result<Inst, Error> {
+    match (select(inst, 10, 3), select(inst, 8, 5), select(inst, 5, 2)) {
+        // CM.JT - jump through jvt entry index[7:0]
+        // CM.JALT - jump through jvt entry index[7:0] and link in x1
+        // The indices below 32 are the jumps without link.
+        (0b000, _, _) if extensions.zcmt => match select(inst, 2, 8) as u8 {
+            index @ 0..32 => Ok(Inst::CMJT { index }),
+            index => Ok(Inst::CMJALT { index }),
+        },
+
+        // CM.MVSA01 - mv r1s', a0; mv r2s', a1
+        // The two registers have to differ.
+        (0b011, _, 0b01) if extensions.zcmp => match (sreg(inst, 7), sreg(inst, 2)) {
+            (r1s, r2s) if r1s == r2s => Err(Error::UnknownInst(inst)),
+            (r1s, r2s) => Ok(Inst::CMMVSA01 { r1s, r2s }),
+        },
+
+        // CM.MVA01S - mv a0, r1s'; mv a1, r2s'
+        (0b011, _, 0b11) if extensions.zcmp => Ok(Inst::CMMVA01S {
+            r1s: sreg(inst, 7),
+            r2s: sreg(inst, 2),
+        }),
+
+        // CM.PUSH, CM.POP, CM.POPRETZ & CM.POPRET - {reg_list}, stack_adj
+        // The register lists below 4 are reserved.
+        (0b110 | 0b111, f5, _) if extensions.zcmp && select(inst, 4, 4) >= 4 => {
+            let rlist = select(inst, 4, 4) as u8;
+            let stack_adj = stack_adj(inst);
+
+            match f5 {
+                0b11000 => Ok(Inst::CMPUSH { rlist, stack_adj }),
+                0b11010 => Ok(Inst::CMPOP { rlist, stack_adj }),
+                0b11100 => Ok(Inst::CMPOPRETZ { rlist, stack_adj }),
+                0b11110 => Ok(Inst::CMPOPRET { rlist, stack_adj }),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        _ => Err(Error::UnknownInst(inst)),
+    }
+}
+
+// The stack adjustment of the Zcmp pushes and pops, the space the register list
+// takes rounded up to 16 bytes plus spimm[5:4].
+#[inline]
+fn stack_adj(inst: u32) -> u32 {
+    let regs = match select(inst, 4, 4) {
+        15 => 13,
+        rlist => rlist - 3,
+    };
+
+    ((regs * 4 + 15) & !15) + (select(inst, 2, 2) << 4)
+}
+
+// The 3 bit register fields of the Zcmp moves address s0 to s7.
+#[inline]
+fn sreg(inst: u32, shift: u8) -> u8 {
+    match select(inst, shift, 3) as u8 {
+        r @ 0..2 => r + 8,
+        r => r + 16,
+    }
+}
+
+// The 3 bit register fields of compressed instructions address x8 to x15.
+#[inline]
+fn reg_low(inst: u32, shift: u8) -> u8 {
+    select(inst, shift, 3) as u8 + 8
+}
+
+// imm[5|4:0] of the CI format.
+#[inline]
+fn imm_ci(inst: u32) -> u32 {
+    (select(inst, 12, 1) << 5) | select(inst, 2, 5)
+}
+
+// offset[5:3|2|6] of C.LW and C.SW.
+#[inline]
+fn offset_w(inst: u32) -> u32 {
+    (select(inst, 10, 3) << 3) | (select(inst, 6, 1) << 2) | (select(inst, 5, 1) << 6)
+}
+
+// offset[5:3|7:6] of C.FLD, C.FSD, C.LD and C.SD.
+#[inline]
+fn offset_d(inst: u32) -> u32 {
+    (select(inst, 10, 3) << 3) | (select(inst, 5, 2) << 6)
+}
+
+// offset[5|4:2|7:6] of C.LWSP and C.FLWSP.
+#[inline]
+fn offset_lwsp(inst: u32) -> u32 {
+    (select(inst, 12, 1) << 5) | (select(inst, 4, 3) << 2) | (select(inst, 2, 2) << 6)
+}
+
+// offset[5:2|7:6] of C.SWSP and C.FSWSP.
+#[inline]
+fn offset_swsp(inst: u32) -> u32 {
+    (select(inst, 9, 4) << 2) | (select(inst, 7, 2) << 6)
+}
+
+// offset[5|4:3|8:6] of C.FLDSP and C.LDSP.
+#[inline]
+fn offset_ldsp(inst: u32) -> u32 {
+    (select(inst, 12, 1) << 5) | (select(inst, 5, 2) << 3) | (select(inst, 2, 3) << 6)
+}
+
+// offset[5:3|8:6] of C.FSDSP and C.SDSP.
+#[inline]
+fn offset_sdsp(inst: u32) -> u32 {
+    (select(inst, 10, 3) << 3) | (select(inst, 7, 3) << 6)
+}
+
+// offset[11|4|9:8|10|6|7|3:1|5] of C.J and C.JAL.
+#[inline]
+fn offset_j(inst: u32) -> u32 {
+    (select(inst, 12, 1) << 11)
+        | (select(inst, 11, 1) << 4)
+        | (select(inst, 9, 2) << 8)
+        | (select(inst, 8, 1) << 10)
+        | (select(inst, 7, 1) << 6)
+        | (select(inst, 6, 1) << 7)
+        | (select(inst, 3, 3) << 1)
+        | (select(inst, 2, 1) << 5)
+}
+
+// offset[8|4:3|7:6|2:1|5] of C.BEQZ and C.BNEZ.
+#[inline]
+fn offset_b(inst: u32) -> u32 {
+    (select(inst, 12, 1) << 8)
+        | (select(inst, 10, 2) << 3)
+        | (select(inst, 5, 2) << 6)
+        | (select(inst, 3, 2) << 1)
+        | (select(inst, 2, 1) << 5)
+}
+
+// Sign extends a from bit wide immediate to to bits, so that it can be stored in the
+// raw immediate fields of the 32 bit instructions.
+#[inline]
+fn resize(imm: u32, from: u8, to: u8) -> u32 {
+    if (imm >> (from - 1)) & 1 == 1 {
+        (imm | (u32::MAX << from)) & ((1 << to) - 1)
+    } else {
+        imm
+    }
+}
+
+#[inline]
+fn select(n: u32, shift: u8, width: u8) -> u32 {
+    (n >> shift) & ((1 << width) - 1)
+}
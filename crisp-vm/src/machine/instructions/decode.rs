@@ -1,14 +1,22 @@
 use thiserror::Error;
 
-use crate::machine::instructions::Inst;
+use crate::machine::instructions::{Inst, R4, V, VMem, VSrc};
 
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("unknown instruction")]
-    UnknownInst,
+    #[error("unknown instruction {0:x}")]
+    UnknownInst(u32),
 }
 
-// TODO: Both 0 and u32::MAX are illegal instructions.
+// The encoding of pause, a FENCE with only the predecessor writes (fence w, 0).
+const PAUSE: u32 = 0x0100_000f;
+
+// The fm, predecessor and successor fields of FENCE.TSO, the only FENCE with a fm
+// other than 0.
+const FENCE_TSO: u32 = 0b1000_0011_0011;
+
+// Decodes a 32 bit instruction. The encodings that are reserved, including the ones
+// reserved for future use that could be ignored, are rejected as unknown.
 pub fn decode(inst: u32) -> Result<Inst, Error> {
     log::debug!(target: "pipe", "decoding val:{:x}", inst);
     match inst & 0b1_111_111 {
@@ -44,7 +52,7 @@ pub fn decode(inst: u32) -> Result<Inst, Error> {
 
             match f3 {
                 0 => Ok(Inst::JALR { rd, rs1, imm }),
-                _ => Err(Error::UnknownInst),
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
@@ -65,7 +73,7 @@ pub fn decode(inst: u32) -> Result<Inst, Error> {
                 0b101 => Ok(Inst::BGE { rs1, rs2, imm }),
                 0b110 => Ok(Inst::BLTU { rs1, rs2, imm }),
                 0b111 => Ok(Inst::BGEU { rs1, rs2, imm }),
-                _ => Err(Error::UnknownInst),
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
@@ -79,7 +87,11 @@ pub fn decode(inst: u32) -> Result<Inst, Error> {
                 0b010 => Ok(Inst::LW { rs1, rd, imm }),
                 0b100 => Ok(Inst::LBU { rs1, rd, imm }),
                 0b101 => Ok(Inst::LHU { rs1, rd, imm }),
-                _ => Err(Error::UnknownInst),
+
+                // RV64I.
+                0b011 => Ok(Inst::LD { rd, rs1, imm }),
+                0b110 => Ok(Inst::LWU { rd, rs1, imm }),
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
@@ -94,7 +106,170 @@ pub fn decode(inst: u32) -> Result<Inst, Error> {
                 0 => Ok(Inst::SB { rs1, rs2, imm }),
                 1 => Ok(Inst::SH { rs1, rs2, imm }),
                 0b010 => Ok(Inst::SW { rs1, rs2, imm }),
-                _ => Err(Error::UnknownInst),
+                0b011 => Ok(Inst::SD { rs1, rs2, imm }),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // F, D and Zfh extension loads and stores.
+        0b0_000_111 => {
+            let (rd, f3, rs1, imm) = unpack_i(inst);
+
+            match f3 {
+                1 => Ok(Inst::FLH { rd, rs1, imm }),
+                0b010 => Ok(Inst::FLW { rd, rs1, imm }),
+                0b011 => Ok(Inst::FLD { rd, rs1, imm }),
+                0 | 0b101 | 0b110 | 0b111 => decode_vector_memory(inst, true),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        0b0_100_111 => {
+            let imm = (((inst >> 25) << 5) | ((inst >> 7) & 0b11_111)) as u16;
+            let f3 = select(inst, 12, 3) as u8;
+            let rs1 = select(inst, 15, 5) as u8;
+            let rs2 = select(inst, 20, 5) as u8;
+
+            match f3 {
+                1 => Ok(Inst::FSH { rs1, rs2, imm }),
+                0b010 => Ok(Inst::FSW { rs1, rs2, imm }),
+                0b011 => Ok(Inst::FSD { rs1, rs2, imm }),
+                0 | 0b101 | 0b110 | 0b111 => decode_vector_memory(inst, false),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // F, D and Zfh extension fused multiply adds (R4), the format of the operands is in the
+        // lowest two bits of the funct7 field.
+        0b1_000_011 | 0b1_000_111 | 0b1_001_011 | 0b1_001_111 => {
+            let r4 = R4 {
+                rd: select(inst, 7, 5) as u8,
+                rm: select(inst, 12, 3) as u8,
+                rs1: select(inst, 15, 5) as u8,
+                rs2: select(inst, 20, 5) as u8,
+                rs3: select(inst, 27, 5) as u8,
+            };
+            let fmt = select(inst, 25, 2);
+
+            match (inst & 0b1_111_111, fmt) {
+                (0b1_000_011, 0) => Ok(Inst::FMADDS(r4)),
+                (0b1_000_111, 0) => Ok(Inst::FMSUBS(r4)),
+                (0b1_001_011, 0) => Ok(Inst::FNMSUBS(r4)),
+                (0b1_001_111, 0) => Ok(Inst::FNMADDS(r4)),
+                (0b1_000_011, 1) => Ok(Inst::FMADDD(r4)),
+                (0b1_000_111, 1) => Ok(Inst::FMSUBD(r4)),
+                (0b1_001_011, 1) => Ok(Inst::FNMSUBD(r4)),
+                (0b1_001_111, 1) => Ok(Inst::FNMADDD(r4)),
+                (0b1_000_011, 0b10) => Ok(Inst::FMADDH(r4)),
+                (0b1_000_111, 0b10) => Ok(Inst::FMSUBH(r4)),
+                (0b1_001_011, 0b10) => Ok(Inst::FNMSUBH(r4)),
+                (0b1_001_111, 0b10) => Ok(Inst::FNMADDH(r4)),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // F, D and Zfh extension operations, the funct3 field holds the rounding mode for
+        // the ones that round. The Zfa instructions take unused rs2 or funct3 values of
+        // the encodings of the others.
+        0b1_010_011 => {
+            let rd = select(inst, 7, 5) as u8;
+            let rm = select(inst, 12, 3) as u8;
+            let rs1 = select(inst, 15, 5) as u8;
+            let rs2 = select(inst, 20, 5) as u8;
+            let f7 = select(inst, 25, 7) as u8;
+
+            match (f7, rs2, rm) {
+                (0b0_000_000, rs2, rm) => Ok(Inst::FADDS { rd, rs1, rs2, rm }),
+                (0b0_000_100, rs2, rm) => Ok(Inst::FSUBS { rd, rs1, rs2, rm }),
+                (0b0_001_000, rs2, rm) => Ok(Inst::FMULS { rd, rs1, rs2, rm }),
+                (0b0_001_100, rs2, rm) => Ok(Inst::FDIVS { rd, rs1, rs2, rm }),
+                (0b0_101_100, 0, rm) => Ok(Inst::FSQRTS { rd, rs1, rm }),
+                (0b0_010_000, rs2, 0) => Ok(Inst::FSGNJS { rd, rs1, rs2 }),
+                (0b0_010_000, rs2, 1) => Ok(Inst::FSGNJNS { rd, rs1, rs2 }),
+                (0b0_010_000, rs2, 0b010) => Ok(Inst::FSGNJXS { rd, rs1, rs2 }),
+                (0b0_010_100, rs2, 0) => Ok(Inst::FMINS { rd, rs1, rs2 }),
+                (0b0_010_100, rs2, 1) => Ok(Inst::FMAXS { rd, rs1, rs2 }),
+                (0b1_100_000, 0, rm) => Ok(Inst::FCVTWS { rd, rs1, rm }),
+                (0b1_100_000, 1, rm) => Ok(Inst::FCVTWUS { rd, rs1, rm }),
+                (0b1_110_000, 0, 0) => Ok(Inst::FMVXW { rd, rs1 }),
+                (0b1_010_000, rs2, 0b010) => Ok(Inst::FEQS { rd, rs1, rs2 }),
+                (0b1_010_000, rs2, 1) => Ok(Inst::FLTS { rd, rs1, rs2 }),
+                (0b1_010_000, rs2, 0) => Ok(Inst::FLES { rd, rs1, rs2 }),
+                (0b1_110_000, 0, 1) => Ok(Inst::FCLASSS { rd, rs1 }),
+                (0b1_101_000, 0, rm) => Ok(Inst::FCVTSW { rd, rs1, rm }),
+                (0b1_101_000, 1, rm) => Ok(Inst::FCVTSWU { rd, rs1, rm }),
+                (0b1_111_000, 0, 0) => Ok(Inst::FMVWX { rd, rs1 }),
+                (0b0_000_001, rs2, rm) => Ok(Inst::FADDD { rd, rs1, rs2, rm }),
+                (0b0_000_101, rs2, rm) => Ok(Inst::FSUBD { rd, rs1, rs2, rm }),
+                (0b0_001_001, rs2, rm) => Ok(Inst::FMULD { rd, rs1, rs2, rm }),
+                (0b0_001_101, rs2, rm) => Ok(Inst::FDIVD { rd, rs1, rs2, rm }),
+                (0b0_101_101, 0, rm) => Ok(Inst::FSQRTD { rd, rs1, rm }),
+                (0b0_010_001, rs2, 0) => Ok(Inst::FSGNJD { rd, rs1, rs2 }),
+                (0b0_010_001, rs2, 1) => Ok(Inst::FSGNJND { rd, rs1, rs2 }),
+                (0b0_010_001, rs2, 0b010) => Ok(Inst::FSGNJXD { rd, rs1, rs2 }),
+                (0b0_010_101, rs2, 0) => Ok(Inst::FMIND { rd, rs1, rs2 }),
+                (0b0_010_101, rs2, 1) => Ok(Inst::FMAXD { rd, rs1, rs2 }),
+                (0b0_100_000, 1, rm) => Ok(Inst::FCVTSD { rd, rs1, rm }),
+                (0b0_100_001, 0, rm) => Ok(Inst::FCVTDS { rd, rs1, rm }),
+                (0b1_010_001, rs2, 0b010) => Ok(Inst::FEQD { rd, rs1, rs2 }),
+                (0b1_010_001, rs2, 1) => Ok(Inst::FLTD { rd, rs1, rs2 }),
+                (0b1_010_001, rs2, 0) => Ok(Inst::FLED { rd, rs1, rs2 }),
+                (0b1_110_001, 0, 1) => Ok(Inst::FCLASSD { rd, rs1 }),
+                (0b1_100_001, 0, rm) => Ok(Inst::FCVTWD { rd, rs1, rm }),
+                (0b1_100_001, 1, rm) => Ok(Inst::FCVTWUD { rd, rs1, rm }),
+                (0b1_101_001, 0, rm) => Ok(Inst::FCVTDW { rd, rs1, rm }),
+                (0b1_101_001, 1, rm) => Ok(Inst::FCVTDWU { rd, rs1, rm }),
+                (0b0_000_010, rs2, rm) => Ok(Inst::FADDH { rd, rs1, rs2, rm }),
+                (0b0_000_110, rs2, rm) => Ok(Inst::FSUBH { rd, rs1, rs2, rm }),
+                (0b0_001_010, rs2, rm) => Ok(Inst::FMULH { rd, rs1, rs2, rm }),
+                (0b0_001_110, rs2, rm) => Ok(Inst::FDIVH { rd, rs1, rs2, rm }),
+                (0b0_101_110, 0, rm) => Ok(Inst::FSQRTH { rd, rs1, rm }),
+                (0b0_010_010, rs2, 0) => Ok(Inst::FSGNJH { rd, rs1, rs2 }),
+                (0b0_010_010, rs2, 1) => Ok(Inst::FSGNJNH { rd, rs1, rs2 }),
+                (0b0_010_010, rs2, 0b010) => Ok(Inst::FSGNJXH { rd, rs1, rs2 }),
+                (0b0_010_110, rs2, 0) => Ok(Inst::FMINH { rd, rs1, rs2 }),
+                (0b0_010_110, rs2, 1) => Ok(Inst::FMAXH { rd, rs1, rs2 }),
+                (0b0_100_000, 0b00_010, rm) => Ok(Inst::FCVTSH { rd, rs1, rm }),
+                (0b0_100_010, 0, rm) => Ok(Inst::FCVTHS { rd, rs1, rm }),
+                (0b0_100_001, 0b00_010, rm) => Ok(Inst::FCVTDH { rd, rs1, rm }),
+                (0b0_100_010, 1, rm) => Ok(Inst::FCVTHD { rd, rs1, rm }),
+                (0b1_010_010, rs2, 0b010) => Ok(Inst::FEQH { rd, rs1, rs2 }),
+                (0b1_010_010, rs2, 1) => Ok(Inst::FLTH { rd, rs1, rs2 }),
+                (0b1_010_010, rs2, 0) => Ok(Inst::FLEH { rd, rs1, rs2 }),
+                (0b1_110_010, 0, 1) => Ok(Inst::FCLASSH { rd, rs1 }),
+                (0b1_100_010, 0, rm) => Ok(Inst::FCVTWH { rd, rs1, rm }),
+                (0b1_100_010, 1, rm) => Ok(Inst::FCVTWUH { rd, rs1, rm }),
+                (0b1_101_010, 0, rm) => Ok(Inst::FCVTHW { rd, rs1, rm }),
+                (0b1_101_010, 1, rm) => Ok(Inst::FCVTHWU { rd, rs1, rm }),
+                (0b1_110_010, 0, 0) => Ok(Inst::FMVXH { rd, rs1 }),
+                (0b1_111_010, 0, 0) => Ok(Inst::FMVHX { rd, rs1 }),
+
+                // Zfa, rs1 is the index of the constant of fli.
+                (0b1_111_000, 1, 0) => Ok(Inst::FLIS { rd, index: rs1 }),
+                (0b1_111_001, 1, 0) => Ok(Inst::FLID { rd, index: rs1 }),
+                (0b1_111_010, 1, 0) => Ok(Inst::FLIH { rd, index: rs1 }),
+                (0b0_010_100, rs2, 0b010) => Ok(Inst::FMINMS { rd, rs1, rs2 }),
+                (0b0_010_100, rs2, 0b011) => Ok(Inst::FMAXMS { rd, rs1, rs2 }),
+                (0b0_010_101, rs2, 0b010) => Ok(Inst::FMINMD { rd, rs1, rs2 }),
+                (0b0_010_101, rs2, 0b011) => Ok(Inst::FMAXMD { rd, rs1, rs2 }),
+                (0b0_010_110, rs2, 0b010) => Ok(Inst::FMINMH { rd, rs1, rs2 }),
+                (0b0_010_110, rs2, 0b011) => Ok(Inst::FMAXMH { rd, rs1, rs2 }),
+                (0b0_100_000, 0b00_100, rm) => Ok(Inst::FROUNDS { rd, rs1, rm }),
+                (0b0_100_000, 0b00_101, rm) => Ok(Inst::FROUNDNXS { rd, rs1, rm }),
+                (0b0_100_001, 0b00_100, rm) => Ok(Inst::FROUNDD { rd, rs1, rm }),
+                (0b0_100_001, 0b00_101, rm) => Ok(Inst::FROUNDNXD { rd, rs1, rm }),
+                (0b0_100_010, 0b00_100, rm) => Ok(Inst::FROUNDH { rd, rs1, rm }),
+                (0b0_100_010, 0b00_101, rm) => Ok(Inst::FROUNDNXH { rd, rs1, rm }),
+                (0b1_010_000, rs2, 0b101) => Ok(Inst::FLTQS { rd, rs1, rs2 }),
+                (0b1_010_000, rs2, 0b100) => Ok(Inst::FLEQS { rd, rs1, rs2 }),
+                (0b1_010_001, rs2, 0b101) => Ok(Inst::FLTQD { rd, rs1, rs2 }),
+                (0b1_010_001, rs2, 0b100) => Ok(Inst::FLEQD { rd, rs1, rs2 }),
+                (0b1_010_010, rs2, 0b101) => Ok(Inst::FLTQH { rd, rs1, rs2 }),
+                (0b1_010_010, rs2, 0b100) => Ok(Inst::FLEQH { rd, rs1, rs2 }),
+                (0b1_100_001, 0b01_000, 1) => Ok(Inst::FCVTMODWD { rd, rs1 }),
+                (0b1_110_001, 1, 0) => Ok(Inst::FMVHXD { rd, rs1 }),
+                (0b1_011_001, rs2, 0) => Ok(Inst::FMVPDX { rd, rs1, rs2 }),
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
@@ -111,21 +286,75 @@ pub fn decode(inst: u32) -> Result<Inst, Error> {
                 0b110 => Ok(Inst::ORI { rd, rs1, imm }),
                 0b111 => Ok(Inst::ANDI { rd, rs1, imm }),
 
-                // Shift
+                // Shift, shamt[5] is the lowest bit of funct7 and only there on
+                // RV64, it is left to the execution to reject it on RV32. The bit
+                // manipulation instructions with a single source share the encoding
+                // of the shifts, with the operation in the shamt field.
                 0b001 => {
                     let shamt = (imm & 0b11_111) as u8;
-                    Ok(Inst::SLLI { rd, rs1, shamt })
+                    match (imm >> 5, shamt) {
+                        (0, shamt) => Ok(Inst::SLLI { rd, rs1, shamt }),
+                        (1, shamt) => Ok(Inst::SLLI {
+                            rd,
+                            rs1,
+                            shamt: shamt | 0b100_000,
+                        }),
+                        (0b0_110_000, 0) => Ok(Inst::CLZ { rd, rs1 }),
+                        (0b0_110_000, 1) => Ok(Inst::CTZ { rd, rs1 }),
+                        (0b0_110_000, 0b00_010) => Ok(Inst::CPOP { rd, rs1 }),
+                        (0b0_110_000, 0b00_100) => Ok(Inst::SEXTB { rd, rs1 }),
+                        (0b0_110_000, 0b00_101) => Ok(Inst::SEXTH { rd, rs1 }),
+                        (0b0_100_100, shamt) => Ok(Inst::BCLRI { rd, rs1, shamt }),
+                        (0b0_110_100, shamt) => Ok(Inst::BINVI { rd, rs1, shamt }),
+                        (0b0_010_100, shamt) => Ok(Inst::BSETI { rd, rs1, shamt }),
+                        (0b0_000_100, 0b01_111) => Ok(Inst::ZIP { rd, rs1 }),
+                        (0b0_001_000, 0) => Ok(Inst::SHA256SUM0 { rd, rs1 }),
+                        (0b0_001_000, 1) => Ok(Inst::SHA256SUM1 { rd, rs1 }),
+                        (0b0_001_000, 0b00_010) => Ok(Inst::SHA256SIG0 { rd, rs1 }),
+                        (0b0_001_000, 0b00_011) => Ok(Inst::SHA256SIG1 { rd, rs1 }),
+                        _ => Err(Error::UnknownInst(inst)),
+                    }
                 }
                 0b101 => {
                     let shamt = (imm & 0b11_111) as u8;
-                    match imm >> 5 {
-                        0 => Ok(Inst::SRLI { rd, rs1, shamt }),
-                        0b0100000 => Ok(Inst::SRAI { rd, rs1, shamt }),
-                        _ => Err(Error::UnknownInst),
+                    match (imm >> 5, shamt) {
+                        (0, shamt) => Ok(Inst::SRLI { rd, rs1, shamt }),
+                        (1, shamt) => Ok(Inst::SRLI {
+                            rd,
+                            rs1,
+                            shamt: shamt | 0b100_000,
+                        }),
+                        (0b0_100_000, shamt) => Ok(Inst::SRAI { rd, rs1, shamt }),
+                        (0b0_100_001, shamt) => Ok(Inst::SRAI {
+                            rd,
+                            rs1,
+                            shamt: shamt | 0b100_000,
+                        }),
+                        (0b0_110_000, shamt) => Ok(Inst::RORI { rd, rs1, shamt }),
+                        (0b0_010_100, 0b00_111) => Ok(Inst::ORCB { rd, rs1 }),
+                        (0b0_110_100, 0b11_000) => Ok(Inst::REV8 { rd, rs1 }),
+                        (0b0_100_100, shamt) => Ok(Inst::BEXTI { rd, rs1, shamt }),
+                        (0b0_110_100, 0b00_111) => Ok(Inst::BREV8 { rd, rs1 }),
+                        (0b0_000_100, 0b01_111) => Ok(Inst::UNZIP { rd, rs1 }),
+                        _ => Err(Error::UnknownInst(inst)),
                     }
                 }
 
-                _ => Err(Error::UnknownInst),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // I instructions on words, RV64 only.
+        0b0_011_011 => {
+            let (rd, f3, rs1, imm) = unpack_i(inst);
+            let shamt = (imm & 0b11_111) as u8;
+
+            match (f3, imm >> 5) {
+                (0, _) => Ok(Inst::ADDIW { rd, rs1, imm }),
+                (1, 0) => Ok(Inst::SLLIW { rd, rs1, shamt }),
+                (0b101, 0) => Ok(Inst::SRLIW { rd, rs1, shamt }),
+                (0b101, 0b0_100_000) => Ok(Inst::SRAIW { rd, rs1, shamt }),
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
@@ -148,27 +377,493 @@ pub fn decode(inst: u32) -> Result<Inst, Error> {
                 (0b101, 0b0_100_000) => Ok(Inst::SRA { rd, rs1, rs2 }),
                 (0b110, 0) => Ok(Inst::OR { rd, rs1, rs2 }),
                 (0b111, 0) => Ok(Inst::AND { rd, rs1, rs2 }),
-                _ => Err(Error::UnknownInst),
+
+                // M extension.
+                (0, 1) => Ok(Inst::MUL { rd, rs1, rs2 }),
+                (1, 1) => Ok(Inst::MULH { rd, rs1, rs2 }),
+                (0b010, 1) => Ok(Inst::MULHSU { rd, rs1, rs2 }),
+                (0b011, 1) => Ok(Inst::MULHU { rd, rs1, rs2 }),
+                (0b100, 1) => Ok(Inst::DIV { rd, rs1, rs2 }),
+                (0b101, 1) => Ok(Inst::DIVU { rd, rs1, rs2 }),
+                (0b110, 1) => Ok(Inst::REM { rd, rs1, rs2 }),
+                (0b111, 1) => Ok(Inst::REMU { rd, rs1, rs2 }),
+
+                // Zba.
+                (0b010, 0b0_010_000) => Ok(Inst::SH1ADD { rd, rs1, rs2 }),
+                (0b100, 0b0_010_000) => Ok(Inst::SH2ADD { rd, rs1, rs2 }),
+                (0b110, 0b0_010_000) => Ok(Inst::SH3ADD { rd, rs1, rs2 }),
+
+                // Zbb, zext.h is the RV32 encoding of pack with rs2 as x0.
+                (0b111, 0b0_100_000) => Ok(Inst::ANDN { rd, rs1, rs2 }),
+                (0b110, 0b0_100_000) => Ok(Inst::ORN { rd, rs1, rs2 }),
+                (0b100, 0b0_100_000) => Ok(Inst::XNOR { rd, rs1, rs2 }),
+                (0b110, 0b0_000_101) => Ok(Inst::MAX { rd, rs1, rs2 }),
+                (0b111, 0b0_000_101) => Ok(Inst::MAXU { rd, rs1, rs2 }),
+                (0b100, 0b0_000_101) => Ok(Inst::MIN { rd, rs1, rs2 }),
+                (0b101, 0b0_000_101) => Ok(Inst::MINU { rd, rs1, rs2 }),
+                (0b100, 0b0_000_100) if rs2 == 0 => Ok(Inst::ZEXTH { rd, rs1 }),
+                (0b100, 0b0_000_100) => Ok(Inst::PACK { rd, rs1, rs2 }),
+                (0b111, 0b0_000_100) => Ok(Inst::PACKH { rd, rs1, rs2 }),
+                (1, 0b0_110_000) => Ok(Inst::ROL { rd, rs1, rs2 }),
+                (0b101, 0b0_110_000) => Ok(Inst::ROR { rd, rs1, rs2 }),
+
+                // Zbc.
+                (1, 0b0_000_101) => Ok(Inst::CLMUL { rd, rs1, rs2 }),
+                (0b011, 0b0_000_101) => Ok(Inst::CLMULH { rd, rs1, rs2 }),
+                (0b010, 0b0_000_101) => Ok(Inst::CLMULR { rd, rs1, rs2 }),
+
+                // Zbs.
+                (1, 0b0_100_100) => Ok(Inst::BCLR { rd, rs1, rs2 }),
+                (0b101, 0b0_100_100) => Ok(Inst::BEXT { rd, rs1, rs2 }),
+                (1, 0b0_110_100) => Ok(Inst::BINV { rd, rs1, rs2 }),
+                (1, 0b0_010_100) => Ok(Inst::BSET { rd, rs1, rs2 }),
+
+                // Zbkx.
+                (0b010, 0b0_010_100) => Ok(Inst::XPERM4 { rd, rs1, rs2 }),
+                (0b100, 0b0_010_100) => Ok(Inst::XPERM8 { rd, rs1, rs2 }),
+
+                // Zicond.
+                (0b101, 0b0_000_111) => Ok(Inst::CZEROEQZ { rd, rs1, rs2 }),
+                (0b111, 0b0_000_111) => Ok(Inst::CZERONEZ { rd, rs1, rs2 }),
+
+                // Zkne and Zknd, the byte select is in the upper two bits of funct7.
+                (0, f7) if f7 & 0b11_111 == 0b10_001 => Ok(Inst::AES32ESI {
+                    rd,
+                    rs1,
+                    rs2,
+                    bs: f7 >> 5,
+                }),
+                (0, f7) if f7 & 0b11_111 == 0b10_011 => Ok(Inst::AES32ESMI {
+                    rd,
+                    rs1,
+                    rs2,
+                    bs: f7 >> 5,
+                }),
+                (0, f7) if f7 & 0b11_111 == 0b10_101 => Ok(Inst::AES32DSI {
+                    rd,
+                    rs1,
+                    rs2,
+                    bs: f7 >> 5,
+                }),
+                (0, f7) if f7 & 0b11_111 == 0b10_111 => Ok(Inst::AES32DSMI {
+                    rd,
+                    rs1,
+                    rs2,
+                    bs: f7 >> 5,
+                }),
+
+                // Zknh, the RV32 halves of the SHA-512 functions.
+                (0, 0b0_101_000) => Ok(Inst::SHA512SUM0R { rd, rs1, rs2 }),
+                (0, 0b0_101_001) => Ok(Inst::SHA512SUM1R { rd, rs1, rs2 }),
+                (0, 0b0_101_010) => Ok(Inst::SHA512SIG0L { rd, rs1, rs2 }),
+                (0, 0b0_101_011) => Ok(Inst::SHA512SIG1L { rd, rs1, rs2 }),
+                (0, 0b0_101_110) => Ok(Inst::SHA512SIG0H { rd, rs1, rs2 }),
+                (0, 0b0_101_111) => Ok(Inst::SHA512SIG1H { rd, rs1, rs2 }),
+
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // R instructions on words, the ones of M included, RV64 only.
+        0b0_111_011 => {
+            let rd = select(inst, 7, 5) as u8;
+            let f3 = select(inst, 12, 3) as u8;
+            let rs1 = select(inst, 15, 5) as u8;
+            let rs2 = select(inst, 20, 5) as u8;
+            let f7 = select(inst, 25, 7) as u8;
+
+            match (f3, f7) {
+                (0, 0) => Ok(Inst::ADDW { rd, rs1, rs2 }),
+                (0, 0b0_100_000) => Ok(Inst::SUBW { rd, rs1, rs2 }),
+                (1, 0) => Ok(Inst::SLLW { rd, rs1, rs2 }),
+                (0b101, 0) => Ok(Inst::SRLW { rd, rs1, rs2 }),
+                (0b101, 0b0_100_000) => Ok(Inst::SRAW { rd, rs1, rs2 }),
+                (0, 1) => Ok(Inst::MULW { rd, rs1, rs2 }),
+                (0b100, 1) => Ok(Inst::DIVW { rd, rs1, rs2 }),
+                (0b101, 1) => Ok(Inst::DIVUW { rd, rs1, rs2 }),
+                (0b110, 1) => Ok(Inst::REMW { rd, rs1, rs2 }),
+                (0b111, 1) => Ok(Inst::REMUW { rd, rs1, rs2 }),
+                _ => Err(Error::UnknownInst(inst)),
+            }
+        }
+
+        // A instructions.
+        0b0_101_111 => {
+            let rd = select(inst, 7, 5) as u8;
+            let f3 = select(inst, 12, 3) as u8;
+            let rs1 = select(inst, 15, 5) as u8;
+            let rs2 = select(inst, 20, 5) as u8;
+            let aqrl = select(inst, 25, 2) as u8;
+            let f5 = select(inst, 27, 5) as u8;
+
+            match (f3, f5) {
+                (0b010, 0b00_010) if rs2 == 0 => Ok(Inst::LRW { rd, rs1, aqrl }),
+                (0b010, 0b00_011) => Ok(Inst::SCW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b00_001) => Ok(Inst::AMOSWAPW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b00_000) => Ok(Inst::AMOADDW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b00_100) => Ok(Inst::AMOXORW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b01_100) => Ok(Inst::AMOANDW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b01_000) => Ok(Inst::AMOORW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b10_000) => Ok(Inst::AMOMINW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b10_100) => Ok(Inst::AMOMAXW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b11_000) => Ok(Inst::AMOMINUW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b11_100) => Ok(Inst::AMOMAXUW { rd, rs1, rs2, aqrl }),
+                (0b010, 0b00_101) => Ok(Inst::AMOCASW { rd, rs1, rs2, aqrl }),
+
+                (0b011, 0b00_010) if rs2 == 0 => Ok(Inst::LRD { rd, rs1, aqrl }),
+                (0b011, 0b00_011) => Ok(Inst::SCD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b00_001) => Ok(Inst::AMOSWAPD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b00_000) => Ok(Inst::AMOADDD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b00_100) => Ok(Inst::AMOXORD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b01_100) => Ok(Inst::AMOANDD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b01_000) => Ok(Inst::AMOORD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b10_000) => Ok(Inst::AMOMIND { rd, rs1, rs2, aqrl }),
+                (0b011, 0b10_100) => Ok(Inst::AMOMAXD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b11_000) => Ok(Inst::AMOMINUD { rd, rs1, rs2, aqrl }),
+                (0b011, 0b11_100) => Ok(Inst::AMOMAXUD { rd, rs1, rs2, aqrl }),
+
+                // The register pairs of amocas.d start at even registers.
+                (0b011, 0b00_101) if rd & 1 == 0 && rs2 & 1 == 0 => {
+                    Ok(Inst::AMOCASD { rd, rs1, rs2, aqrl })
+                }
+
+                (0b000, 0b00_001) => Ok(Inst::AMOSWAPB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b00_000) => Ok(Inst::AMOADDB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b00_100) => Ok(Inst::AMOXORB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b01_100) => Ok(Inst::AMOANDB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b01_000) => Ok(Inst::AMOORB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b10_000) => Ok(Inst::AMOMINB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b10_100) => Ok(Inst::AMOMAXB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b11_000) => Ok(Inst::AMOMINUB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b11_100) => Ok(Inst::AMOMAXUB { rd, rs1, rs2, aqrl }),
+                (0b000, 0b00_101) => Ok(Inst::AMOCASB { rd, rs1, rs2, aqrl }),
+
+                (0b001, 0b00_001) => Ok(Inst::AMOSWAPH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b00_000) => Ok(Inst::AMOADDH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b00_100) => Ok(Inst::AMOXORH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b01_100) => Ok(Inst::AMOANDH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b01_000) => Ok(Inst::AMOORH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b10_000) => Ok(Inst::AMOMINH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b10_100) => Ok(Inst::AMOMAXH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b11_000) => Ok(Inst::AMOMINUH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b11_100) => Ok(Inst::AMOMAXUH { rd, rs1, rs2, aqrl }),
+                (0b001, 0b00_101) => Ok(Inst::AMOCASH { rd, rs1, rs2, aqrl }),
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
+        // System instructions.
         0b1_110_011 => {
-            let f3 = select(inst, 12, 3);
-            let f12 = select(inst, 20, 12) as u16;
+            let (rd, f3, rs1, f12) = unpack_i(inst);
 
             match (f3, f12) {
-                (0, 0) | (0, 1) => Ok(Inst::ECALL),
-                _ => Ok(Inst::IGNORE),
+                (0, 0) if rd == 0 && rs1 == 0 => Ok(Inst::ECALL),
+                (0, 1) if rd == 0 && rs1 == 0 => Ok(Inst::EBREAK),
+                (0, 0x102) if rd == 0 && rs1 == 0 => Ok(Inst::SRET),
+                (0, f12) if rd == 0 && f12 >> 5 == 0b0_001_001 => Ok(Inst::SFENCEVMA {
+                    rs1,
+                    rs2: (f12 & 0b11111) as u8,
+                }),
+                (0, 0x302) if rd == 0 && rs1 == 0 => Ok(Inst::MRET),
+                (0, 0x105) if rd == 0 && rs1 == 0 => Ok(Inst::WFI),
+
+                // Zawrs.
+                (0, 0x00d) if rd == 0 && rs1 == 0 => Ok(Inst::WRSNTO),
+                (0, 0x01d) if rd == 0 && rs1 == 0 => Ok(Inst::WRSSTO),
+
+                // Zicsr, the rs1 field holds an unsigned immediate on the I variants.
+                (0b001, csr) => Ok(Inst::CSRRW { rd, rs1, csr }),
+                (0b010, csr) => Ok(Inst::CSRRS { rd, rs1, csr }),
+                (0b011, csr) => Ok(Inst::CSRRC { rd, rs1, csr }),
+                (0b101, csr) => Ok(Inst::CSRRWI { rd, uimm: rs1, csr }),
+                (0b110, csr) => Ok(Inst::CSRRSI { rd, uimm: rs1, csr }),
+                (0b111, csr) => Ok(Inst::CSRRCI { rd, uimm: rs1, csr }),
+
+                _ => Err(Error::UnknownInst(inst)),
             }
         }
 
-        // Ignore all the safely unimplemented instructions.
-        0b0_001_111 => Ok(Inst::IGNORE),
+        // V extension arithmetic and configuration.
+        0b1_010_111 => decode_vector(inst),
+
+        // FENCE and FENCE.I, there is a single hart and no caches, so, they are safe
+        // to ignore, apart from pause, the FENCE that only orders the earlier writes.
+        // The rd and rs1 of both, the fm of FENCE other than the one of FENCE.TSO and
+        // the imm of FENCE.I are reserved and have to be 0. The cache block operations
+        // of Zicbom and Zicboz sit next to them.
+        0b0_001_111 => match (
+            select(inst, 12, 3),
+            select(inst, 7, 5),
+            select(inst, 15, 5) as u8,
+            select(inst, 20, 12),
+        ) {
+            (0, 0, 0, _) if inst == PAUSE => Ok(Inst::PAUSE),
+            (0, 0, 0, f12) if f12 >> 8 == 0 || f12 == FENCE_TSO => Ok(Inst::IGNORE),
+            (1, 0, 0, 0) => Ok(Inst::IGNORE),
+            (0b010, 0, rs1, f12) => match f12 {
+                0 => Ok(Inst::CBOINVAL { rs1 }),
+                1 => Ok(Inst::CBOCLEAN { rs1 }),
+                2 => Ok(Inst::CBOFLUSH { rs1 }),
+                4 => Ok(Inst::CBOZERO { rs1 }),
+                _ => Err(Error::UnknownInst(inst)),
+            },
+            _ => Err(Error::UnknownInst(inst)),
+        },
 
-        _ => Err(Error::UnknownInst),
+        _ => Err(Error::UnknownInst(inst)),
     }
 }
 
+// The vector loads and stores share the major opcodes of the floating point ones,
+// with the element width in place of the F and D widths. The mop field selects the
+// addressing and the lumop field in rs2 the kind of unit stride access.
+fn decode_vector_memory(inst: u32, load: bool) -> Result<Inst, Error> {
+    let m = VMem {
+        vd: select(inst, 7, 5) as u8,
+        rs1: select(inst, 15, 5) as u8,
+        rs2: select(inst, 20, 5) as u8,
+        eew: match select(inst, 12, 3) {
+            0 => 8,
+            0b101 => 16,
+            0b110 => 32,
+            _ => 64,
+        },
+        nf: select(inst, 29, 3) as u8 + 1,
+        vm: select(inst, 25, 1) == 1,
+    };
+
+    // mew is reserved for the elements wider than 64 bits.
+    if select(inst, 28, 1) != 0 {
+        return Err(Error::UnknownInst(inst));
+    }
+
+    let whole = m.vm && m.nf.is_power_of_two();
+    let mask = m.vm && m.nf == 1 && m.eew == 8;
+    match (load, select(inst, 26, 2), m.rs2) {
+        (true, 0, 0) => Ok(Inst::VLE(m)),
+        (true, 0, 0b01_000) if whole => Ok(Inst::VLR(m)),
+        (true, 0, 0b01_011) if mask => Ok(Inst::VLM(m)),
+        (true, 0, 0b10_000) => Ok(Inst::VLEFF(m)),
+        (true, 1, _) => Ok(Inst::VLUXEI(m)),
+        (true, 0b10, _) => Ok(Inst::VLSE(m)),
+        (true, 0b11, _) => Ok(Inst::VLOXEI(m)),
+        (false, 0, 0) => Ok(Inst::VSE(m)),
+        (false, 0, 0b01_000) if whole && m.eew == 8 => Ok(Inst::VSR(m)),
+        (false, 0, 0b01_011) if mask => Ok(Inst::VSM(m)),
+        (false, 1, _) => Ok(Inst::VSUXEI(m)),
+        (false, 0b10, _) => Ok(Inst::VSSE(m)),
+        (false, 0b11, _) => Ok(Inst::VSOXEI(m)),
+        _ => Err(Error::UnknownInst(inst)),
+    }
+}
+
+// The categories of the OP-V major opcode in funct3, the integer (OPI) and the mask
+// (OPM) ones on two vectors, a vector and a scalar or a vector and an immediate,
+// along with the configuration ones.
+const OPIVV: u32 = 0b000;
+const OPMVV: u32 = 0b010;
+const OPIVI: u32 = 0b011;
+const OPIVX: u32 = 0b100;
+const OPMVX: u32 = 0b110;
+const OPCFG: u32 = 0b111;
+
+// Decodes the OP-V major opcode. The operation is in funct6 and the kind of the
+// second source in funct3, the shifts, the slides and the gathers zero extend their
+// immediate while the rest sign extend it.
+fn decode_vector(inst: u32) -> Result<Inst, Error> {
+    let vd = select(inst, 7, 5) as u8;
+    let f3 = select(inst, 12, 3);
+    let rs1 = select(inst, 15, 5) as u8;
+    let vs2 = select(inst, 20, 5) as u8;
+    let vm = select(inst, 25, 1) == 1;
+    let f6 = select(inst, 26, 6);
+
+    let src = match f3 {
+        OPIVV | OPMVV => VSrc::Vector(rs1),
+        OPIVX | OPMVX => VSrc::Scalar(rs1),
+        OPIVI => match f6 {
+            0b001_100 | 0b001_110 | 0b001_111 | 0b100_101 | 0b101_000..=0b101_111 => {
+                VSrc::Imm(rs1 as u32)
+            }
+            _ => VSrc::Imm(sign_extend(rs1 as u32, 5)),
+        },
+        OPCFG => return decode_vector_config(inst),
+        _ => return Err(Error::UnknownInst(inst)),
+    };
+    let v = V { vd, vs2, src, vm };
+
+    match (f3, f6) {
+        // Integer arithmetic.
+        (OPIVV | OPIVX | OPIVI, 0b000_000) => Ok(Inst::VADD(v)),
+        (OPIVV | OPIVX, 0b000_010) => Ok(Inst::VSUB(v)),
+        (OPIVX | OPIVI, 0b000_011) => Ok(Inst::VRSUB(v)),
+        (OPIVV | OPIVX, 0b000_100) => Ok(Inst::VMINU(v)),
+        (OPIVV | OPIVX, 0b000_101) => Ok(Inst::VMIN(v)),
+        (OPIVV | OPIVX, 0b000_110) => Ok(Inst::VMAXU(v)),
+        (OPIVV | OPIVX, 0b000_111) => Ok(Inst::VMAX(v)),
+        (OPIVV | OPIVX | OPIVI, 0b001_001) => Ok(Inst::VAND(v)),
+        (OPIVV | OPIVX | OPIVI, 0b001_010) => Ok(Inst::VOR(v)),
+        (OPIVV | OPIVX | OPIVI, 0b001_011) => Ok(Inst::VXOR(v)),
+        (OPIVV | OPIVX | OPIVI, 0b001_100) => Ok(Inst::VRGATHER(v)),
+        (OPIVV, 0b001_110) => Ok(Inst::VRGATHEREI16(v)),
+        (OPIVX | OPIVI, 0b001_110) => Ok(Inst::VSLIDEUP(v)),
+        (OPIVX | OPIVI, 0b001_111) => Ok(Inst::VSLIDEDOWN(v)),
+        (OPIVV | OPIVX | OPIVI, 0b010_000) if !vm => Ok(Inst::VADC(v)),
+        (OPIVV | OPIVX | OPIVI, 0b010_001) => Ok(Inst::VMADC(v)),
+        (OPIVV | OPIVX, 0b010_010) if !vm => Ok(Inst::VSBC(v)),
+        (OPIVV | OPIVX, 0b010_011) => Ok(Inst::VMSBC(v)),
+        (OPIVV | OPIVX | OPIVI, 0b010_111) if !vm => Ok(Inst::VMERGE(v)),
+        (OPIVV | OPIVX | OPIVI, 0b010_111) if vs2 == 0 => Ok(Inst::VMV(v)),
+        (OPIVV | OPIVX | OPIVI, 0b011_000) => Ok(Inst::VMSEQ(v)),
+        (OPIVV | OPIVX | OPIVI, 0b011_001) => Ok(Inst::VMSNE(v)),
+        (OPIVV | OPIVX, 0b011_010) => Ok(Inst::VMSLTU(v)),
+        (OPIVV | OPIVX, 0b011_011) => Ok(Inst::VMSLT(v)),
+        (OPIVV | OPIVX | OPIVI, 0b011_100) => Ok(Inst::VMSLEU(v)),
+        (OPIVV | OPIVX | OPIVI, 0b011_101) => Ok(Inst::VMSLE(v)),
+        (OPIVX | OPIVI, 0b011_110) => Ok(Inst::VMSGTU(v)),
+        (OPIVX | OPIVI, 0b011_111) => Ok(Inst::VMSGT(v)),
+        (OPIVV | OPIVX | OPIVI, 0b100_000) => Ok(Inst::VSADDU(v)),
+        (OPIVV | OPIVX | OPIVI, 0b100_001) => Ok(Inst::VSADD(v)),
+        (OPIVV | OPIVX, 0b100_010) => Ok(Inst::VSSUBU(v)),
+        (OPIVV | OPIVX, 0b100_011) => Ok(Inst::VSSUB(v)),
+        (OPIVV | OPIVX | OPIVI, 0b100_101) => Ok(Inst::VSLL(v)),
+        (OPIVV | OPIVX, 0b100_111) => Ok(Inst::VSMUL(v)),
+        (OPIVI, 0b100_111) if vm && matches!(rs1, 0 | 1 | 0b11 | 0b111) => Ok(Inst::VMVR {
+            vd,
+            vs2,
+            nr: rs1 + 1,
+        }),
+        (OPIVV | OPIVX | OPIVI, 0b101_000) => Ok(Inst::VSRL(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_001) => Ok(Inst::VSRA(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_010) => Ok(Inst::VSSRL(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_011) => Ok(Inst::VSSRA(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_100) => Ok(Inst::VNSRL(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_101) => Ok(Inst::VNSRA(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_110) => Ok(Inst::VNCLIPU(v)),
+        (OPIVV | OPIVX | OPIVI, 0b101_111) => Ok(Inst::VNCLIP(v)),
+        (OPIVV, 0b110_000) => Ok(Inst::VWREDSUMU(v)),
+        (OPIVV, 0b110_001) => Ok(Inst::VWREDSUM(v)),
+
+        // Reductions, averages and slides by one.
+        (OPMVV, 0b000_000) => Ok(Inst::VREDSUM(v)),
+        (OPMVV, 0b000_001) => Ok(Inst::VREDAND(v)),
+        (OPMVV, 0b000_010) => Ok(Inst::VREDOR(v)),
+        (OPMVV, 0b000_011) => Ok(Inst::VREDXOR(v)),
+        (OPMVV, 0b000_100) => Ok(Inst::VREDMINU(v)),
+        (OPMVV, 0b000_101) => Ok(Inst::VREDMIN(v)),
+        (OPMVV, 0b000_110) => Ok(Inst::VREDMAXU(v)),
+        (OPMVV, 0b000_111) => Ok(Inst::VREDMAX(v)),
+        (OPMVV | OPMVX, 0b001_000) => Ok(Inst::VAADDU(v)),
+        (OPMVV | OPMVX, 0b001_001) => Ok(Inst::VAADD(v)),
+        (OPMVV | OPMVX, 0b001_010) => Ok(Inst::VASUBU(v)),
+        (OPMVV | OPMVX, 0b001_011) => Ok(Inst::VASUB(v)),
+        (OPMVX, 0b001_110) => Ok(Inst::VSLIDE1UP(v)),
+        (OPMVX, 0b001_111) => Ok(Inst::VSLIDE1DOWN(v)),
+
+        // The unary ones, with the operation in the vs1 field.
+        (OPMVV, 0b010_000) => match rs1 {
+            0 if vm => Ok(Inst::VMVXS { rd: vd, vs2 }),
+            0b10_000 => Ok(Inst::VCPOP { rd: vd, vs2, vm }),
+            0b10_001 => Ok(Inst::VFIRST { rd: vd, vs2, vm }),
+            _ => Err(Error::UnknownInst(inst)),
+        },
+        (OPMVX, 0b010_000) if vs2 == 0 && vm => Ok(Inst::VMVSX { vd, rs1 }),
+        (OPMVV, 0b010_010) => match rs1 {
+            0b00_010..=0b00_111 => {
+                let frac = 4 - (rs1 >> 1);
+                match rs1 & 1 {
+                    0 => Ok(Inst::VZEXT { vd, vs2, vm, frac }),
+                    _ => Ok(Inst::VSEXT { vd, vs2, vm, frac }),
+                }
+            }
+            _ => Err(Error::UnknownInst(inst)),
+        },
+        (OPMVV, 0b010_100) => match rs1 {
+            0b00_001 => Ok(Inst::VMSBF { vd, vs2, vm }),
+            0b00_010 => Ok(Inst::VMSOF { vd, vs2, vm }),
+            0b00_011 => Ok(Inst::VMSIF { vd, vs2, vm }),
+            0b10_000 => Ok(Inst::VIOTA { vd, vs2, vm }),
+            0b10_001 if vs2 == 0 => Ok(Inst::VID { vd, vm }),
+            _ => Err(Error::UnknownInst(inst)),
+        },
+        (OPMVV, 0b010_111) if vm => Ok(Inst::VCOMPRESS { vd, vs2, vs1: rs1 }),
+
+        // Mask logical.
+        (OPMVV, 0b011_000) if vm => Ok(Inst::VMANDN { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_001) if vm => Ok(Inst::VMAND { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_010) if vm => Ok(Inst::VMOR { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_011) if vm => Ok(Inst::VMXOR { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_100) if vm => Ok(Inst::VMORN { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_101) if vm => Ok(Inst::VMNAND { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_110) if vm => Ok(Inst::VMNOR { vd, vs2, vs1: rs1 }),
+        (OPMVV, 0b011_111) if vm => Ok(Inst::VMXNOR { vd, vs2, vs1: rs1 }),
+
+        // Multiplication and division.
+        (OPMVV | OPMVX, 0b100_000) => Ok(Inst::VDIVU(v)),
+        (OPMVV | OPMVX, 0b100_001) => Ok(Inst::VDIV(v)),
+        (OPMVV | OPMVX, 0b100_010) => Ok(Inst::VREMU(v)),
+        (OPMVV | OPMVX, 0b100_011) => Ok(Inst::VREM(v)),
+        (OPMVV | OPMVX, 0b100_100) => Ok(Inst::VMULHU(v)),
+        (OPMVV | OPMVX, 0b100_101) => Ok(Inst::VMUL(v)),
+        (OPMVV | OPMVX, 0b100_110) => Ok(Inst::VMULHSU(v)),
+        (OPMVV | OPMVX, 0b100_111) => Ok(Inst::VMULH(v)),
+        (OPMVV | OPMVX, 0b101_001) => Ok(Inst::VMADD(v)),
+        (OPMVV | OPMVX, 0b101_011) => Ok(Inst::VNMSUB(v)),
+        (OPMVV | OPMVX, 0b101_101) => Ok(Inst::VMACC(v)),
+        (OPMVV | OPMVX, 0b101_111) => Ok(Inst::VNMSAC(v)),
+
+        // Widening.
+        (OPMVV | OPMVX, 0b110_000) => Ok(Inst::VWADDU(v)),
+        (OPMVV | OPMVX, 0b110_001) => Ok(Inst::VWADD(v)),
+        (OPMVV | OPMVX, 0b110_010) => Ok(Inst::VWSUBU(v)),
+        (OPMVV | OPMVX, 0b110_011) => Ok(Inst::VWSUB(v)),
+        (OPMVV | OPMVX, 0b110_100) => Ok(Inst::VWADDUW(v)),
+        (OPMVV | OPMVX, 0b110_101) => Ok(Inst::VWADDW(v)),
+        (OPMVV | OPMVX, 0b110_110) => Ok(Inst::VWSUBUW(v)),
+        (OPMVV | OPMVX, 0b110_111) => Ok(Inst::VWSUBW(v)),
+        (OPMVV | OPMVX, 0b111_000) => Ok(Inst::VWMULU(v)),
+        (OPMVV | OPMVX, 0b111_010) => Ok(Inst::VWMULSU(v)),
+        (OPMVV | OPMVX, 0b111_011) => Ok(Inst::VWMUL(v)),
+        (OPMVV | OPMVX, 0b111_100) => Ok(Inst::VWMACCU(v)),
+        (OPMVV | OPMVX, 0b111_101) => Ok(Inst::VWMACC(v)),
+        (OPMVX, 0b111_110) => Ok(Inst::VWMACCUS(v)),
+        (OPMVV | OPMVX, 0b111_111) => Ok(Inst::VWMACCSU(v)),
+
+        _ => Err(Error::UnknownInst(inst)),
+    }
+}
+
+// Decodes vsetvli, vsetivli and vsetvl, told apart by the highest two bits.
+fn decode_vector_config(inst: u32) -> Result<Inst, Error> {
+    let rd = select(inst, 7, 5) as u8;
+    let rs1 = select(inst, 15, 5) as u8;
+
+    match inst >> 30 {
+        0 | 1 => Ok(Inst::VSETVLI {
+            rd,
+            rs1,
+            vtypei: select(inst, 20, 11),
+        }),
+        0b11 => Ok(Inst::VSETIVLI {
+            rd,
+            uimm: rs1 as u32,
+            vtypei: select(inst, 20, 10),
+        }),
+        0b10 if select(inst, 25, 5) == 0 => Ok(Inst::VSETVL {
+            rd,
+            rs1,
+            rs2: select(inst, 20, 5) as u8,
+        }),
+        _ => Err(Error::UnknownInst(inst)),
+    }
+}
+
+// Sign extends the lowest bits of a value to 32 bits.
+#[inline]
+fn sign_extend(val: u32, bits: u32) -> u32 {
+    (((val << (32 - bits)) as i32) >> (32 - bits)) as u32
+}
+
 // Unpacks an I type instruction.
 #[inline]
 fn unpack_i(inst: u32) -> (u8, u8, u8, u16) {
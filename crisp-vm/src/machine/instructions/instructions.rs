@@ -1,9 +1,31 @@
 use thiserror::Error;
 
-use crate::machine::state::{self, State};
+use crate::machine::{
+    config::Extensions,
+    counters, crypto, csr, mmu,
+    softfloat::{F16, F32, F64, Format, Rounding, Softfloat},
+    state::{self, Access, Privilege, State},
+    trap::{self, Exception},
+};
+
+use super::vector::{
+    self, Addressing, Avl, First, V, VMem, VSrc, clip_signed, clip_unsigned, round_shift, signed,
+};
+
+// The operands of the R4 format of the fused multiply adds, which have a third
+// source register along with the rounding mode.
+#[derive(Debug, Clone, Copy)]
+pub struct R4 {
+    pub rd: u8,
+    pub rs1: u8,
+    pub rs2: u8,
+    pub rs3: u8,
+    pub rm: u8,
+}
 
 // https://docs.openhwgroup.org/projects/cva6-user-manual/01_cva6_user/RISCV_Instructions_RV32I.html
 #[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Inst {
     // U - Load Upper Immediate
     // Place the immediate value in the top 20 bits of the destination register rd, filling in the
@@ -18,7 +40,8 @@ pub enum Inst {
     // J - Jump and Link
     // Offset is sign-extended and added to the pc to form the jump target address, then
     // setting the least-significant bit of the result to zero (for easy 2 byte alignment),
-    // and store the address of instruction following the jump (pc+4) into register rd.
+    // and store the address of instruction following the jump (pc+4, or pc+2 when compressed)
+    // into register rd.
     JAL { rd: u8, imm: u32 },
 
     // I - Jump and Link Register
@@ -94,6 +117,20 @@ pub enum Inst {
     // Stores the 4 bytes from rs2 in memory starting at rs1 + sign extended imm.
     SW { rs1: u8, rs2: u8, imm: u16 },
 
+    // I - Load Word Unsigned
+    // Loads 4 bytes starting from the address rs1 + sign extended imm and zero extends the value
+    // before storing it in rd. RV64 only.
+    LWU { rd: u8, rs1: u8, imm: u16 },
+
+    // I - Load Doubleword
+    // Loads 8 byte value from memory starting at rs1 + sign extended imm, then, stores it in rd.
+    // Uses little endian. RV64 only.
+    LD { rd: u8, rs1: u8, imm: u16 },
+
+    // S - Store Doubleword
+    // Stores the 8 bytes from rs2 in memory starting at rs1 + sign extended imm. RV64 only.
+    SD { rs1: u8, rs2: u8, imm: u16 },
+
     // I - Add Immediate
     // Add sign-extended 12-bit immediate to rs1, and store the result in rd.
     ADDI { rd: u8, rs1: u8, imm: u16 },
@@ -119,7 +156,8 @@ pub enum Inst {
     ANDI { rd: u8, rs1: u8, imm: u16 },
 
     // I - Shift Left Logical Immediate
-    // Shifts the value in rs1 left by shamt and stores the value in rd.
+    // Shifts the value in rs1 left by shamt and stores the value in rd. The shamt is 5 bits on
+    // RV32 and 6 bits on RV64, like the rest of the shifts by an immediate.
     SLLI { rd: u8, rs1: u8, shamt: u8 },
 
     // I - Shift Right Logical Immediate
@@ -131,6 +169,26 @@ pub enum Inst {
     // stores that value in rd.
     SRAI { rd: u8, rs1: u8, shamt: u8 },
 
+    // I - Add Word Immediate
+    // Adds the sign extended imm to the lower 32 bits of rs1 and stores the 32 bit result
+    // sign extended in rd. RV64 only, like the rest of the word operations.
+    ADDIW { rd: u8, rs1: u8, imm: u16 },
+
+    // I - Shift Left Logical Word Immediate
+    // Shifts the lower 32 bits of rs1 left by shamt and stores the 32 bit result sign extended
+    // in rd.
+    SLLIW { rd: u8, rs1: u8, shamt: u8 },
+
+    // I - Shift Right Logical Word Immediate
+    // Shifts the lower 32 bits of rs1 right by shamt and stores the 32 bit result sign extended
+    // in rd.
+    SRLIW { rd: u8, rs1: u8, shamt: u8 },
+
+    // I - Shift Right Arithmetic Word Immediate
+    // Right shifts the lower 32 bits of rs1 by shamt while retaining their sign bit and stores
+    // the 32 bit result sign extended in rd.
+    SRAIW { rd: u8, rs1: u8, shamt: u8 },
+
     // R - Add
     // Add rs2 to register rs1, and store the result in register rd.
     ADD { rd: u8, rs1: u8, rs2: u8 },
@@ -172,11 +230,1654 @@ pub enum Inst {
     // Store the value of *rs1 & *rs2 in rd.
     AND { rd: u8, rs1: u8, rs2: u8 },
 
+    // R - Add Word
+    // Adds the lower 32 bits of rs1 and rs2 and stores the 32 bit result sign extended in rd.
+    ADDW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Subtract Word
+    // Subtracts the lower 32 bits of rs2 from the ones of rs1 and stores the 32 bit result sign
+    // extended in rd.
+    SUBW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Shift Left Logical Word
+    // Shifts the lower 32 bits of rs1 left by the lowest 5 bits of rs2 and stores the 32 bit
+    // result sign extended in rd.
+    SLLW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Shift Right Logical Word
+    // Shifts the lower 32 bits of rs1 right by the lowest 5 bits of rs2 and stores the 32 bit
+    // result sign extended in rd.
+    SRLW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Shift Right Arithmetic Word
+    // Right shifts the lower 32 bits of rs1 by the lowest 5 bits of rs2 while retaining their
+    // sign bit and stores the 32 bit result sign extended in rd.
+    SRAW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Multiply
+    // Multiplies rs1 and rs2 and stores the lower XLEN bits of the product in rd.
+    MUL { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Multiply High
+    // Multiplies rs1 and rs2 as signed values and stores the upper XLEN bits of the
+    // 2 * XLEN bit product in rd.
+    MULH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Multiply High Signed Unsigned
+    // Multiplies signed rs1 with unsigned rs2 and stores the upper XLEN bits of the
+    // 2 * XLEN bit product in rd.
+    MULHSU { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Multiply High Unsigned
+    // Multiplies rs1 and rs2 as unsigned values and stores the upper XLEN bits of the
+    // 2 * XLEN bit product in rd.
+    MULHU { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Divide
+    // Signed division of rs1 by rs2 rounding towards zero. Dividing by zero sets all
+    // the bits in rd and an overflow (-2^(XLEN-1) / -1) results in -2^(XLEN-1).
+    DIV { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Divide Unsigned
+    // Unsigned division of rs1 by rs2. Dividing by zero sets all the bits in rd.
+    DIVU { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Remainder
+    // Remainder of the signed division of rs1 by rs2, the sign of the result follows
+    // the dividend. Dividing by zero results in rs1 and an overflow results in 0.
+    REM { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Remainder Unsigned
+    // Remainder of the unsigned division of rs1 by rs2. Dividing by zero results in rs1.
+    REMU { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Multiply Word
+    // Multiplies the lower 32 bits of rs1 and rs2 and stores the lower 32 bits of the
+    // product sign extended in rd.
+    MULW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Divide Word
+    // Like DIV on the lower 32 bits of rs1 and rs2, with the 32 bit result sign
+    // extended in rd.
+    DIVW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Divide Unsigned Word
+    // Like DIVU on the lower 32 bits of rs1 and rs2, with the 32 bit result sign
+    // extended in rd.
+    DIVUW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Remainder Word
+    // Like REM on the lower 32 bits of rs1 and rs2, with the 32 bit result sign
+    // extended in rd.
+    REMW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Remainder Unsigned Word
+    // Like REMU on the lower 32 bits of rs1 and rs2, with the 32 bit result sign
+    // extended in rd.
+    REMUW { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Shift Left by 1 and Add
+    // Adds rs1 shifted left by 1 to rs2 and stores the result in rd.
+    SH1ADD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Shift Left by 2 and Add
+    // Adds rs1 shifted left by 2 to rs2 and stores the result in rd.
+    SH2ADD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Shift Left by 3 and Add
+    // Adds rs1 shifted left by 3 to rs2 and stores the result in rd.
+    SH3ADD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - AND with Inverted Operand
+    // Stores the value of *rs1 & !*rs2 in rd.
+    ANDN { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - OR with Inverted Operand
+    // Stores the value of *rs1 | !*rs2 in rd.
+    ORN { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Exclusive NOR
+    // Stores the value of !(*rs1 ^ *rs2) in rd.
+    XNOR { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Count Leading Zeros
+    // Stores the number of zero bits above the highest set bit of rs1 in rd, 32
+    // when rs1 is 0.
+    CLZ { rd: u8, rs1: u8 },
+
+    // I - Count Trailing Zeros
+    // Stores the number of zero bits below the lowest set bit of rs1 in rd, 32
+    // when rs1 is 0.
+    CTZ { rd: u8, rs1: u8 },
+
+    // I - Count Set Bits
+    // Stores the number of bits set in rs1 in rd.
+    CPOP { rd: u8, rs1: u8 },
+
+    // R - Maximum
+    // Stores the larger of rs1 and rs2 in rd during signed comparison.
+    MAX { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Maximum Unsigned
+    // Stores the larger of rs1 and rs2 in rd during unsigned comparison.
+    MAXU { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Minimum
+    // Stores the smaller of rs1 and rs2 in rd during signed comparison.
+    MIN { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Minimum Unsigned
+    // Stores the smaller of rs1 and rs2 in rd during unsigned comparison.
+    MINU { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Sign Extend Byte
+    // Sign extends the lowest byte of rs1 and stores it in rd.
+    SEXTB { rd: u8, rs1: u8 },
+
+    // I - Sign Extend Halfword
+    // Sign extends the lowest 16 bits of rs1 and stores them in rd.
+    SEXTH { rd: u8, rs1: u8 },
+
+    // R - Zero Extend Halfword
+    // Zero extends the lowest 16 bits of rs1 and stores them in rd.
+    ZEXTH { rd: u8, rs1: u8 },
+
+    // R - Rotate Left
+    // Rotates the value in rs1 left by the lowest 5 bits of rs2 and stores it in rd.
+    ROL { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Rotate Right
+    // Rotates the value in rs1 right by the lowest 5 bits of rs2 and stores it in
+    // rd.
+    ROR { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Rotate Right Immediate
+    // Rotates the value in rs1 right by shamt and stores it in rd.
+    RORI { rd: u8, rs1: u8, shamt: u8 },
+
+    // I - OR Combine Bytes
+    // Sets every byte of rd to all ones if any bit of the same byte of rs1 is set,
+    // to zero otherwise.
+    ORCB { rd: u8, rs1: u8 },
+
+    // I - Byte Reverse
+    // Stores the value in rs1 with the order of its bytes reversed in rd.
+    REV8 { rd: u8, rs1: u8 },
+
+    // R - Carry-Less Multiply
+    // Stores the lower 32 bits of the carry-less product of rs1 and rs2 in rd.
+    CLMUL { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Carry-Less Multiply High
+    // Stores the upper 32 bits of the 64 bit carry-less product of rs1 and rs2 in
+    // rd.
+    CLMULH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Carry-Less Multiply Reversed
+    // Stores bits 62 to 31 of the carry-less product of rs1 and rs2 in rd.
+    CLMULR { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Single-Bit Clear
+    // Clears the bit of rs1 indexed by the lowest 5 bits of rs2 and stores the
+    // value in rd.
+    BCLR { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Single-Bit Clear Immediate
+    // Clears the bit of rs1 indexed by shamt and stores the value in rd.
+    BCLRI { rd: u8, rs1: u8, shamt: u8 },
+
+    // R - Single-Bit Extract
+    // Stores the bit of rs1 indexed by the lowest 5 bits of rs2 in rd.
+    BEXT { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Single-Bit Extract Immediate
+    // Stores the bit of rs1 indexed by shamt in rd.
+    BEXTI { rd: u8, rs1: u8, shamt: u8 },
+
+    // R - Single-Bit Invert
+    // Inverts the bit of rs1 indexed by the lowest 5 bits of rs2 and stores the
+    // value in rd.
+    BINV { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Single-Bit Invert Immediate
+    // Inverts the bit of rs1 indexed by shamt and stores the value in rd.
+    BINVI { rd: u8, rs1: u8, shamt: u8 },
+
+    // R - Single-Bit Set
+    // Sets the bit of rs1 indexed by the lowest 5 bits of rs2 and stores the value
+    // in rd.
+    BSET { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Single-Bit Set Immediate
+    // Sets the bit of rs1 indexed by shamt and stores the value in rd.
+    BSETI { rd: u8, rs1: u8, shamt: u8 },
+
+    // R - Pack
+    // Stores the lower 16 bits of rs1 in the lower half of rd and the lower 16 bits
+    // of rs2 in its upper half.
+    PACK { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Pack Bytes
+    // Stores the lowest byte of rs1 in the lowest byte of rd and the lowest byte of
+    // rs2 in the byte above it, the rest of rd is zero.
+    PACKH { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Reverse Bits in Bytes
+    // Stores the value in rs1 with the order of the bits of each of its bytes
+    // reversed in rd.
+    BREV8 { rd: u8, rs1: u8 },
+
+    // I - Bit Interleave
+    // Stores the lower half of rs1 in the even bits of rd and its upper half in the
+    // odd bits.
+    ZIP { rd: u8, rs1: u8 },
+
+    // I - Bit Deinterleave
+    // Stores the even bits of rs1 in the lower half of rd and its odd bits in the
+    // upper half, the inverse of ZIP.
+    UNZIP { rd: u8, rs1: u8 },
+
+    // R - Crossbar Permutation Nibbles
+    // Replaces every nibble of rs2 by the nibble of rs1 it indexes, 0 when the
+    // index is out of range, and stores the result in rd.
+    XPERM4 { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Crossbar Permutation Bytes
+    // Replaces every byte of rs2 by the byte of rs1 it indexes, 0 when the index is
+    // out of range, and stores the result in rd.
+    XPERM8 { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - AES Final Round Encrypt
+    // XORs the forward S-box of the byte bs of rs2 into the same byte of rs1 and
+    // stores the result in rd.
+    AES32ESI { rd: u8, rs1: u8, rs2: u8, bs: u8 },
+
+    // R - AES Middle Round Encrypt
+    // Like AES32ESI with MixColumns applied to the substituted byte.
+    AES32ESMI { rd: u8, rs1: u8, rs2: u8, bs: u8 },
+
+    // R - AES Final Round Decrypt
+    // XORs the inverse S-box of the byte bs of rs2 into the same byte of rs1 and
+    // stores the result in rd.
+    AES32DSI { rd: u8, rs1: u8, rs2: u8, bs: u8 },
+
+    // R - AES Middle Round Decrypt
+    // Like AES32DSI with InvMixColumns applied to the substituted byte.
+    AES32DSMI { rd: u8, rs1: u8, rs2: u8, bs: u8 },
+
+    // I - SHA-256 Sigma0
+    // Stores the sigma0 function of SHA-256 on rs1 in rd.
+    SHA256SIG0 { rd: u8, rs1: u8 },
+
+    // I - SHA-256 Sigma1
+    // Stores the sigma1 function of SHA-256 on rs1 in rd.
+    SHA256SIG1 { rd: u8, rs1: u8 },
+
+    // I - SHA-256 Sum0
+    // Stores the sum0 function of SHA-256 on rs1 in rd.
+    SHA256SUM0 { rd: u8, rs1: u8 },
+
+    // I - SHA-256 Sum1
+    // Stores the sum1 function of SHA-256 on rs1 in rd.
+    SHA256SUM1 { rd: u8, rs1: u8 },
+
+    // R - SHA-512 Sigma0 High
+    // Stores the high half of the sigma0 function of SHA-512 in rd, the value has its
+    // high half in rs1 and its low half in rs2.
+    SHA512SIG0H { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - SHA-512 Sigma0 Low
+    // Stores the low half of the sigma0 function of SHA-512 in rd, the value has its
+    // low half in rs1 and its high half in rs2.
+    SHA512SIG0L { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - SHA-512 Sigma1 High
+    // Stores the high half of the sigma1 function of SHA-512 in rd, the value has its
+    // high half in rs1 and its low half in rs2.
+    SHA512SIG1H { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - SHA-512 Sigma1 Low
+    // Stores the low half of the sigma1 function of SHA-512 in rd, the value has its
+    // low half in rs1 and its high half in rs2.
+    SHA512SIG1L { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - SHA-512 Sum0
+    // Stores a half of the sum0 function of SHA-512 in rd, the same half of the value
+    // is in rs1 and the other one in rs2.
+    SHA512SUM0R { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - SHA-512 Sum1
+    // Stores a half of the sum1 function of SHA-512 in rd, the same half of the value
+    // is in rs1 and the other one in rs2.
+    SHA512SUM1R { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Conditional Zero if Equal to Zero
+    // Places 0 in rd if rs2 is 0, rs1 otherwise.
+    CZEROEQZ { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Conditional Zero if Not Equal to Zero
+    // Places 0 in rd if rs2 is not 0, rs1 otherwise.
+    CZERONEZ { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Load Reserved Word
+    // Loads the word at the address in rs1 into rd and registers a reservation on it.
+    // The aqrl bits (aq << 1 | rl) only order memory accesses across harts and are
+    // kept as decoded.
+    LRW { rd: u8, rs1: u8, aqrl: u8 },
+
+    // R - Store Conditional Word
+    // Stores rs2 at the address in rs1 only if a reservation is still held on it and
+    // writes 0 to rd on success or 1 on failure. The reservation is cleared either way.
+    SCW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Swap Word
+    // Atomically loads the word at the address in rs1 into rd and stores rs2 in its place.
+    AMOSWAPW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Add Word
+    // Atomically loads the word at the address in rs1 into rd and stores the sum of
+    // the loaded value and rs2 in its place.
+    AMOADDW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic XOR Word
+    // Atomically loads the word at the address in rs1 into rd and stores the loaded
+    // value XOR rs2 in its place.
+    AMOXORW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic AND Word
+    // Atomically loads the word at the address in rs1 into rd and stores the loaded
+    // value AND rs2 in its place.
+    AMOANDW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic OR Word
+    // Atomically loads the word at the address in rs1 into rd and stores the loaded
+    // value OR rs2 in its place.
+    AMOORW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Word
+    // Atomically loads the word at the address in rs1 into rd and stores the smaller
+    // of the loaded value and rs2 on signed comparison in its place.
+    AMOMINW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Word
+    // Atomically loads the word at the address in rs1 into rd and stores the larger
+    // of the loaded value and rs2 on signed comparison in its place.
+    AMOMAXW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Unsigned Word
+    // Atomically loads the word at the address in rs1 into rd and stores the smaller
+    // of the loaded value and rs2 on unsigned comparison in its place.
+    AMOMINUW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Unsigned Word
+    // Atomically loads the word at the address in rs1 into rd and stores the larger
+    // of the loaded value and rs2 on unsigned comparison in its place.
+    AMOMAXUW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Load Reserved Doubleword
+    // Like LRW on the doubleword at the address in rs1.
+    LRD { rd: u8, rs1: u8, aqrl: u8 },
+
+    // R - Store Conditional Doubleword
+    // Like SCW on the doubleword at the address in rs1.
+    SCD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Swap Doubleword
+    // Like AMOSWAPW on the doubleword at the address in rs1.
+    AMOSWAPD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Add Doubleword
+    // Like AMOADDW on the doubleword at the address in rs1.
+    AMOADDD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic XOR Doubleword
+    // Like AMOXORW on the doubleword at the address in rs1.
+    AMOXORD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic AND Doubleword
+    // Like AMOANDW on the doubleword at the address in rs1.
+    AMOANDD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic OR Doubleword
+    // Like AMOORW on the doubleword at the address in rs1.
+    AMOORD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Doubleword
+    // Like AMOMINW on the doubleword at the address in rs1.
+    AMOMIND { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Doubleword
+    // Like AMOMAXW on the doubleword at the address in rs1.
+    AMOMAXD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Unsigned Doubleword
+    // Like AMOMINUW on the doubleword at the address in rs1.
+    AMOMINUD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Unsigned Doubleword
+    // Like AMOMAXUW on the doubleword at the address in rs1.
+    AMOMAXUD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Compare and Swap Word
+    // Atomically loads the word at the address in rs1 into rd and stores rs2 in its
+    // place if it equals the value of rd. It needs the permission to store even when
+    // the comparison fails.
+    AMOCASW { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Compare and Swap Doubleword
+    // Like AMOCASW on the doubleword at the address in rs1, with the compared and
+    // loaded value in the register pair rd, rd + 1 and the stored one in rs2, rs2 + 1,
+    // the low word in the even register. The pairs start at even registers, x0 as a
+    // pair reads as 0 and discards what is written to it.
+    AMOCASD { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Swap Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the low byte of rs2 in its place.
+    AMOSWAPB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Add Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the sum of the loaded value and rs2 in its place.
+    AMOADDB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic XOR Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the loaded value XOR rs2 in its place.
+    AMOXORB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic AND Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the loaded value AND rs2 in its place.
+    AMOANDB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic OR Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the loaded value OR rs2 in its place.
+    AMOORB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the smaller of the loaded value and rs2 on signed comparison in its place.
+    AMOMINB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the larger of the loaded value and rs2 on signed comparison in its place.
+    AMOMAXB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Unsigned Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the smaller of the loaded value and rs2 on unsigned comparison in its
+    // place.
+    AMOMINUB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Unsigned Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the larger of the loaded value and rs2 on unsigned comparison in its
+    // place.
+    AMOMAXUB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Compare and Swap Byte
+    // Atomically loads the byte at the address in rs1 into rd, sign extended, and
+    // stores the low byte of rs2 in its place if it equals the low byte of rd.
+    AMOCASB { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Swap Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the low halfword of rs2 in its place.
+    AMOSWAPH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Add Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the sum of the loaded value and rs2 in its place.
+    AMOADDH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic XOR Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the loaded value XOR rs2 in its place.
+    AMOXORH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic AND Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the loaded value AND rs2 in its place.
+    AMOANDH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic OR Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the loaded value OR rs2 in its place.
+    AMOORH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the smaller of the loaded value and rs2 on signed comparison in its place.
+    AMOMINH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the larger of the loaded value and rs2 on signed comparison in its place.
+    AMOMAXH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Minimum Unsigned Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the smaller of the loaded value and rs2 on unsigned comparison in its
+    // place.
+    AMOMINUH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Maximum Unsigned Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the larger of the loaded value and rs2 on unsigned comparison in its
+    // place.
+    AMOMAXUH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // R - Atomic Compare and Swap Halfword
+    // Atomically loads the halfword at the address in rs1 into rd, sign extended, and
+    // stores the low halfword of rs2 in its place if it equals the low halfword of rd.
+    AMOCASH { rd: u8, rs1: u8, rs2: u8, aqrl: u8 },
+
+    // I - Wait on Reservation Set, No Timeout
+    // Stall while a reservation is held, until another hart takes it away or an
+    // interrupt becomes pending. Below machine mode with mstatus.TW set, the stall
+    // times out with an illegal instruction exception.
+    WRSNTO,
+
+    // I - Wait on Reservation Set, Short Timeout
+    // Like WRSNTO but gives up after a short while, which never traps.
+    WRSSTO,
+
+    // I - Floating-Point Load Word
+    // Loads the single precision value at the address rs1 + sign extended imm into the
+    // floating point register rd.
+    FLW { rd: u8, rs1: u8, imm: u16 },
+
+    // S - Floating-Point Store Word
+    // Stores the single precision value in the floating point register rs2 at the
+    // address rs1 + sign extended imm.
+    FSW { rs1: u8, rs2: u8, imm: u16 },
+
+    // R4 - Fused Multiply-Add Single
+    // Places rs1 * rs2 + rs3 in rd, rounded once with the rounding mode in rm.
+    FMADDS(R4),
+
+    // R4 - Fused Multiply-Subtract Single
+    // Places rs1 * rs2 - rs3 in rd, rounded once.
+    FMSUBS(R4),
+
+    // R4 - Fused Negated Multiply-Subtract Single
+    // Places -(rs1 * rs2) + rs3 in rd, rounded once.
+    FNMSUBS(R4),
+
+    // R4 - Fused Negated Multiply-Add Single
+    // Places -(rs1 * rs2) - rs3 in rd, rounded once.
+    FNMADDS(R4),
+
+    // R - Floating-Point Add Single
+    // Places rs1 + rs2 in rd, rounded with the rounding mode in rm, 0b111 being the
+    // dynamic rounding mode in frm.
+    FADDS { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Subtract Single
+    // Places rs1 - rs2 in rd.
+    FSUBS { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Multiply Single
+    // Places rs1 * rs2 in rd.
+    FMULS { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Divide Single
+    // Places rs1 / rs2 in rd.
+    FDIVS { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Square Root Single
+    // Places the square root of rs1 in rd.
+    FSQRTS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Sign Inject Single
+    // Places the magnitude of rs1 with the sign of rs2 in rd.
+    FSGNJS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Sign Inject-Negate Single
+    // Places the magnitude of rs1 with the opposite of the sign of rs2 in rd.
+    FSGNJNS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Sign Inject-XOR Single
+    // Places the magnitude of rs1 with the sign of rs1 XOR the sign of rs2 in rd.
+    FSGNJXS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Minimum Single
+    // Places the smaller of rs1 and rs2 in rd, a NaN is only picked when both are.
+    FMINS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Maximum Single
+    // Places the larger of rs1 and rs2 in rd, a NaN is only picked when both are.
+    FMAXS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Convert to Word from Single
+    // Converts the floating point rs1 to a signed integer in the integer register rd,
+    // the values out of range saturate.
+    FCVTWS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Unsigned Word from Single
+    // Converts the floating point rs1 to an unsigned integer in the integer register
+    // rd, the values out of range saturate.
+    FCVTWUS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Move Word to Integer
+    // Copies the bits of the floating point rs1 to the integer register rd.
+    FMVXW { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Equals Single
+    // Writes 1 to the integer register rd if rs1 and rs2 are equal, 0 otherwise.
+    FEQS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Less Than Single
+    // Writes 1 to the integer register rd if rs1 < rs2, 0 otherwise.
+    FLTS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Less Than or Equal Single
+    // Writes 1 to the integer register rd if rs1 <= rs2, 0 otherwise.
+    FLES { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Classify Single
+    // Writes a mask with the bit of the class of rs1 set to the integer register rd.
+    FCLASSS { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Convert to Single from Word
+    // Converts the signed integer in the integer register rs1 to the floating point rd.
+    FCVTSW { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Single from Unsigned Word
+    // Converts the unsigned integer in the integer register rs1 to the floating point
+    // rd.
+    FCVTSWU { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Move Word from Integer
+    // Copies the bits of the integer register rs1 to the floating point rd.
+    FMVWX { rd: u8, rs1: u8 },
+
+    // I - Floating-Point Load Double
+    // Loads the double precision value at the address rs1 + sign extended imm into the
+    // floating point register rd.
+    FLD { rd: u8, rs1: u8, imm: u16 },
+
+    // S - Floating-Point Store Double
+    // Stores the double precision value in the floating point register rs2 at the
+    // address rs1 + sign extended imm.
+    FSD { rs1: u8, rs2: u8, imm: u16 },
+
+    // R4 - Fused Multiply-Add Double
+    // Places rs1 * rs2 + rs3 in rd, rounded once with the rounding mode in rm.
+    FMADDD(R4),
+
+    // R4 - Fused Multiply-Subtract Double
+    // Places rs1 * rs2 - rs3 in rd, rounded once.
+    FMSUBD(R4),
+
+    // R4 - Fused Negated Multiply-Subtract Double
+    // Places -(rs1 * rs2) + rs3 in rd, rounded once.
+    FNMSUBD(R4),
+
+    // R4 - Fused Negated Multiply-Add Double
+    // Places -(rs1 * rs2) - rs3 in rd, rounded once.
+    FNMADDD(R4),
+
+    // R - Floating-Point Add Double
+    // Places rs1 + rs2 in rd, rounded with the rounding mode in rm.
+    FADDD { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Subtract Double
+    // Places rs1 - rs2 in rd.
+    FSUBD { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Multiply Double
+    // Places rs1 * rs2 in rd.
+    FMULD { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Divide Double
+    // Places rs1 / rs2 in rd.
+    FDIVD { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Square Root Double
+    // Places the square root of rs1 in rd.
+    FSQRTD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Sign Inject Double
+    // Places the magnitude of rs1 with the sign of rs2 in rd.
+    FSGNJD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Sign Inject-Negate Double
+    // Places the magnitude of rs1 with the opposite of the sign of rs2 in rd.
+    FSGNJND { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Sign Inject-XOR Double
+    // Places the magnitude of rs1 with the sign of rs1 XOR the sign of rs2 in rd.
+    FSGNJXD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Minimum Double
+    // Places the smaller of rs1 and rs2 in rd, a NaN is only picked when both are.
+    FMIND { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Maximum Double
+    // Places the larger of rs1 and rs2 in rd, a NaN is only picked when both are.
+    FMAXD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Convert to Single from Double
+    // Rounds the double precision rs1 to the single precision rd.
+    FCVTSD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Double from Single
+    // Widens the single precision rs1 to the double precision rd, which is exact.
+    FCVTDS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Equals Double
+    // Writes 1 to the integer register rd if rs1 and rs2 are equal, 0 otherwise.
+    FEQD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Less Than Double
+    // Writes 1 to the integer register rd if rs1 < rs2, 0 otherwise.
+    FLTD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Less Than or Equal Double
+    // Writes 1 to the integer register rd if rs1 <= rs2, 0 otherwise.
+    FLED { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Classify Double
+    // Writes a mask with the bit of the class of rs1 set to the integer register rd.
+    FCLASSD { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Convert to Word from Double
+    // Converts the floating point rs1 to a signed integer in the integer register rd,
+    // the values out of range saturate.
+    FCVTWD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Unsigned Word from Double
+    // Converts the floating point rs1 to an unsigned integer in the integer register
+    // rd, the values out of range saturate.
+    FCVTWUD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Double from Word
+    // Converts the signed integer in the integer register rs1 to the floating point rd,
+    // which is exact.
+    FCVTDW { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Double from Unsigned Word
+    // Converts the unsigned integer in the integer register rs1 to the floating point
+    // rd, which is exact.
+    FCVTDWU { rd: u8, rs1: u8, rm: u8 },
+
+    // I - Floating-Point Load Halfword
+    // Loads the half precision value at the address rs1 + sign extended imm into the
+    // floating point register rd.
+    FLH { rd: u8, rs1: u8, imm: u16 },
+
+    // S - Floating-Point Store Halfword
+    // Stores the half precision value in the floating point register rs2 at the
+    // address rs1 + sign extended imm.
+    FSH { rs1: u8, rs2: u8, imm: u16 },
+
+    // R4 - Fused Multiply-Add Half
+    // Places rs1 * rs2 + rs3 in rd, rounded once with the rounding mode in rm.
+    FMADDH(R4),
+
+    // R4 - Fused Multiply-Subtract Half
+    // Places rs1 * rs2 - rs3 in rd, rounded once.
+    FMSUBH(R4),
+
+    // R4 - Fused Negated Multiply-Subtract Half
+    // Places -(rs1 * rs2) + rs3 in rd, rounded once.
+    FNMSUBH(R4),
+
+    // R4 - Fused Negated Multiply-Add Half
+    // Places -(rs1 * rs2) - rs3 in rd, rounded once.
+    FNMADDH(R4),
+
+    // R - Floating-Point Add Half
+    // Places rs1 + rs2 in rd, rounded with the rounding mode in rm.
+    FADDH { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Subtract Half
+    // Places rs1 - rs2 in rd.
+    FSUBH { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Multiply Half
+    // Places rs1 * rs2 in rd.
+    FMULH { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Divide Half
+    // Places rs1 / rs2 in rd.
+    FDIVH { rd: u8, rs1: u8, rs2: u8, rm: u8 },
+
+    // R - Floating-Point Square Root Half
+    // Places the square root of rs1 in rd.
+    FSQRTH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Sign Inject Half
+    // Places the magnitude of rs1 with the sign of rs2 in rd.
+    FSGNJH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Sign Inject-Negate Half
+    // Places the magnitude of rs1 with the opposite of the sign of rs2 in rd.
+    FSGNJNH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Sign Inject-XOR Half
+    // Places the magnitude of rs1 with the sign of rs1 XOR the sign of rs2 in rd.
+    FSGNJXH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Minimum Half
+    // Places the smaller of rs1 and rs2 in rd, a NaN is only picked when both are.
+    FMINH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Maximum Half
+    // Places the larger of rs1 and rs2 in rd, a NaN is only picked when both are.
+    FMAXH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Convert to Single from Half
+    // Widens the half precision rs1 to the single precision rd, which is exact.
+    FCVTSH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Half from Single
+    // Rounds the single precision rs1 to the half precision rd.
+    FCVTHS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Double from Half
+    // Widens the half precision rs1 to the double precision rd, which is exact.
+    FCVTDH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Half from Double
+    // Rounds the double precision rs1 to the half precision rd.
+    FCVTHD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Equals Half
+    // Writes 1 to the integer register rd if rs1 and rs2 are equal, 0 otherwise.
+    FEQH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Less Than Half
+    // Writes 1 to the integer register rd if rs1 < rs2, 0 otherwise.
+    FLTH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Less Than or Equal Half
+    // Writes 1 to the integer register rd if rs1 <= rs2, 0 otherwise.
+    FLEH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Classify Half
+    // Writes a mask with the bit of the class of rs1 set to the integer register rd.
+    FCLASSH { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Convert to Word from Half
+    // Converts the floating point rs1 to a signed integer in the integer register rd,
+    // the values out of range saturate.
+    FCVTWH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Unsigned Word from Half
+    // Converts the floating point rs1 to an unsigned integer in the integer register
+    // rd, the values out of range saturate.
+    FCVTWUH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Half from Word
+    // Converts the signed integer in the integer register rs1 to the floating point rd.
+    FCVTHW { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Convert to Half from Unsigned Word
+    // Converts the unsigned integer in the integer register rs1 to the floating point
+    // rd.
+    FCVTHWU { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Move Halfword to Integer
+    // Copies the bits of the floating point rs1 to the integer register rd, sign
+    // extended.
+    FMVXH { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Move Halfword from Integer
+    // Copies the low halfword of the integer register rs1 to the floating point rd.
+    FMVHX { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Load Immediate Single
+    // Places the constant of the table of FLI at the index in rs1 in rd, -1.0, the
+    // smallest normal number, powers of two and a few fractions in between, infinity
+    // and the canonical NaN.
+    FLIS { rd: u8, index: u8 },
+
+    // R - Floating-Point Load Immediate Double
+    // Places the constant at the index in rd.
+    FLID { rd: u8, index: u8 },
+
+    // R - Floating-Point Load Immediate Half
+    // Places the constant at the index in rd, 2^-16 and 2^-15 being subnormal and 2^16
+    // infinite.
+    FLIH { rd: u8, index: u8 },
+
+    // R - Floating-Point Minimum Single, NaN Propagating
+    // Places the smaller of rs1 and rs2 in rd, the canonical NaN if either is a NaN.
+    FMINMS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Maximum Single, NaN Propagating
+    // Places the larger of rs1 and rs2 in rd, the canonical NaN if either is a NaN.
+    FMAXMS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Minimum Double, NaN Propagating
+    // Places the smaller of rs1 and rs2 in rd, the canonical NaN if either is a NaN.
+    FMINMD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Maximum Double, NaN Propagating
+    // Places the larger of rs1 and rs2 in rd, the canonical NaN if either is a NaN.
+    FMAXMD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Minimum Half, NaN Propagating
+    // Places the smaller of rs1 and rs2 in rd, the canonical NaN if either is a NaN.
+    FMINMH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Maximum Half, NaN Propagating
+    // Places the larger of rs1 and rs2 in rd, the canonical NaN if either is a NaN.
+    FMAXMH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Round to Integer Single
+    // Places rs1 rounded to an integer with the rounding mode in rm in rd, without
+    // raising the inexact flag.
+    FROUNDS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Round to Integer Single, Inexact
+    // Like FROUNDS but raises the inexact flag when rs1 was not an integer.
+    FROUNDNXS { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Round to Integer Double
+    // Places rs1 rounded to an integer in rd, without raising the inexact flag.
+    FROUNDD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Round to Integer Double, Inexact
+    // Like FROUNDD but raises the inexact flag when rs1 was not an integer.
+    FROUNDNXD { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Round to Integer Half
+    // Places rs1 rounded to an integer in rd, without raising the inexact flag.
+    FROUNDH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Round to Integer Half, Inexact
+    // Like FROUNDH but raises the inexact flag when rs1 was not an integer.
+    FROUNDNXH { rd: u8, rs1: u8, rm: u8 },
+
+    // R - Floating-Point Quiet Less Than Single
+    // Like FLTS but only a signaling NaN is invalid.
+    FLTQS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Quiet Less Than or Equal Single
+    // Like FLES but only a signaling NaN is invalid.
+    FLEQS { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Quiet Less Than Double
+    // Like FLTD but only a signaling NaN is invalid.
+    FLTQD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Quiet Less Than or Equal Double
+    // Like FLED but only a signaling NaN is invalid.
+    FLEQD { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Quiet Less Than Half
+    // Like FLTH but only a signaling NaN is invalid.
+    FLTQH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Quiet Less Than or Equal Half
+    // Like FLEH but only a signaling NaN is invalid.
+    FLEQH { rd: u8, rs1: u8, rs2: u8 },
+
+    // R - Floating-Point Convert to Word from Double, Modular
+    // Converts the floating point rs1 to a signed integer rounded towards zero in the
+    // integer register rd, the values out of range wrap around modulo 2^32 and the
+    // infinities and the NaNs are 0.
+    FCVTMODWD { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Move High Half of Double to Integer
+    // Copies the upper 32 bits of the double precision rs1 to the integer register rd.
+    FMVHXD { rd: u8, rs1: u8 },
+
+    // R - Floating-Point Move Pair to Double from Integer
+    // Places the double precision value with its upper 32 bits in the integer register
+    // rs2 and its lower ones in rs1 in rd.
+    FMVPDX { rd: u8, rs1: u8, rs2: u8 },
+
+    // I - Set Vector Length Immediate
+    // Sets vtype to the immediate and vl to the application vector length in rs1, capped
+    // to VLMAX, then places vl in rd. rs1 as x0 asks for VLMAX, or keeps vl when rd is
+    // x0 as well.
+    VSETVLI { rd: u8, rs1: u8, vtypei: u32 },
+
+    // I - Set Vector Length Immediate AVL
+    // Like VSETVLI with the application vector length in a 5 bit unsigned immediate.
+    VSETIVLI { rd: u8, uimm: u32, vtypei: u32 },
+
+    // R - Set Vector Length
+    // Like VSETVLI with vtype in rs2.
+    VSETVL { rd: u8, rs1: u8, rs2: u8 },
+
+    // VL - Vector Unit-Stride Load
+    // Loads the elements of EEW next to each other from the address in rs1 into the
+    // group at vd, the segments of nf fields go into nf groups from vd on.
+    VLE(VMem),
+
+    // VL - Vector Unit-Stride Fault-Only-First Load
+    // Like VLE, but a fault on an element past the first one sets vl to its index
+    // instead of trapping.
+    VLEFF(VMem),
+
+    // VL - Vector Strided Load
+    // Like VLE with the segments rs2 bytes apart.
+    VLSE(VMem),
+
+    // VL - Vector Indexed-Unordered Load
+    // Loads the segments at the offsets of EEW in vs2 from the address in rs1 into
+    // groups of SEW elements, in any order.
+    VLUXEI(VMem),
+
+    // VL - Vector Indexed-Ordered Load
+    // Like VLUXEI with the elements accessed in order.
+    VLOXEI(VMem),
+
+    // VL - Vector Whole Register Load
+    // Loads nf whole registers from the address in rs1 into the registers from vd on,
+    // regardless of vtype and vl.
+    VLR(VMem),
+
+    // VL - Vector Mask Load
+    // Loads a mask of vl bits from the address in rs1 into vd.
+    VLM(VMem),
+
+    // VS - Vector Unit-Stride Store
+    // Stores the elements of EEW in the group at vd next to each other from the address
+    // in rs1, the segments of nf fields come from nf groups from vd on.
+    VSE(VMem),
+
+    // VS - Vector Strided Store
+    // Like VSE with the segments rs2 bytes apart.
+    VSSE(VMem),
+
+    // VS - Vector Indexed-Unordered Store
+    // Stores the segments of SEW elements at the offsets of EEW in vs2 from the address
+    // in rs1, in any order.
+    VSUXEI(VMem),
+
+    // VS - Vector Indexed-Ordered Store
+    // Like VSUXEI with the elements accessed in order.
+    VSOXEI(VMem),
+
+    // VS - Vector Whole Register Store
+    // Stores nf whole registers from vd on at the address in rs1, regardless of vtype
+    // and vl.
+    VSR(VMem),
+
+    // VS - Vector Mask Store
+    // Stores the mask of vl bits in vd at the address in rs1.
+    VSM(VMem),
+
+    // V - Vector Add
+    // Places vs2 + vs1, rs1 or the immediate in vd.
+    VADD(V),
+
+    // V - Vector Subtract
+    // Places vs2 - vs1 or rs1 in vd.
+    VSUB(V),
+
+    // V - Vector Reverse Subtract
+    // Places rs1 or the immediate - vs2 in vd.
+    VRSUB(V),
+
+    // V - Vector Widening Add Unsigned
+    // Places vs2 + vs1 or rs1 zero extended to 2 * SEW in vd.
+    VWADDU(V),
+
+    // V - Vector Widening Add
+    // Places vs2 + vs1 or rs1 sign extended to 2 * SEW in vd.
+    VWADD(V),
+
+    // V - Vector Widening Subtract Unsigned
+    // Places vs2 - vs1 or rs1 zero extended to 2 * SEW in vd.
+    VWSUBU(V),
+
+    // V - Vector Widening Subtract
+    // Places vs2 - vs1 or rs1 sign extended to 2 * SEW in vd.
+    VWSUB(V),
+
+    // V - Vector Widening Add Unsigned Wide
+    // Like VWADDU with vs2 already 2 * SEW wide.
+    VWADDUW(V),
+
+    // V - Vector Widening Add Wide
+    // Like VWADD with vs2 already 2 * SEW wide.
+    VWADDW(V),
+
+    // V - Vector Widening Subtract Unsigned Wide
+    // Like VWSUBU with vs2 already 2 * SEW wide.
+    VWSUBUW(V),
+
+    // V - Vector Widening Subtract Wide
+    // Like VWSUB with vs2 already 2 * SEW wide.
+    VWSUBW(V),
+
+    // V - Vector Zero Extend
+    // Places the elements of vs2 that are 2 to the power of frac narrower than SEW zero
+    // extended in vd.
+    VZEXT { vd: u8, vs2: u8, vm: bool, frac: u8 },
+
+    // V - Vector Sign Extend
+    // Like VZEXT with the elements sign extended.
+    VSEXT { vd: u8, vs2: u8, vm: bool, frac: u8 },
+
+    // V - Vector Add with Carry
+    // Places vs2 + vs1, rs1 or the immediate + the carry in v0 in vd.
+    VADC(V),
+
+    // V - Vector Add Carry Out
+    // Sets the bits of the mask in vd to the carry out of vs2 + vs1, rs1 or the
+    // immediate, with the carry in v0 unless vm is set.
+    VMADC(V),
+
+    // V - Vector Subtract with Borrow
+    // Places vs2 - vs1 or rs1 - the borrow in v0 in vd.
+    VSBC(V),
+
+    // V - Vector Subtract Borrow Out
+    // Sets the bits of the mask in vd to the borrow out of vs2 - vs1 or rs1, with the
+    // borrow in v0 unless vm is set.
+    VMSBC(V),
+
+    // V - Vector And
+    // Places the bitwise AND of vs2 and vs1, rs1 or the immediate in vd.
+    VAND(V),
+
+    // V - Vector Or
+    // Places the bitwise OR of vs2 and vs1, rs1 or the immediate in vd.
+    VOR(V),
+
+    // V - Vector Xor
+    // Places the bitwise XOR of vs2 and vs1, rs1 or the immediate in vd.
+    VXOR(V),
+
+    // V - Vector Shift Left Logical
+    // Places vs2 shifted left by the lowest log2(SEW) bits of vs1, rs1 or the unsigned
+    // immediate in vd.
+    VSLL(V),
+
+    // V - Vector Shift Right Logical
+    // Like VSLL, shifting right with zeros.
+    VSRL(V),
+
+    // V - Vector Shift Right Arithmetic
+    // Like VSLL, shifting right with copies of the sign bit.
+    VSRA(V),
+
+    // V - Vector Narrowing Shift Right Logical
+    // Places vs2 of 2 * SEW shifted right with zeros by the lowest log2(2 * SEW) bits
+    // of vs1, rs1 or the unsigned immediate in vd.
+    VNSRL(V),
+
+    // V - Vector Narrowing Shift Right Arithmetic
+    // Like VNSRL, shifting right with copies of the sign bit.
+    VNSRA(V),
+
+    // V - Vector Set If Equal
+    // Sets the bits of the mask in vd where vs2 == vs1, rs1 or the immediate.
+    VMSEQ(V),
+
+    // V - Vector Set If Not Equal
+    // Sets the bits of the mask in vd where vs2 != vs1, rs1 or the immediate.
+    VMSNE(V),
+
+    // V - Vector Set If Less Than Unsigned
+    // Sets the bits of the mask in vd where vs2 < vs1 or rs1 on unsigned comparison.
+    VMSLTU(V),
+
+    // V - Vector Set If Less Than
+    // Sets the bits of the mask in vd where vs2 < vs1 or rs1 on signed comparison.
+    VMSLT(V),
+
+    // V - Vector Set If Less or Equal Unsigned
+    // Sets the bits of the mask in vd where vs2 <= vs1, rs1 or the immediate on
+    // unsigned comparison.
+    VMSLEU(V),
+
+    // V - Vector Set If Less or Equal
+    // Sets the bits of the mask in vd where vs2 <= vs1, rs1 or the immediate on signed
+    // comparison.
+    VMSLE(V),
+
+    // V - Vector Set If Greater Than Unsigned
+    // Sets the bits of the mask in vd where vs2 > rs1 or the immediate on unsigned
+    // comparison.
+    VMSGTU(V),
+
+    // V - Vector Set If Greater Than
+    // Sets the bits of the mask in vd where vs2 > rs1 or the immediate on signed
+    // comparison.
+    VMSGT(V),
+
+    // V - Vector Minimum Unsigned
+    // Places the unsigned minimum of vs2 and vs1 or rs1 in vd.
+    VMINU(V),
+
+    // V - Vector Minimum
+    // Places the signed minimum of vs2 and vs1 or rs1 in vd.
+    VMIN(V),
+
+    // V - Vector Maximum Unsigned
+    // Places the unsigned maximum of vs2 and vs1 or rs1 in vd.
+    VMAXU(V),
+
+    // V - Vector Maximum
+    // Places the signed maximum of vs2 and vs1 or rs1 in vd.
+    VMAX(V),
+
+    // V - Vector Multiply
+    // Places the lowest SEW bits of vs2 * vs1 or rs1 in vd.
+    VMUL(V),
+
+    // V - Vector Multiply High
+    // Places the highest SEW bits of vs2 * vs1 or rs1 on signed multiplication in vd.
+    VMULH(V),
+
+    // V - Vector Multiply High Unsigned
+    // Places the highest SEW bits of vs2 * vs1 or rs1 on unsigned multiplication in vd.
+    VMULHU(V),
+
+    // V - Vector Multiply High Signed-Unsigned
+    // Places the highest SEW bits of the signed vs2 * the unsigned vs1 or rs1 in vd.
+    VMULHSU(V),
+
+    // V - Vector Divide Unsigned
+    // Places vs2 / vs1 or rs1 on unsigned division in vd, a division by zero gives all
+    // the bits set.
+    VDIVU(V),
+
+    // V - Vector Divide
+    // Places vs2 / vs1 or rs1 on signed division in vd, a division by zero gives -1 and
+    // the overflow gives the dividend.
+    VDIV(V),
+
+    // V - Vector Remainder Unsigned
+    // Places the remainder of vs2 / vs1 or rs1 on unsigned division in vd, a division
+    // by zero gives the dividend.
+    VREMU(V),
+
+    // V - Vector Remainder
+    // Places the remainder of vs2 / vs1 or rs1 on signed division in vd, a division by
+    // zero gives the dividend and the overflow gives 0.
+    VREM(V),
+
+    // V - Vector Widening Multiply
+    // Places vs2 * vs1 or rs1 on signed multiplication in vd of 2 * SEW.
+    VWMUL(V),
+
+    // V - Vector Widening Multiply Unsigned
+    // Places vs2 * vs1 or rs1 on unsigned multiplication in vd of 2 * SEW.
+    VWMULU(V),
+
+    // V - Vector Widening Multiply Signed-Unsigned
+    // Places the signed vs2 * the unsigned vs1 or rs1 in vd of 2 * SEW.
+    VWMULSU(V),
+
+    // V - Vector Multiply Add Overwrite Addend
+    // Places vs1 or rs1 * vs2 + vd in vd.
+    VMACC(V),
+
+    // V - Vector Negated Multiply Subtract Overwrite Addend
+    // Places -(vs1 or rs1 * vs2) + vd in vd.
+    VNMSAC(V),
+
+    // V - Vector Multiply Add Overwrite Multiplicand
+    // Places vs1 or rs1 * vd + vs2 in vd.
+    VMADD(V),
+
+    // V - Vector Negated Multiply Subtract Overwrite Multiplicand
+    // Places -(vs1 or rs1 * vd) + vs2 in vd.
+    VNMSUB(V),
+
+    // V - Vector Widening Multiply Add Unsigned
+    // Places vs1 or rs1 * vs2 on unsigned multiplication + vd of 2 * SEW in vd.
+    VWMACCU(V),
+
+    // V - Vector Widening Multiply Add
+    // Places vs1 or rs1 * vs2 on signed multiplication + vd of 2 * SEW in vd.
+    VWMACC(V),
+
+    // V - Vector Widening Multiply Add Signed-Unsigned
+    // Places the signed vs1 or rs1 * the unsigned vs2 + vd of 2 * SEW in vd.
+    VWMACCSU(V),
+
+    // V - Vector Widening Multiply Add Unsigned-Signed
+    // Places the unsigned rs1 * the signed vs2 + vd of 2 * SEW in vd.
+    VWMACCUS(V),
+
+    // V - Vector Merge
+    // Places vs1, rs1 or the immediate in vd where the bit of v0 is set and vs2 where
+    // it is clear.
+    VMERGE(V),
+
+    // V - Vector Move
+    // Places vs1, rs1 or the immediate in vd, vs2 is v0.
+    VMV(V),
+
+    // V - Vector Saturating Add Unsigned
+    // Places vs2 + vs1, rs1 or the immediate in vd, saturated to the unsigned range of
+    // SEW.
+    VSADDU(V),
+
+    // V - Vector Saturating Add
+    // Places vs2 + vs1, rs1 or the immediate in vd, saturated to the signed range of
+    // SEW.
+    VSADD(V),
+
+    // V - Vector Saturating Subtract Unsigned
+    // Places vs2 - vs1 or rs1 in vd, saturated to the unsigned range of SEW.
+    VSSUBU(V),
+
+    // V - Vector Saturating Subtract
+    // Places vs2 - vs1 or rs1 in vd, saturated to the signed range of SEW.
+    VSSUB(V),
+
+    // V - Vector Averaging Add Unsigned
+    // Places (vs2 + vs1 or rs1) / 2 on unsigned addition, rounded as vxrm says, in vd.
+    VAADDU(V),
+
+    // V - Vector Averaging Add
+    // Places (vs2 + vs1 or rs1) / 2 on signed addition, rounded as vxrm says, in vd.
+    VAADD(V),
+
+    // V - Vector Averaging Subtract Unsigned
+    // Places (vs2 - vs1 or rs1) / 2 on unsigned subtraction, rounded as vxrm says, in
+    // vd.
+    VASUBU(V),
+
+    // V - Vector Averaging Subtract
+    // Places (vs2 - vs1 or rs1) / 2 on signed subtraction, rounded as vxrm says, in vd.
+    VASUB(V),
+
+    // V - Vector Fractional Multiply
+    // Places vs2 * vs1 or rs1 shifted right by SEW - 1, rounded as vxrm says, in vd,
+    // saturated to the signed range of SEW.
+    VSMUL(V),
+
+    // V - Vector Scaling Shift Right Logical
+    // Like VSRL with the result rounded as vxrm says.
+    VSSRL(V),
+
+    // V - Vector Scaling Shift Right Arithmetic
+    // Like VSRA with the result rounded as vxrm says.
+    VSSRA(V),
+
+    // V - Vector Narrowing Clip Unsigned
+    // Like VNSRL with the result rounded as vxrm says and saturated to the unsigned
+    // range of SEW.
+    VNCLIPU(V),
+
+    // V - Vector Narrowing Clip
+    // Like VNSRA with the result rounded as vxrm says and saturated to the signed range
+    // of SEW.
+    VNCLIP(V),
+
+    // V - Vector Reduce Sum
+    // Places the sum of the element 0 of vs1 and the active elements of vs2 in the
+    // element 0 of vd.
+    VREDSUM(V),
+
+    // V - Vector Reduce Maximum Unsigned
+    // Like VREDSUM with the unsigned maximum.
+    VREDMAXU(V),
+
+    // V - Vector Reduce Maximum
+    // Like VREDSUM with the signed maximum.
+    VREDMAX(V),
+
+    // V - Vector Reduce Minimum Unsigned
+    // Like VREDSUM with the unsigned minimum.
+    VREDMINU(V),
+
+    // V - Vector Reduce Minimum
+    // Like VREDSUM with the signed minimum.
+    VREDMIN(V),
+
+    // V - Vector Reduce And
+    // Like VREDSUM with the bitwise AND.
+    VREDAND(V),
+
+    // V - Vector Reduce Or
+    // Like VREDSUM with the bitwise OR.
+    VREDOR(V),
+
+    // V - Vector Reduce Xor
+    // Like VREDSUM with the bitwise XOR.
+    VREDXOR(V),
+
+    // V - Vector Widening Reduce Sum Unsigned
+    // Like VREDSUM with the elements zero extended to the 2 * SEW of vs1 and vd.
+    VWREDSUMU(V),
+
+    // V - Vector Widening Reduce Sum
+    // Like VREDSUM with the elements sign extended to the 2 * SEW of vs1 and vd.
+    VWREDSUM(V),
+
+    // V - Vector Mask And
+    // Places the bitwise AND of the masks in vs2 and vs1 in vd.
+    VMAND { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Nand
+    // Places the inverted bitwise AND of the masks in vs2 and vs1 in vd.
+    VMNAND { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask And-Not
+    // Places the bitwise AND of the mask in vs2 and the inverted mask in vs1 in vd.
+    VMANDN { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Xor
+    // Places the bitwise XOR of the masks in vs2 and vs1 in vd.
+    VMXOR { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Or
+    // Places the bitwise OR of the masks in vs2 and vs1 in vd.
+    VMOR { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Nor
+    // Places the inverted bitwise OR of the masks in vs2 and vs1 in vd.
+    VMNOR { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Or-Not
+    // Places the bitwise OR of the mask in vs2 and the inverted mask in vs1 in vd.
+    VMORN { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Xnor
+    // Places the inverted bitwise XOR of the masks in vs2 and vs1 in vd.
+    VMXNOR { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Mask Population Count
+    // Places the number of the active bits set in the mask in vs2 in rd.
+    VCPOP { rd: u8, vs2: u8, vm: bool },
+
+    // V - Vector Find First Set Mask Bit
+    // Places the index of the first active bit set in the mask in vs2 in rd, or -1.
+    VFIRST { rd: u8, vs2: u8, vm: bool },
+
+    // V - Vector Set Before First Mask Bit
+    // Sets the bits of the mask in vd before the first active bit set in vs2.
+    VMSBF { vd: u8, vs2: u8, vm: bool },
+
+    // V - Vector Set Including First Mask Bit
+    // Sets the bits of the mask in vd up to the first active bit set in vs2.
+    VMSIF { vd: u8, vs2: u8, vm: bool },
+
+    // V - Vector Set Only First Mask Bit
+    // Sets the bit of the mask in vd of the first active bit set in vs2.
+    VMSOF { vd: u8, vs2: u8, vm: bool },
+
+    // V - Vector Iota
+    // Places the number of the active bits set in the mask in vs2 before each element
+    // in vd.
+    VIOTA { vd: u8, vs2: u8, vm: bool },
+
+    // V - Vector Element Index
+    // Places the index of each element in vd.
+    VID { vd: u8, vm: bool },
+
+    // V - Vector Move to Scalar
+    // Places the element 0 of vs2 sign extended in rd.
+    VMVXS { rd: u8, vs2: u8 },
+
+    // V - Vector Move from Scalar
+    // Places rs1 in the element 0 of vd.
+    VMVSX { vd: u8, rs1: u8 },
+
+    // V - Vector Slide Up
+    // Places the elements of vs2 in vd at their index + rs1 or the unsigned immediate,
+    // the elements below it are left as they are.
+    VSLIDEUP(V),
+
+    // V - Vector Slide Down
+    // Places the elements of vs2 in vd at their index - rs1 or the unsigned immediate,
+    // the elements past VLMAX in vs2 read as 0.
+    VSLIDEDOWN(V),
+
+    // V - Vector Slide 1 Up
+    // Places the elements of vs2 in vd at their index + 1, with rs1 in the element 0.
+    VSLIDE1UP(V),
+
+    // V - Vector Slide 1 Down
+    // Places the elements of vs2 in vd at their index - 1, with rs1 in the last
+    // element.
+    VSLIDE1DOWN(V),
+
+    // V - Vector Register Gather
+    // Places the elements of vs2 at the indices in vs1, rs1 or the unsigned immediate
+    // in vd, the indices past VLMAX read as 0.
+    VRGATHER(V),
+
+    // V - Vector Register Gather with 16 Bit Indices
+    // Like VRGATHER with the indices in vs1 always 16 bits wide.
+    VRGATHEREI16(V),
+
+    // V - Vector Compress
+    // Packs the elements of vs2 whose bit is set in the mask in vs1 into the lowest
+    // elements of vd.
+    VCOMPRESS { vd: u8, vs2: u8, vs1: u8 },
+
+    // V - Vector Whole Register Move
+    // Copies nr whole registers from vs2 on to vd, regardless of vtype and vl.
+    VMVR { vd: u8, vs2: u8, nr: u8 },
+
+    // I - Atomic Read/Write CSR
+    // Writes rs1 to the CSR and places its old value in rd. The CSR is not read at all
+    // when rd is x0.
+    CSRRW { rd: u8, rs1: u8, csr: u16 },
+
+    // I - Atomic Read and Set Bits in CSR
+    // Places the value of the CSR in rd and sets the bits that are set in rs1 on it. The
+    // CSR is not written at all when rs1 is x0.
+    CSRRS { rd: u8, rs1: u8, csr: u16 },
+
+    // I - Atomic Read and Clear Bits in CSR
+    // Places the value of the CSR in rd and clears the bits that are set in rs1 on it. The
+    // CSR is not written at all when rs1 is x0.
+    CSRRC { rd: u8, rs1: u8, csr: u16 },
+
+    // I - Atomic Read/Write CSR Immediate
+    // Like CSRRW but writes the zero extended 5 bit uimm instead of rs1.
+    CSRRWI { rd: u8, uimm: u8, csr: u16 },
+
+    // I - Atomic Read and Set Bits in CSR Immediate
+    // Like CSRRS but uses the zero extended 5 bit uimm instead of rs1. The CSR is not
+    // written at all when uimm is 0.
+    CSRRSI { rd: u8, uimm: u8, csr: u16 },
+
+    // I - Atomic Read and Clear Bits in CSR Immediate
+    // Like CSRRC but uses the zero extended 5 bit uimm instead of rs1. The CSR is not
+    // written at all when uimm is 0.
+    CSRRCI { rd: u8, uimm: u8, csr: u16 },
+
     // I - ECALL
-    // Trigger a trap into the runtime.
+    // Raise an environment call exception to request a service from the execution
+    // environment.
     ECALL,
 
-    // All the CSRR* instructions.
+    // I - EBREAK
+    // Raise a breakpoint exception to hand control over to a debugger, or to the
+    // execution environment.
+    EBREAK,
+
+    // Priv - MRET
+    // Return from a trap handled in machine mode to the address in mepc, restoring the
+    // interrupt enable and the privilege level that were stacked when the trap was
+    // taken.
+    MRET,
+
+    // Priv - Supervisor Memory-Management Fence
+    // Order the earlier updates to the page table before the accesses that follow it.
+    // rs1 and rs2 limit the fence to a virtual address and an address space.
+    SFENCEVMA { rs1: u8, rs2: u8 },
+
+    // Priv - SRET
+    // Return from a trap handled in supervisor mode to the address in sepc, like MRET
+    // but with the supervisor fields of mstatus.
+    SRET,
+
+    // Priv - WFI
+    // Park the hart until an interrupt becomes pending. It is illegal in user mode, and
+    // in supervisor mode when mstatus.TW is set.
+    WFI,
+
+    // I - Pause
+    // Hint that the hart is spinning, it is a FENCE that orders nothing.
+    PAUSE,
+
+    // I - Cache Block Clean
+    // Write the cache block holding the address in rs1 back to the memory, which
+    // needs the block to be readable or writable.
+    CBOCLEAN { rs1: u8 },
+
+    // I - Cache Block Flush
+    // Write the cache block holding the address in rs1 back to the memory and
+    // invalidate it.
+    CBOFLUSH { rs1: u8 },
+
+    // I - Cache Block Invalidate
+    // Invalidate the cache block holding the address in rs1 without writing it back,
+    // or flush it when menvcfg or senvcfg say so for the lower privilege levels.
+    CBOINVAL { rs1: u8 },
+
+    // I - Cache Block Zero
+    // Store zeros to the whole cache block holding the address in rs1.
+    CBOZERO { rs1: u8 },
+
+    // CM - Push
+    // Stores the registers of the list (ra, s0 and up, see push_list) below sp, the
+    // last one at sp - 4, and lowers sp by stack_adj.
+    CMPUSH { rlist: u8, stack_adj: u32 },
+
+    // CM - Pop
+    // Loads the registers of the list from the top of the stack frame of stack_adj
+    // bytes at sp, the layout CMPUSH leaves behind, and raises sp by stack_adj.
+    CMPOP { rlist: u8, stack_adj: u32 },
+
+    // CM - Pop and Return
+    // Like CMPOP, then returns to the address in ra.
+    CMPOPRET { rlist: u8, stack_adj: u32 },
+
+    // CM - Pop, Zero and Return
+    // Like CMPOP, then clears a0 and returns to the address in ra.
+    CMPOPRETZ { rlist: u8, stack_adj: u32 },
+
+    // CM - Move a0 and a1 to S Registers
+    // Copies a0 into r1s and a1 into r2s, which are two different registers of s0 to
+    // s7.
+    CMMVSA01 { r1s: u8, r2s: u8 },
+
+    // CM - Move S Registers to a0 and a1
+    // Copies r1s into a0 and r2s into a1, both of s0 to s7.
+    CMMVA01S { r1s: u8, r2s: u8 },
+
+    // CM - Jump via Table
+    // Jumps to the address in the entry of the jump table at jvt, fetched like an
+    // instruction. The lowest bit of the entry is ignored.
+    CMJT { index: u8 },
+
+    // CM - Jump via Table and Link
+    // Like CMJT, storing the address of the next instruction in ra.
+    CMJALT { index: u8 },
+
+    // FENCE and FENCE.I.
     IGNORE,
 }
 
@@ -185,8 +1886,8 @@ pub enum InstError {
     #[error(transparent)]
     State(#[from] state::Error),
 
-    #[error("suspend")]
-    Suspend,
+    #[error(transparent)]
+    Exception(#[from] Exception),
 }
 
 // Sign extends a number to be a negative value with a different bit size if the original
@@ -201,6 +1902,13 @@ macro_rules! sign_extend {
     };
 }
 
+// Sign extends a number like sign_extend, on to the 64 bits the registers are kept in.
+macro_rules! sign_extend_x {
+    ($size:expr, $val:expr) => {
+        sign_extend!($size, $val) as i32 as u64
+    };
+}
+
 macro_rules! add {
     ($a:expr, $b:expr) => {
         $a.wrapping_add($b)
@@ -220,9 +1928,825 @@ macro_rules! shr {
 }
 
 impl Inst {
+    // Whether the optional extension the instruction belongs to is turned on.
+    fn is_enabled(&self, extensions: &Extensions) -> bool {
+        match self {
+            Inst::SH1ADD { .. } | Inst::SH2ADD { .. } | Inst::SH3ADD { .. } => extensions.zba,
+
+            // The rotations, the logical operations with an inverted operand and the
+            // byte reverse are in both Zbb and Zbkb, zext.h is the pack of Zbkb with
+            // rs2 as x0.
+            Inst::ANDN { .. }
+            | Inst::ORN { .. }
+            | Inst::XNOR { .. }
+            | Inst::ROL { .. }
+            | Inst::ROR { .. }
+            | Inst::RORI { .. }
+            | Inst::REV8 { .. }
+            | Inst::ZEXTH { .. } => extensions.zbb || extensions.zbkb,
+
+            Inst::CLZ { .. }
+            | Inst::CTZ { .. }
+            | Inst::CPOP { .. }
+            | Inst::MAX { .. }
+            | Inst::MAXU { .. }
+            | Inst::MIN { .. }
+            | Inst::MINU { .. }
+            | Inst::SEXTB { .. }
+            | Inst::SEXTH { .. }
+            | Inst::ORCB { .. } => extensions.zbb,
+
+            Inst::CLMUL { .. } | Inst::CLMULH { .. } => extensions.zbc || extensions.zbkc,
+            Inst::CLMULR { .. } => extensions.zbc,
+
+            Inst::BCLR { .. }
+            | Inst::BCLRI { .. }
+            | Inst::BEXT { .. }
+            | Inst::BEXTI { .. }
+            | Inst::BINV { .. }
+            | Inst::BINVI { .. }
+            | Inst::BSET { .. }
+            | Inst::BSETI { .. } => extensions.zbs,
+
+            Inst::PACK { .. }
+            | Inst::PACKH { .. }
+            | Inst::BREV8 { .. }
+            | Inst::ZIP { .. }
+            | Inst::UNZIP { .. } => extensions.zbkb,
+
+            Inst::XPERM4 { .. } | Inst::XPERM8 { .. } => extensions.zbkx,
+
+            Inst::AES32ESI { .. } | Inst::AES32ESMI { .. } => extensions.zkne,
+            Inst::AES32DSI { .. } | Inst::AES32DSMI { .. } => extensions.zknd,
+
+            Inst::SHA256SIG0 { .. }
+            | Inst::SHA256SIG1 { .. }
+            | Inst::SHA256SUM0 { .. }
+            | Inst::SHA256SUM1 { .. }
+            | Inst::SHA512SIG0H { .. }
+            | Inst::SHA512SIG0L { .. }
+            | Inst::SHA512SIG1H { .. }
+            | Inst::SHA512SIG1L { .. }
+            | Inst::SHA512SUM0R { .. }
+            | Inst::SHA512SUM1R { .. } => extensions.zknh,
+
+            Inst::CZEROEQZ { .. } | Inst::CZERONEZ { .. } => extensions.zicond,
+
+            Inst::CBOCLEAN { .. } | Inst::CBOFLUSH { .. } | Inst::CBOINVAL { .. } => {
+                extensions.zicbom
+            }
+            Inst::CBOZERO { .. } => extensions.zicboz,
+
+            Inst::WRSNTO | Inst::WRSSTO => extensions.zawrs,
+
+            Inst::AMOCASW { .. } | Inst::AMOCASD { .. } => extensions.zacas,
+            Inst::AMOCASB { .. } | Inst::AMOCASH { .. } => extensions.zacas && extensions.zabha,
+            Inst::AMOSWAPB { .. }
+            | Inst::AMOADDB { .. }
+            | Inst::AMOXORB { .. }
+            | Inst::AMOANDB { .. }
+            | Inst::AMOORB { .. }
+            | Inst::AMOMINB { .. }
+            | Inst::AMOMAXB { .. }
+            | Inst::AMOMINUB { .. }
+            | Inst::AMOMAXUB { .. }
+            | Inst::AMOSWAPH { .. }
+            | Inst::AMOADDH { .. }
+            | Inst::AMOXORH { .. }
+            | Inst::AMOANDH { .. }
+            | Inst::AMOORH { .. }
+            | Inst::AMOMINH { .. }
+            | Inst::AMOMAXH { .. }
+            | Inst::AMOMINUH { .. }
+            | Inst::AMOMAXUH { .. } => extensions.zabha,
+
+            // Zfhmin is the half precision loads, stores, moves and conversions alone,
+            // Zfh the whole of the half precision arithmetic.
+            Inst::FLH { .. }
+            | Inst::FSH { .. }
+            | Inst::FMVXH { .. }
+            | Inst::FMVHX { .. }
+            | Inst::FCVTSH { .. }
+            | Inst::FCVTHS { .. }
+            | Inst::FCVTDH { .. }
+            | Inst::FCVTHD { .. } => extensions.zfh || extensions.zfhmin,
+
+            Inst::FLIH { .. }
+            | Inst::FMINMH { .. }
+            | Inst::FMAXMH { .. }
+            | Inst::FROUNDH { .. }
+            | Inst::FROUNDNXH { .. }
+            | Inst::FLTQH { .. }
+            | Inst::FLEQH { .. } => extensions.zfa && extensions.zfh,
+
+            Inst::FLIS { .. }
+            | Inst::FLID { .. }
+            | Inst::FMINMS { .. }
+            | Inst::FMAXMS { .. }
+            | Inst::FMINMD { .. }
+            | Inst::FMAXMD { .. }
+            | Inst::FROUNDS { .. }
+            | Inst::FROUNDNXS { .. }
+            | Inst::FROUNDD { .. }
+            | Inst::FROUNDNXD { .. }
+            | Inst::FLTQS { .. }
+            | Inst::FLEQS { .. }
+            | Inst::FLTQD { .. }
+            | Inst::FLEQD { .. }
+            | Inst::FCVTMODWD { .. }
+            | Inst::FMVHXD { .. }
+            | Inst::FMVPDX { .. } => extensions.zfa,
+
+            Inst::FMADDH(_)
+            | Inst::FMSUBH(_)
+            | Inst::FNMSUBH(_)
+            | Inst::FNMADDH(_)
+            | Inst::FADDH { .. }
+            | Inst::FSUBH { .. }
+            | Inst::FMULH { .. }
+            | Inst::FDIVH { .. }
+            | Inst::FSQRTH { .. }
+            | Inst::FSGNJH { .. }
+            | Inst::FSGNJNH { .. }
+            | Inst::FSGNJXH { .. }
+            | Inst::FMINH { .. }
+            | Inst::FMAXH { .. }
+            | Inst::FEQH { .. }
+            | Inst::FLTH { .. }
+            | Inst::FLEH { .. }
+            | Inst::FCLASSH { .. }
+            | Inst::FCVTWH { .. }
+            | Inst::FCVTWUH { .. }
+            | Inst::FCVTHW { .. }
+            | Inst::FCVTHWU { .. } => extensions.zfh,
+
+            _ if self.is_vector() => extensions.zve32x,
+
+            _ => true,
+        }
+    }
+
+    // Whether the instruction belongs to the floating point unit, which makes it
+    // illegal while the unit is off.
+    fn is_float(&self) -> bool {
+        matches!(
+            self,
+            Inst::FLW { .. }
+                | Inst::FSW { .. }
+                | Inst::FMADDS(_)
+                | Inst::FMSUBS(_)
+                | Inst::FNMSUBS(_)
+                | Inst::FNMADDS(_)
+                | Inst::FADDS { .. }
+                | Inst::FSUBS { .. }
+                | Inst::FMULS { .. }
+                | Inst::FDIVS { .. }
+                | Inst::FSQRTS { .. }
+                | Inst::FSGNJS { .. }
+                | Inst::FSGNJNS { .. }
+                | Inst::FSGNJXS { .. }
+                | Inst::FMINS { .. }
+                | Inst::FMAXS { .. }
+                | Inst::FCVTWS { .. }
+                | Inst::FCVTWUS { .. }
+                | Inst::FMVXW { .. }
+                | Inst::FEQS { .. }
+                | Inst::FLTS { .. }
+                | Inst::FLES { .. }
+                | Inst::FCLASSS { .. }
+                | Inst::FCVTSW { .. }
+                | Inst::FCVTSWU { .. }
+                | Inst::FMVWX { .. }
+                | Inst::FLD { .. }
+                | Inst::FSD { .. }
+                | Inst::FMADDD(_)
+                | Inst::FMSUBD(_)
+                | Inst::FNMSUBD(_)
+                | Inst::FNMADDD(_)
+                | Inst::FADDD { .. }
+                | Inst::FSUBD { .. }
+                | Inst::FMULD { .. }
+                | Inst::FDIVD { .. }
+                | Inst::FSQRTD { .. }
+                | Inst::FSGNJD { .. }
+                | Inst::FSGNJND { .. }
+                | Inst::FSGNJXD { .. }
+                | Inst::FMIND { .. }
+                | Inst::FMAXD { .. }
+                | Inst::FCVTSD { .. }
+                | Inst::FCVTDS { .. }
+                | Inst::FEQD { .. }
+                | Inst::FLTD { .. }
+                | Inst::FLED { .. }
+                | Inst::FCLASSD { .. }
+                | Inst::FCVTWD { .. }
+                | Inst::FCVTWUD { .. }
+                | Inst::FCVTDW { .. }
+                | Inst::FCVTDWU { .. }
+                | Inst::FLH { .. }
+                | Inst::FSH { .. }
+                | Inst::FMADDH(_)
+                | Inst::FMSUBH(_)
+                | Inst::FNMSUBH(_)
+                | Inst::FNMADDH(_)
+                | Inst::FADDH { .. }
+                | Inst::FSUBH { .. }
+                | Inst::FMULH { .. }
+                | Inst::FDIVH { .. }
+                | Inst::FSQRTH { .. }
+                | Inst::FSGNJH { .. }
+                | Inst::FSGNJNH { .. }
+                | Inst::FSGNJXH { .. }
+                | Inst::FMINH { .. }
+                | Inst::FMAXH { .. }
+                | Inst::FCVTSH { .. }
+                | Inst::FCVTHS { .. }
+                | Inst::FCVTDH { .. }
+                | Inst::FCVTHD { .. }
+                | Inst::FEQH { .. }
+                | Inst::FLTH { .. }
+                | Inst::FLEH { .. }
+                | Inst::FCLASSH { .. }
+                | Inst::FCVTWH { .. }
+                | Inst::FCVTWUH { .. }
+                | Inst::FCVTHW { .. }
+                | Inst::FCVTHWU { .. }
+                | Inst::FMVXH { .. }
+                | Inst::FMVHX { .. }
+                | Inst::FLIS { .. }
+                | Inst::FLID { .. }
+                | Inst::FLIH { .. }
+                | Inst::FMINMS { .. }
+                | Inst::FMAXMS { .. }
+                | Inst::FMINMD { .. }
+                | Inst::FMAXMD { .. }
+                | Inst::FMINMH { .. }
+                | Inst::FMAXMH { .. }
+                | Inst::FROUNDS { .. }
+                | Inst::FROUNDNXS { .. }
+                | Inst::FROUNDD { .. }
+                | Inst::FROUNDNXD { .. }
+                | Inst::FROUNDH { .. }
+                | Inst::FROUNDNXH { .. }
+                | Inst::FLTQS { .. }
+                | Inst::FLEQS { .. }
+                | Inst::FLTQD { .. }
+                | Inst::FLEQD { .. }
+                | Inst::FLTQH { .. }
+                | Inst::FLEQH { .. }
+                | Inst::FCVTMODWD { .. }
+                | Inst::FMVHXD { .. }
+                | Inst::FMVPDX { .. }
+        )
+    }
+
+    // Whether the instruction belongs to the vector unit, which makes it illegal while
+    // the unit is off.
+    fn is_vector(&self) -> bool {
+        matches!(
+            self,
+            Inst::VSETVLI { .. }
+                | Inst::VSETIVLI { .. }
+                | Inst::VSETVL { .. }
+                | Inst::VLE(_)
+                | Inst::VLEFF(_)
+                | Inst::VLSE(_)
+                | Inst::VLUXEI(_)
+                | Inst::VLOXEI(_)
+                | Inst::VLR(_)
+                | Inst::VLM(_)
+                | Inst::VSE(_)
+                | Inst::VSSE(_)
+                | Inst::VSUXEI(_)
+                | Inst::VSOXEI(_)
+                | Inst::VSR(_)
+                | Inst::VSM(_)
+                | Inst::VADD(_)
+                | Inst::VSUB(_)
+                | Inst::VRSUB(_)
+                | Inst::VWADDU(_)
+                | Inst::VWADD(_)
+                | Inst::VWSUBU(_)
+                | Inst::VWSUB(_)
+                | Inst::VWADDUW(_)
+                | Inst::VWADDW(_)
+                | Inst::VWSUBUW(_)
+                | Inst::VWSUBW(_)
+                | Inst::VZEXT { .. }
+                | Inst::VSEXT { .. }
+                | Inst::VADC(_)
+                | Inst::VMADC(_)
+                | Inst::VSBC(_)
+                | Inst::VMSBC(_)
+                | Inst::VAND(_)
+                | Inst::VOR(_)
+                | Inst::VXOR(_)
+                | Inst::VSLL(_)
+                | Inst::VSRL(_)
+                | Inst::VSRA(_)
+                | Inst::VNSRL(_)
+                | Inst::VNSRA(_)
+                | Inst::VMSEQ(_)
+                | Inst::VMSNE(_)
+                | Inst::VMSLTU(_)
+                | Inst::VMSLT(_)
+                | Inst::VMSLEU(_)
+                | Inst::VMSLE(_)
+                | Inst::VMSGTU(_)
+                | Inst::VMSGT(_)
+                | Inst::VMINU(_)
+                | Inst::VMIN(_)
+                | Inst::VMAXU(_)
+                | Inst::VMAX(_)
+                | Inst::VMUL(_)
+                | Inst::VMULH(_)
+                | Inst::VMULHU(_)
+                | Inst::VMULHSU(_)
+                | Inst::VDIVU(_)
+                | Inst::VDIV(_)
+                | Inst::VREMU(_)
+                | Inst::VREM(_)
+                | Inst::VWMUL(_)
+                | Inst::VWMULU(_)
+                | Inst::VWMULSU(_)
+                | Inst::VMACC(_)
+                | Inst::VNMSAC(_)
+                | Inst::VMADD(_)
+                | Inst::VNMSUB(_)
+                | Inst::VWMACCU(_)
+                | Inst::VWMACC(_)
+                | Inst::VWMACCSU(_)
+                | Inst::VWMACCUS(_)
+                | Inst::VMERGE(_)
+                | Inst::VMV(_)
+                | Inst::VSADDU(_)
+                | Inst::VSADD(_)
+                | Inst::VSSUBU(_)
+                | Inst::VSSUB(_)
+                | Inst::VAADDU(_)
+                | Inst::VAADD(_)
+                | Inst::VASUBU(_)
+                | Inst::VASUB(_)
+                | Inst::VSMUL(_)
+                | Inst::VSSRL(_)
+                | Inst::VSSRA(_)
+                | Inst::VNCLIPU(_)
+                | Inst::VNCLIP(_)
+                | Inst::VREDSUM(_)
+                | Inst::VREDMAXU(_)
+                | Inst::VREDMAX(_)
+                | Inst::VREDMINU(_)
+                | Inst::VREDMIN(_)
+                | Inst::VREDAND(_)
+                | Inst::VREDOR(_)
+                | Inst::VREDXOR(_)
+                | Inst::VWREDSUMU(_)
+                | Inst::VWREDSUM(_)
+                | Inst::VMAND { .. }
+                | Inst::VMNAND { .. }
+                | Inst::VMANDN { .. }
+                | Inst::VMXOR { .. }
+                | Inst::VMOR { .. }
+                | Inst::VMNOR { .. }
+                | Inst::VMORN { .. }
+                | Inst::VMXNOR { .. }
+                | Inst::VCPOP { .. }
+                | Inst::VFIRST { .. }
+                | Inst::VMSBF { .. }
+                | Inst::VMSIF { .. }
+                | Inst::VMSOF { .. }
+                | Inst::VIOTA { .. }
+                | Inst::VID { .. }
+                | Inst::VMVXS { .. }
+                | Inst::VMVSX { .. }
+                | Inst::VSLIDEUP(_)
+                | Inst::VSLIDEDOWN(_)
+                | Inst::VSLIDE1UP(_)
+                | Inst::VSLIDE1DOWN(_)
+                | Inst::VRGATHER(_)
+                | Inst::VRGATHEREI16(_)
+                | Inst::VCOMPRESS { .. }
+                | Inst::VMVR { .. }
+        )
+    }
+
+    // Whether the instruction is there for the XLEN of the machine. RV32 has neither
+    // the RV64 only instructions nor the shifts by 32 or more. The extensions a 64 bit
+    // machine does not have are turned off in its config instead.
+    fn is_supported(&self, xlen: u32) -> bool {
+        if xlen == 64 {
+            return true;
+        }
+
+        match self {
+            Inst::SLLI { shamt, .. } | Inst::SRLI { shamt, .. } | Inst::SRAI { shamt, .. } => {
+                *shamt < 32
+            }
+            _ => !matches!(
+                self,
+                Inst::LWU { .. }
+                    | Inst::LD { .. }
+                    | Inst::SD { .. }
+                    | Inst::ADDIW { .. }
+                    | Inst::SLLIW { .. }
+                    | Inst::SRLIW { .. }
+                    | Inst::SRAIW { .. }
+                    | Inst::ADDW { .. }
+                    | Inst::SUBW { .. }
+                    | Inst::SLLW { .. }
+                    | Inst::SRLW { .. }
+                    | Inst::SRAW { .. }
+                    | Inst::MULW { .. }
+                    | Inst::DIVW { .. }
+                    | Inst::DIVUW { .. }
+                    | Inst::REMW { .. }
+                    | Inst::REMUW { .. }
+                    | Inst::LRD { .. }
+                    | Inst::SCD { .. }
+                    | Inst::AMOSWAPD { .. }
+                    | Inst::AMOADDD { .. }
+                    | Inst::AMOXORD { .. }
+                    | Inst::AMOANDD { .. }
+                    | Inst::AMOORD { .. }
+                    | Inst::AMOMIND { .. }
+                    | Inst::AMOMAXD { .. }
+                    | Inst::AMOMINUD { .. }
+                    | Inst::AMOMAXUD { .. }
+            ),
+        }
+    }
+
+    // The highest numbered integer register the instruction refers to, x0 if it
+    // refers to none. RV32E only has x0 to x15.
+    pub fn max_x_register(&self) -> u8 {
+        match *self {
+            Inst::ADD { rd, rs1, rs2 }
+            | Inst::SUB { rd, rs1, rs2 }
+            | Inst::SLL { rd, rs1, rs2 }
+            | Inst::SRL { rd, rs1, rs2 }
+            | Inst::SRA { rd, rs1, rs2 }
+            | Inst::SLT { rd, rs1, rs2 }
+            | Inst::SLTU { rd, rs1, rs2 }
+            | Inst::XOR { rd, rs1, rs2 }
+            | Inst::OR { rd, rs1, rs2 }
+            | Inst::AND { rd, rs1, rs2 }
+            | Inst::ADDW { rd, rs1, rs2 }
+            | Inst::SUBW { rd, rs1, rs2 }
+            | Inst::SLLW { rd, rs1, rs2 }
+            | Inst::SRLW { rd, rs1, rs2 }
+            | Inst::SRAW { rd, rs1, rs2 }
+            | Inst::MUL { rd, rs1, rs2 }
+            | Inst::MULH { rd, rs1, rs2 }
+            | Inst::MULHSU { rd, rs1, rs2 }
+            | Inst::MULHU { rd, rs1, rs2 }
+            | Inst::DIV { rd, rs1, rs2 }
+            | Inst::DIVU { rd, rs1, rs2 }
+            | Inst::REM { rd, rs1, rs2 }
+            | Inst::REMU { rd, rs1, rs2 }
+            | Inst::MULW { rd, rs1, rs2 }
+            | Inst::DIVW { rd, rs1, rs2 }
+            | Inst::DIVUW { rd, rs1, rs2 }
+            | Inst::REMW { rd, rs1, rs2 }
+            | Inst::REMUW { rd, rs1, rs2 }
+            | Inst::SH1ADD { rd, rs1, rs2 }
+            | Inst::SH2ADD { rd, rs1, rs2 }
+            | Inst::SH3ADD { rd, rs1, rs2 }
+            | Inst::ANDN { rd, rs1, rs2 }
+            | Inst::ORN { rd, rs1, rs2 }
+            | Inst::XNOR { rd, rs1, rs2 }
+            | Inst::MAX { rd, rs1, rs2 }
+            | Inst::MAXU { rd, rs1, rs2 }
+            | Inst::MIN { rd, rs1, rs2 }
+            | Inst::MINU { rd, rs1, rs2 }
+            | Inst::ROL { rd, rs1, rs2 }
+            | Inst::ROR { rd, rs1, rs2 }
+            | Inst::CLMUL { rd, rs1, rs2 }
+            | Inst::CLMULH { rd, rs1, rs2 }
+            | Inst::CLMULR { rd, rs1, rs2 }
+            | Inst::BCLR { rd, rs1, rs2 }
+            | Inst::BEXT { rd, rs1, rs2 }
+            | Inst::BINV { rd, rs1, rs2 }
+            | Inst::BSET { rd, rs1, rs2 }
+            | Inst::PACK { rd, rs1, rs2 }
+            | Inst::PACKH { rd, rs1, rs2 }
+            | Inst::XPERM4 { rd, rs1, rs2 }
+            | Inst::XPERM8 { rd, rs1, rs2 }
+            | Inst::SHA512SIG0H { rd, rs1, rs2 }
+            | Inst::SHA512SIG0L { rd, rs1, rs2 }
+            | Inst::SHA512SIG1H { rd, rs1, rs2 }
+            | Inst::SHA512SIG1L { rd, rs1, rs2 }
+            | Inst::SHA512SUM0R { rd, rs1, rs2 }
+            | Inst::SHA512SUM1R { rd, rs1, rs2 }
+            | Inst::CZEROEQZ { rd, rs1, rs2 }
+            | Inst::CZERONEZ { rd, rs1, rs2 }
+            | Inst::VSETVL { rd, rs1, rs2 }
+            | Inst::AES32ESI { rd, rs1, rs2, .. }
+            | Inst::AES32ESMI { rd, rs1, rs2, .. }
+            | Inst::AES32DSI { rd, rs1, rs2, .. }
+            | Inst::AES32DSMI { rd, rs1, rs2, .. }
+            | Inst::SCW { rd, rs1, rs2, .. }
+            | Inst::AMOSWAPW { rd, rs1, rs2, .. }
+            | Inst::AMOADDW { rd, rs1, rs2, .. }
+            | Inst::AMOXORW { rd, rs1, rs2, .. }
+            | Inst::AMOANDW { rd, rs1, rs2, .. }
+            | Inst::AMOORW { rd, rs1, rs2, .. }
+            | Inst::AMOMINW { rd, rs1, rs2, .. }
+            | Inst::AMOMAXW { rd, rs1, rs2, .. }
+            | Inst::AMOMINUW { rd, rs1, rs2, .. }
+            | Inst::AMOMAXUW { rd, rs1, rs2, .. }
+            | Inst::SCD { rd, rs1, rs2, .. }
+            | Inst::AMOSWAPD { rd, rs1, rs2, .. }
+            | Inst::AMOADDD { rd, rs1, rs2, .. }
+            | Inst::AMOXORD { rd, rs1, rs2, .. }
+            | Inst::AMOANDD { rd, rs1, rs2, .. }
+            | Inst::AMOORD { rd, rs1, rs2, .. }
+            | Inst::AMOMIND { rd, rs1, rs2, .. }
+            | Inst::AMOMAXD { rd, rs1, rs2, .. }
+            | Inst::AMOMINUD { rd, rs1, rs2, .. }
+            | Inst::AMOMAXUD { rd, rs1, rs2, .. }
+            | Inst::AMOCASW { rd, rs1, rs2, .. }
+            | Inst::AMOCASD { rd, rs1, rs2, .. }
+            | Inst::AMOSWAPB { rd, rs1, rs2, .. }
+            | Inst::AMOADDB { rd, rs1, rs2, .. }
+            | Inst::AMOXORB { rd, rs1, rs2, .. }
+            | Inst::AMOANDB { rd, rs1, rs2, .. }
+            | Inst::AMOORB { rd, rs1, rs2, .. }
+            | Inst::AMOMINB { rd, rs1, rs2, .. }
+            | Inst::AMOMAXB { rd, rs1, rs2, .. }
+            | Inst::AMOMINUB { rd, rs1, rs2, .. }
+            | Inst::AMOMAXUB { rd, rs1, rs2, .. }
+            | Inst::AMOCASB { rd, rs1, rs2, .. }
+            | Inst::AMOSWAPH { rd, rs1, rs2, .. }
+            | Inst::AMOADDH { rd, rs1, rs2, .. }
+            | Inst::AMOXORH { rd, rs1, rs2, .. }
+            | Inst::AMOANDH { rd, rs1, rs2, .. }
+            | Inst::AMOORH { rd, rs1, rs2, .. }
+            | Inst::AMOMINH { rd, rs1, rs2, .. }
+            | Inst::AMOMAXH { rd, rs1, rs2, .. }
+            | Inst::AMOMINUH { rd, rs1, rs2, .. }
+            | Inst::AMOMAXUH { rd, rs1, rs2, .. }
+            | Inst::AMOCASH { rd, rs1, rs2, .. } => rd.max(rs1).max(rs2),
+
+            Inst::JALR { rd, rs1, .. }
+            | Inst::LB { rd, rs1, .. }
+            | Inst::LH { rd, rs1, .. }
+            | Inst::LW { rd, rs1, .. }
+            | Inst::LBU { rd, rs1, .. }
+            | Inst::LHU { rd, rs1, .. }
+            | Inst::LWU { rd, rs1, .. }
+            | Inst::LD { rd, rs1, .. }
+            | Inst::ADDI { rd, rs1, .. }
+            | Inst::SLTI { rd, rs1, .. }
+            | Inst::SLTIU { rd, rs1, .. }
+            | Inst::XORI { rd, rs1, .. }
+            | Inst::ORI { rd, rs1, .. }
+            | Inst::ANDI { rd, rs1, .. }
+            | Inst::ADDIW { rd, rs1, .. }
+            | Inst::SLLI { rd, rs1, .. }
+            | Inst::SRLI { rd, rs1, .. }
+            | Inst::SRAI { rd, rs1, .. }
+            | Inst::SLLIW { rd, rs1, .. }
+            | Inst::SRLIW { rd, rs1, .. }
+            | Inst::SRAIW { rd, rs1, .. }
+            | Inst::RORI { rd, rs1, .. }
+            | Inst::BCLRI { rd, rs1, .. }
+            | Inst::BEXTI { rd, rs1, .. }
+            | Inst::BINVI { rd, rs1, .. }
+            | Inst::BSETI { rd, rs1, .. }
+            | Inst::LRW { rd, rs1, .. }
+            | Inst::LRD { rd, rs1, .. }
+            | Inst::VSETVLI { rd, rs1, .. }
+            | Inst::CSRRW { rd, rs1, .. }
+            | Inst::CSRRS { rd, rs1, .. }
+            | Inst::CSRRC { rd, rs1, .. }
+            | Inst::CLZ { rd, rs1 }
+            | Inst::CTZ { rd, rs1 }
+            | Inst::CPOP { rd, rs1 }
+            | Inst::SEXTB { rd, rs1 }
+            | Inst::SEXTH { rd, rs1 }
+            | Inst::ZEXTH { rd, rs1 }
+            | Inst::ORCB { rd, rs1 }
+            | Inst::REV8 { rd, rs1 }
+            | Inst::BREV8 { rd, rs1 }
+            | Inst::ZIP { rd, rs1 }
+            | Inst::UNZIP { rd, rs1 }
+            | Inst::SHA256SIG0 { rd, rs1 }
+            | Inst::SHA256SIG1 { rd, rs1 }
+            | Inst::SHA256SUM0 { rd, rs1 }
+            | Inst::SHA256SUM1 { rd, rs1 } => rd.max(rs1),
+
+            // The floating point comparisons, classifications, moves and conversions
+            // to integers write an integer register.
+            Inst::LUI { rd, .. }
+            | Inst::AUIPC { rd, .. }
+            | Inst::JAL { rd, .. }
+            | Inst::FMVXW { rd, .. }
+            | Inst::FCLASSS { rd, .. }
+            | Inst::FCLASSD { rd, .. }
+            | Inst::FCLASSH { rd, .. }
+            | Inst::FMVXH { rd, .. }
+            | Inst::FCVTMODWD { rd, .. }
+            | Inst::FMVHXD { rd, .. }
+            | Inst::FEQS { rd, .. }
+            | Inst::FLTS { rd, .. }
+            | Inst::FLES { rd, .. }
+            | Inst::FEQD { rd, .. }
+            | Inst::FLTD { rd, .. }
+            | Inst::FLED { rd, .. }
+            | Inst::FEQH { rd, .. }
+            | Inst::FLTH { rd, .. }
+            | Inst::FLEH { rd, .. }
+            | Inst::FLTQS { rd, .. }
+            | Inst::FLEQS { rd, .. }
+            | Inst::FLTQD { rd, .. }
+            | Inst::FLEQD { rd, .. }
+            | Inst::FLTQH { rd, .. }
+            | Inst::FLEQH { rd, .. }
+            | Inst::FCVTWS { rd, .. }
+            | Inst::FCVTWUS { rd, .. }
+            | Inst::FCVTWD { rd, .. }
+            | Inst::FCVTWUD { rd, .. }
+            | Inst::FCVTWH { rd, .. }
+            | Inst::FCVTWUH { rd, .. }
+            | Inst::VSETIVLI { rd, .. }
+            | Inst::VCPOP { rd, .. }
+            | Inst::VFIRST { rd, .. }
+            | Inst::VMVXS { rd, .. }
+            | Inst::CSRRWI { rd, .. }
+            | Inst::CSRRSI { rd, .. }
+            | Inst::CSRRCI { rd, .. } => rd,
+
+            Inst::BEQ { rs1, rs2, .. }
+            | Inst::BNE { rs1, rs2, .. }
+            | Inst::BLT { rs1, rs2, .. }
+            | Inst::BLTU { rs1, rs2, .. }
+            | Inst::BGE { rs1, rs2, .. }
+            | Inst::BGEU { rs1, rs2, .. }
+            | Inst::SB { rs1, rs2, .. }
+            | Inst::SH { rs1, rs2, .. }
+            | Inst::SW { rs1, rs2, .. }
+            | Inst::SD { rs1, rs2, .. }
+            | Inst::SFENCEVMA { rs1, rs2, .. }
+            | Inst::FMVPDX { rs1, rs2, .. } => rs1.max(rs2),
+
+            // The floating point loads, stores, moves and conversions from integers
+            // read an integer register.
+            Inst::FLW { rs1, .. }
+            | Inst::FLD { rs1, .. }
+            | Inst::FLH { rs1, .. }
+            | Inst::FSW { rs1, .. }
+            | Inst::FSD { rs1, .. }
+            | Inst::FSH { rs1, .. }
+            | Inst::FMVWX { rs1, .. }
+            | Inst::FMVHX { rs1, .. }
+            | Inst::FCVTSW { rs1, .. }
+            | Inst::FCVTSWU { rs1, .. }
+            | Inst::FCVTDW { rs1, .. }
+            | Inst::FCVTDWU { rs1, .. }
+            | Inst::FCVTHW { rs1, .. }
+            | Inst::FCVTHWU { rs1, .. }
+            | Inst::VMVSX { rs1, .. }
+            | Inst::CBOCLEAN { rs1, .. }
+            | Inst::CBOFLUSH { rs1, .. }
+            | Inst::CBOINVAL { rs1, .. }
+            | Inst::CBOZERO { rs1, .. } => rs1,
+
+            // The strided vector loads and stores take the stride from rs2, the rest
+            // of them only have the base address in an integer register.
+            Inst::VLSE(mem) | Inst::VSSE(mem) => mem.rs1.max(mem.rs2),
+            Inst::VLE(mem)
+            | Inst::VLEFF(mem)
+            | Inst::VLUXEI(mem)
+            | Inst::VLOXEI(mem)
+            | Inst::VLR(mem)
+            | Inst::VLM(mem)
+            | Inst::VSE(mem)
+            | Inst::VSUXEI(mem)
+            | Inst::VSOXEI(mem)
+            | Inst::VSR(mem)
+            | Inst::VSM(mem) => mem.rs1,
+
+            Inst::VADD(v)
+            | Inst::VSUB(v)
+            | Inst::VRSUB(v)
+            | Inst::VWADDU(v)
+            | Inst::VWADD(v)
+            | Inst::VWSUBU(v)
+            | Inst::VWSUB(v)
+            | Inst::VWADDUW(v)
+            | Inst::VWADDW(v)
+            | Inst::VWSUBUW(v)
+            | Inst::VWSUBW(v)
+            | Inst::VADC(v)
+            | Inst::VMADC(v)
+            | Inst::VSBC(v)
+            | Inst::VMSBC(v)
+            | Inst::VAND(v)
+            | Inst::VOR(v)
+            | Inst::VXOR(v)
+            | Inst::VSLL(v)
+            | Inst::VSRL(v)
+            | Inst::VSRA(v)
+            | Inst::VNSRL(v)
+            | Inst::VNSRA(v)
+            | Inst::VMSEQ(v)
+            | Inst::VMSNE(v)
+            | Inst::VMSLTU(v)
+            | Inst::VMSLT(v)
+            | Inst::VMSLEU(v)
+            | Inst::VMSLE(v)
+            | Inst::VMSGTU(v)
+            | Inst::VMSGT(v)
+            | Inst::VMINU(v)
+            | Inst::VMIN(v)
+            | Inst::VMAXU(v)
+            | Inst::VMAX(v)
+            | Inst::VMUL(v)
+            | Inst::VMULH(v)
+            | Inst::VMULHU(v)
+            | Inst::VMULHSU(v)
+            | Inst::VDIVU(v)
+            | Inst::VDIV(v)
+            | Inst::VREMU(v)
+            | Inst::VREM(v)
+            | Inst::VWMUL(v)
+            | Inst::VWMULU(v)
+            | Inst::VWMULSU(v)
+            | Inst::VMACC(v)
+            | Inst::VNMSAC(v)
+            | Inst::VMADD(v)
+            | Inst::VNMSUB(v)
+            | Inst::VWMACCU(v)
+            | Inst::VWMACC(v)
+            | Inst::VWMACCSU(v)
+            | Inst::VWMACCUS(v)
+            | Inst::VMERGE(v)
+            | Inst::VMV(v)
+            | Inst::VSADDU(v)
+            | Inst::VSADD(v)
+            | Inst::VSSUBU(v)
+            | Inst::VSSUB(v)
+            | Inst::VAADDU(v)
+            | Inst::VAADD(v)
+            | Inst::VASUBU(v)
+            | Inst::VASUB(v)
+            | Inst::VSMUL(v)
+            | Inst::VSSRL(v)
+            | Inst::VSSRA(v)
+            | Inst::VNCLIPU(v)
+            | Inst::VNCLIP(v)
+            | Inst::VREDSUM(v)
+            | Inst::VREDMAXU(v)
+            | Inst::VREDMAX(v)
+            | Inst::VREDMINU(v)
+            | Inst::VREDMIN(v)
+            | Inst::VREDAND(v)
+            | Inst::VREDOR(v)
+            | Inst::VREDXOR(v)
+            | Inst::VWREDSUMU(v)
+            | Inst::VWREDSUM(v)
+            | Inst::VSLIDEUP(v)
+            | Inst::VSLIDEDOWN(v)
+            | Inst::VSLIDE1UP(v)
+            | Inst::VSLIDE1DOWN(v)
+            | Inst::VRGATHER(v)
+            | Inst::VRGATHEREI16(v) => match v.src {
+                VSrc::Scalar(rs1) => rs1,
+                _ => 0,
+            },
+
+            Inst::CMPUSH { rlist, .. }
+            | Inst::CMPOP { rlist, .. }
+            | Inst::CMPOPRET { rlist, .. }
+            | Inst::CMPOPRETZ { rlist, .. } => push_list(rlist).max().unwrap_or_default(),
+
+            Inst::CMMVSA01 { r1s, r2s } | Inst::CMMVA01S { r1s, r2s } => r1s.max(r2s),
+
+            _ => 0,
+        }
+    }
+
     // Executes the instruction on the state and returns a Result with the updated value of
     // PC. If None was passed, it is expected that the machine increments to the next instruction.
-    pub fn execute<const M: usize>(self, state: &mut State<M>) -> Result<Option<u32>, InstError> {
+    // The length is the size of the encoded instruction in bytes, which differs from 4 when it
+    // was expanded from a compressed instruction.
+    pub fn execute<const M: usize>(
+        self,
+        state: &mut State<M>,
+        len: u32,
+    ) -> Result<Option<u64>, InstError> {
+        if !self.is_supported(state.xlen()) || !self.is_enabled(&state.config().extensions) {
+            return Err(state::Error::IllegalOperation.into());
+        }
+
+        if self.is_float() && !state.is_fp_enabled() {
+            return Err(state::Error::IllegalOperation.into());
+        }
+
+        if self.is_vector() && !state.is_vector_enabled() {
+            return Err(state::Error::IllegalOperation.into());
+        }
+
         match self {
             // Upper immediates.
             Inst::LUI { rd, imm } => {
@@ -236,33 +2760,31 @@ impl Inst {
             Inst::AUIPC { rd, imm } => {
                 log::debug!(target: "exec", "auipc rd:{:x} imm:{:x}", rd, imm);
 
-                let val = add!(state.get_pc(), imm);
-                state.set_r(rd, val)?;
+                let val = add!(state.get_pc(), imm as i32 as u64);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
 
             // Jumps.
-            // TODO: Check for alignment and throw exception.
             Inst::JAL { rd, imm } => {
                 log::debug!(target: "exec", "jal rd:{:x} imm:{:x}", rd, imm);
 
                 let current_pc = state.get_pc();
-                state.set_r(rd, current_pc + 4)?;
+                let loc = jump(state, add!(current_pc, sign_extend_x!(21, imm)))?;
 
-                let loc = add!(current_pc, sign_extend!(21, imm));
+                state.set_x(rd, add!(current_pc, len as u64))?;
                 Ok(Some(loc))
             }
 
-            // TODO: Check for alignment and throw exception even though we assume it.
             Inst::JALR { rd, rs1, imm } => {
                 log::debug!(target: "exec", "jalr rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
-                let addr = addr >> 1 << 1;
+                let addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let addr = jump(state, addr >> 1 << 1)?;
 
                 let current_pc = state.get_pc();
-                state.set_r(rd, current_pc + 4)?;
+                state.set_x(rd, add!(current_pc, len as u64))?;
 
                 Ok(Some(addr))
             }
@@ -280,7 +2802,7 @@ impl Inst {
 
             Inst::BLT { rs1, rs2, imm } => {
                 log::debug!(target: "exec", "blt rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
-                branch(state, rs1, rs2, imm, |a, b| signed_cmp_lt(a, b))
+                branch(state, rs1, rs2, imm, |a, b| (a as i64) < (b as i64))
             }
 
             Inst::BLTU { rs1, rs2, imm } => {
@@ -290,19 +2812,19 @@ impl Inst {
 
             Inst::BGE { rs1, rs2, imm } => {
                 log::debug!(target: "exec", "bge rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
-                branch(state, rs1, rs2, imm, |a, b| signed_cmp_gt(a, b))
+                branch(state, rs1, rs2, imm, |a, b| (a as i64) >= (b as i64))
             }
 
             Inst::BGEU { rs1, rs2, imm } => {
                 log::debug!(target: "exec", "bgeu rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
-                branch(state, rs1, rs2, imm, |a, b| !(a < b))
+                branch(state, rs1, rs2, imm, |a, b| a >= b)
             }
 
             // Loads
             Inst::LB { rs1, rd, imm } => {
                 log::debug!(target: "exec", "lb rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
+                let addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
                 let val = state.get_mem_u8(addr)?;
                 state.set_r(rd, sign_extend!(8, val))?;
 
@@ -312,7 +2834,7 @@ impl Inst {
             Inst::LH { rs1, rd, imm } => {
                 log::debug!(target: "exec", "lh rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let base_addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
                 let val = state.get_mem_u16(base_addr)?;
                 state.set_r(rd, sign_extend!(16, val))?;
 
@@ -322,7 +2844,7 @@ impl Inst {
             Inst::LW { rd, rs1, imm } => {
                 log::debug!(target: "exec", "lw rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let base_addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
                 let val = state.get_mem_u32(base_addr)?;
                 state.set_r(rd, val)?;
 
@@ -332,8 +2854,9 @@ impl Inst {
             Inst::LBU { rd, rs1, imm } => {
                 log::debug!(target: "exec", "lbu rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
-                state.set_r(rd, state.get_mem_u8(addr)? as u32)?;
+                let addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u8(addr)?;
+                state.set_x(rd, val as u64)?;
 
                 Ok(None)
             }
@@ -341,8 +2864,29 @@ impl Inst {
             Inst::LHU { rd, rs1, imm } => {
                 log::debug!(target: "exec", "lhu rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let base_addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
-                state.set_r(rd, state.get_mem_u16(base_addr)? as u32)?;
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u16(base_addr)?;
+                state.set_x(rd, val as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::LWU { rd, rs1, imm } => {
+                log::debug!(target: "exec", "lwu rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u32(base_addr)?;
+                state.set_x(rd, val as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::LD { rd, rs1, imm } => {
+                log::debug!(target: "exec", "ld rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u64(base_addr)?;
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -351,8 +2895,8 @@ impl Inst {
             Inst::SB { rs1, rs2, imm } => {
                 log::debug!(target: "exec", "sb rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
 
-                let addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
-                state.set_mem_u8(addr, state.get_r(rs2)? as u8)?;
+                let addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_mem_u8(addr, state.get_x(rs2)? as u8)?;
 
                 Ok(None)
             }
@@ -360,8 +2904,8 @@ impl Inst {
             Inst::SH { rs1, rs2, imm } => {
                 log::debug!(target: "exec", "sh rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
 
-                let base_addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
-                state.set_mem_u16(base_addr, state.get_r(rs2)? as u16)?;
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_mem_u16(base_addr, state.get_x(rs2)? as u16)?;
 
                 Ok(None)
             }
@@ -369,18 +2913,27 @@ impl Inst {
             Inst::SW { rs1, rs2, imm } => {
                 log::debug!(target: "exec", "sw rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
 
-                let base_addr = add!(state.get_r(rs1)?, sign_extend!(12, imm));
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
                 state.set_mem_u32(base_addr, state.get_r(rs2)?)?;
 
                 Ok(None)
             }
 
+            Inst::SD { rs1, rs2, imm } => {
+                log::debug!(target: "exec", "sd rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_mem_u64(base_addr, state.get_x(rs2)?)?;
+
+                Ok(None)
+            }
+
             // Immediate Logical and arithematics.
             Inst::ADDI { rd, rs1, imm } => {
                 log::debug!(target: "exec", "addi rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let val = add!(state.get_r(rs1)?, sign_extend!(12, imm));
-                state.set_r(rd, val)?;
+                let val = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -388,8 +2941,8 @@ impl Inst {
             Inst::SLTI { rd, rs1, imm } => {
                 log::debug!(target: "exec", "slti rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let lt = signed_cmp_lt(state.get_r(rs1)?, sign_extend!(12, imm));
-                state.set_r(rd, if lt { 1 } else { 0 })?;
+                let lt = (state.get_x(rs1)? as i64) < sign_extend_x!(12, imm) as i64;
+                state.set_x(rd, if lt { 1 } else { 0 })?;
 
                 Ok(None)
             }
@@ -397,8 +2950,8 @@ impl Inst {
             Inst::SLTIU { rd, rs1, imm } => {
                 log::debug!(target: "exec", "sltiu rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let lt = state.get_r(rs1)? < sign_extend!(12, imm);
-                state.set_r(rd, if lt { 1 } else { 0 })?;
+                let lt = state.get_x(rs1)? < sign_extend_x!(12, imm);
+                state.set_x(rd, if lt { 1 } else { 0 })?;
 
                 Ok(None)
             }
@@ -406,8 +2959,8 @@ impl Inst {
             Inst::XORI { rd, rs1, imm } => {
                 log::debug!(target: "exec", "xori rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let val = state.get_r(rs1)? ^ sign_extend!(12, imm);
-                state.set_r(rd, val)?;
+                let val = state.get_x(rs1)? ^ sign_extend_x!(12, imm);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -415,8 +2968,8 @@ impl Inst {
             Inst::ORI { rd, rs1, imm } => {
                 log::debug!(target: "exec", "ori rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let val = state.get_r(rs1)? | sign_extend!(12, imm);
-                state.set_r(rd, val)?;
+                let val = state.get_x(rs1)? | sign_extend_x!(12, imm);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -424,18 +2977,27 @@ impl Inst {
             Inst::ANDI { rd, rs1, imm } => {
                 log::debug!(target: "exec", "andi rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
 
-                let val = state.get_r(rs1)? & sign_extend!(12, imm);
+                let val = state.get_x(rs1)? & sign_extend_x!(12, imm);
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ADDIW { rd, rs1, imm } => {
+                log::debug!(target: "exec", "addiw rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
+
+                let val = add!(state.get_r(rs1)?, sign_extend!(12, imm));
                 state.set_r(rd, val)?;
 
                 Ok(None)
             }
 
-            // Shifts
+            // Shifts, the logical right shifts move zeros in above XLEN.
             Inst::SLLI { rd, rs1, shamt } => {
                 log::debug!(target: "exec", "slli rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
 
-                let val = shl!(state.get_r(rs1)?, shamt as u32);
-                state.set_r(rd, val)?;
+                let val = shl!(state.get_x(rs1)?, shamt as u32);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -443,8 +3005,8 @@ impl Inst {
             Inst::SRLI { rd, rs1, shamt } => {
                 log::debug!(target: "exec", "srli rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
 
-                let val = shr!(state.get_r(rs1)?, shamt as u32);
-                state.set_r(rd, val)?;
+                let val = shr!(state.to_xlen(state.get_x(rs1)?), shamt as u32);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -452,6 +3014,33 @@ impl Inst {
             Inst::SRAI { rd, rs1, shamt } => {
                 log::debug!(target: "exec", "srai rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
 
+                let val = state.get_x(rs1)? as i64 >> shamt;
+                state.set_x(rd, val as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::SLLIW { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "slliw rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+
+                let val = shl!(state.get_r(rs1)?, shamt as u32);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SRLIW { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "srliw rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+
+                let val = shr!(state.get_r(rs1)?, shamt as u32);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SRAIW { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "sraiw rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+
                 let val = right_shift_arithmetic(state.get_r(rs1)?, shamt as u32);
                 state.set_r(rd, val)?;
 
@@ -462,8 +3051,8 @@ impl Inst {
             Inst::ADD { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "add rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = add!(state.get_r(rs1)?, state.get_r(rs2)?);
-                state.set_r(rd, val)?;
+                let val = add!(state.get_x(rs1)?, state.get_x(rs2)?);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -472,9 +3061,9 @@ impl Inst {
                 log::debug!(target: "exec", "sub rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
                 // Two's complement rs2 and add it to rs1.
-                let b = add!(!state.get_r(rs2)?, 1);
-                let val = add!(state.get_r(rs1)?, b);
-                state.set_r(rd, val)?;
+                let b = add!(!state.get_x(rs2)?, 1);
+                let val = add!(state.get_x(rs1)?, b);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -482,8 +3071,8 @@ impl Inst {
             Inst::SLT { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "slt rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let lt = signed_cmp_lt(state.get_r(rs1)?, state.get_r(rs2)?);
-                state.set_r(rd, if lt { 1 } else { 0 })?;
+                let lt = (state.get_x(rs1)? as i64) < state.get_x(rs2)? as i64;
+                state.set_x(rd, if lt { 1 } else { 0 })?;
 
                 Ok(None)
             }
@@ -491,17 +3080,19 @@ impl Inst {
             Inst::SLTU { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "sltu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let lt = state.get_r(rs1)? < state.get_r(rs2)?;
-                state.set_r(rd, if lt { 1 } else { 0 })?;
+                let lt = state.get_x(rs1)? < state.get_x(rs2)?;
+                state.set_x(rd, if lt { 1 } else { 0 })?;
 
                 Ok(None)
             }
 
+            // The shift amount is in the lowest log2(XLEN) bits of rs2.
             Inst::SLL { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "sll rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = shl!(state.get_r(rs1)?, state.get_r(rs2)?);
-                state.set_r(rd, val)?;
+                let amount = state.get_r(rs2)? & (state.xlen() - 1);
+                let val = shl!(state.get_x(rs1)?, amount);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -509,8 +3100,9 @@ impl Inst {
             Inst::SRL { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "srl rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = shr!(state.get_r(rs1)?, state.get_r(rs2)?);
-                state.set_r(rd, val)?;
+                let amount = state.get_r(rs2)? & (state.xlen() - 1);
+                let val = shr!(state.to_xlen(state.get_x(rs1)?), amount);
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -518,8 +3110,9 @@ impl Inst {
             Inst::SRA { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "sra rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = right_shift_arithmetic(state.get_r(rs1)?, state.get_r(rs2)?);
-                state.set_r(rd, val)?;
+                let amount = state.get_r(rs2)? & (state.xlen() - 1);
+                let val = state.get_x(rs1)? as i64 >> amount;
+                state.set_x(rd, val as u64)?;
 
                 Ok(None)
             }
@@ -527,8 +3120,8 @@ impl Inst {
             Inst::XOR { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "xor rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = state.get_r(rs1)? ^ state.get_r(rs2)?;
-                state.set_r(rd, val)?;
+                let val = state.get_x(rs1)? ^ state.get_x(rs2)?;
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -536,8 +3129,8 @@ impl Inst {
             Inst::OR { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "or rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = state.get_r(rs1)? | state.get_r(rs2)?;
-                state.set_r(rd, val)?;
+                let val = state.get_x(rs1)? | state.get_x(rs2)?;
+                state.set_x(rd, val)?;
 
                 Ok(None)
             }
@@ -545,77 +3138,3065 @@ impl Inst {
             Inst::AND { rd, rs1, rs2 } => {
                 log::debug!(target: "exec", "and rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-                let val = state.get_r(rs1)? & state.get_r(rs2)?;
+                let val = state.get_x(rs1)? & state.get_x(rs2)?;
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            // The word operations of RV64, on the lower 32 bits of the registers with
+            // the results sign extended.
+            Inst::ADDW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "addw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let val = add!(state.get_r(rs1)?, state.get_r(rs2)?);
                 state.set_r(rd, val)?;
 
                 Ok(None)
             }
 
-            // Indicate that we want to suspend execution in some manner here.
-            Inst::ECALL => {
-                log::debug!(target: "exec", "ecall");
-                Err(InstError::Suspend)
+            Inst::SUBW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "subw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let val = state.get_r(rs1)?.wrapping_sub(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
             }
 
-            // Fence, FenceI & CSR
-            Inst::IGNORE => {
-                log::debug!(target: "exec", "ignore");
+            Inst::SLLW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sllw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let val = shl!(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
                 Ok(None)
             }
-        }
-    }
-}
 
-// TODO: Check for alignment of the jump address if the branch will be taken.
-// It needs to be on a 4 byte boundary.
-fn branch<const M: usize, C: Fn(u32, u32) -> bool>(
-    state: &State<M>,
-    rs1: u8,
-    rs2: u8,
-    imm: u16,
-    cmp: C,
-) -> Result<Option<u32>, InstError> {
-    let a = state.get_r(rs1)?;
-    let b = state.get_r(rs2)?;
-    if cmp(a, b) {
-        let addr = add!(state.get_pc(), sign_extend!(13, imm));
-        Ok(Some(addr))
-    } else {
-        Ok(None)
-    }
-}
+            Inst::SRLW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "srlw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-// Even in case of negative numbers, the two's complement of a smaller number
-// will still be smaller than the other number.
-#[inline]
-fn signed_cmp_lt(a: u32, b: u32) -> bool {
-    if (a >> 31) == (b >> 31) {
-        a < b
-    } else {
-        (a >> 31) == 1
-    }
-}
+                let val = shr!(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
 
-// Even in case of negative numbers, the two's complement of a smaller number
-// will still be smaller than the other number.
-#[inline]
-fn signed_cmp_gt(a: u32, b: u32) -> bool {
-    if (a >> 31) == (b >> 31) {
-        !(a < b)
-    } else {
-        (a >> 31) == 0
-    }
-}
+                Ok(None)
+            }
 
-// TODO: I bet this can be optimized more.
-// Right shift the value while moving in sign bit of the original value instead.
-#[inline]
-fn right_shift_arithmetic(val: u32, amount: u32) -> u32 {
-    let amount = amount % 32;
+            Inst::SRAW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sraw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
 
-    if ((val >> 31) == 1) && (amount > 0) {
-        (val >> amount) | (u32::MAX << (32 - amount))
-    } else {
-        val >> amount
-    }
+                let val = right_shift_arithmetic(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            // Multiplication and division.
+            Inst::MUL { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "mul rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let val = state.get_x(rs1)?.wrapping_mul(state.get_x(rs2)?);
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            // The registers hold their values sign extended, so, the signed operands are
+            // already right, the unsigned ones are truncated to XLEN bits.
+            Inst::MULH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "mulh rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_x(rs1)? as i64 as i128;
+                let b = state.get_x(rs2)? as i64 as i128;
+                state.set_x(rd, ((a * b) >> state.xlen()) as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::MULHSU { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "mulhsu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_x(rs1)? as i64 as i128;
+                let b = state.to_xlen(state.get_x(rs2)?) as i128;
+                state.set_x(rd, ((a * b) >> state.xlen()) as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::MULHU { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "mulhu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.to_xlen(state.get_x(rs1)?) as u128;
+                let b = state.to_xlen(state.get_x(rs2)?) as u128;
+                state.set_x(rd, ((a * b) >> state.xlen()) as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::DIV { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "div rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_x(rs1)? as i64;
+                let b = state.get_x(rs2)? as i64;
+                let val = match b {
+                    0 => u64::MAX,
+                    // Overflow only happens on -2^(XLEN-1) / -1 where the result is the
+                    // dividend, on RV32 the 64 bit quotient is truncated back to it.
+                    b => a.wrapping_div(b) as u64,
+                };
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::DIVU { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "divu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.to_xlen(state.get_x(rs1)?);
+                let val = match state.to_xlen(state.get_x(rs2)?) {
+                    0 => u64::MAX,
+                    b => a / b,
+                };
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::REM { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "rem rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_x(rs1)? as i64;
+                let b = state.get_x(rs2)? as i64;
+                let val = match b {
+                    0 => a as u64,
+                    // Overflow only happens on -2^(XLEN-1) % -1 where the result is 0.
+                    b => a.wrapping_rem(b) as u64,
+                };
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::REMU { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "remu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.to_xlen(state.get_x(rs1)?);
+                let val = match state.to_xlen(state.get_x(rs2)?) {
+                    0 => a,
+                    b => a % b,
+                };
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            // The word multiplications and divisions of RV64.
+            Inst::MULW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "mulw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let val = state.get_r(rs1)?.wrapping_mul(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::DIVW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "divw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)? as i32;
+                let b = state.get_r(rs2)? as i32;
+                let val = match b {
+                    0 => u32::MAX,
+                    // Overflow only happens on -2^31 / -1 where the result is the dividend.
+                    b => a.wrapping_div(b) as u32,
+                };
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::DIVUW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "divuw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)?;
+                let val = match state.get_r(rs2)? {
+                    0 => u32::MAX,
+                    b => a / b,
+                };
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::REMW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "remw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)? as i32;
+                let b = state.get_r(rs2)? as i32;
+                let val = match b {
+                    0 => a as u32,
+                    // Overflow only happens on -2^31 % -1 where the result is 0.
+                    b => a.wrapping_rem(b) as u32,
+                };
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::REMUW { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "remuw rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)?;
+                let val = match state.get_r(rs2)? {
+                    0 => a,
+                    b => a % b,
+                };
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            // Bit manipulation.
+            Inst::SH1ADD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sh1add rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = add!(shl!(state.get_r(rs1)?, 1), state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SH2ADD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sh2add rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = add!(shl!(state.get_r(rs1)?, 2), state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SH3ADD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sh3add rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = add!(shl!(state.get_r(rs1)?, 3), state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ANDN { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "andn rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)? & !state.get_r(rs2)?;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ORN { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "orn rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)? | !state.get_r(rs2)?;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::XNOR { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "xnor rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = !(state.get_r(rs1)? ^ state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CLZ { rd, rs1 } => {
+                log::debug!(target: "exec", "clz rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)?.leading_zeros();
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CTZ { rd, rs1 } => {
+                log::debug!(target: "exec", "ctz rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)?.trailing_zeros();
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CPOP { rd, rs1 } => {
+                log::debug!(target: "exec", "cpop rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)?.count_ones();
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::MAX { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "max rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)?;
+                let b = state.get_r(rs2)?;
+                let val = (a as i32).max(b as i32) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::MAXU { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "maxu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)?.max(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::MIN { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "min rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)?;
+                let b = state.get_r(rs2)?;
+                let val = (a as i32).min(b as i32) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::MINU { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "minu rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)?.min(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SEXTB { rd, rs1 } => {
+                log::debug!(target: "exec", "sext.b rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)? as i8 as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SEXTH { rd, rs1 } => {
+                log::debug!(target: "exec", "sext.h rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)? as i16 as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ZEXTH { rd, rs1 } => {
+                log::debug!(target: "exec", "zext.h rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)? & 0xffff;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ROL { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "rol rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)?.rotate_left(state.get_r(rs2)? & 0b11_111);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ROR { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "ror rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)?.rotate_right(state.get_r(rs2)? & 0b11_111);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::RORI { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "rori rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+                let val = state.get_r(rs1)?.rotate_right(shamt as u32);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ORCB { rd, rs1 } => {
+                log::debug!(target: "exec", "orc.b rd:{:x} rs1:{:x}", rd, rs1);
+
+                let a = state.get_r(rs1)?;
+                let val = u32::from_le_bytes(a.to_le_bytes().map(|byte| match byte {
+                    0 => 0,
+                    _ => 0xff,
+                }));
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::REV8 { rd, rs1 } => {
+                log::debug!(target: "exec", "rev8 rd:{:x} rs1:{:x}", rd, rs1);
+                let val = state.get_r(rs1)?.swap_bytes();
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CLMUL { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "clmul rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = clmul(state.get_r(rs1)?, state.get_r(rs2)?) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CLMULH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "clmulh rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = (clmul(state.get_r(rs1)?, state.get_r(rs2)?) >> 32) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CLMULR { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "clmulr rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = (clmul(state.get_r(rs1)?, state.get_r(rs2)?) >> 31) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BCLR { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "bclr rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)? & !bit(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BCLRI { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "bclri rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+                let val = state.get_r(rs1)? & !bit(shamt as u32);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BEXT { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "bext rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = (state.get_r(rs1)? & bit(state.get_r(rs2)?) != 0) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BEXTI { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "bexti rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+                let val = (state.get_r(rs1)? & bit(shamt as u32) != 0) as u32;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BINV { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "binv rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)? ^ bit(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BINVI { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "binvi rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+                let val = state.get_r(rs1)? ^ bit(shamt as u32);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BSET { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "bset rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = state.get_r(rs1)? | bit(state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BSETI { rd, rs1, shamt } => {
+                log::debug!(target: "exec", "bseti rd:{:x} rs1:{:x} shamt:{:x}", rd, rs1, shamt);
+                let val = state.get_r(rs1)? | bit(shamt as u32);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            // Cryptography.
+            Inst::PACK { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "pack rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)?;
+                let b = state.get_r(rs2)?;
+                let val = (b << 16) | (a & 0xffff);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::PACKH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "packh rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let a = state.get_r(rs1)?;
+                let b = state.get_r(rs2)?;
+                let val = ((b & 0xff) << 8) | (a & 0xff);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::BREV8 { rd, rs1 } => {
+                log::debug!(target: "exec", "brev8 rd:{:x} rs1:{:x}", rd, rs1);
+                let val = u32::from_le_bytes(state.get_r(rs1)?.to_le_bytes().map(u8::reverse_bits));
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::ZIP { rd, rs1 } => {
+                log::debug!(target: "exec", "zip rd:{:x} rs1:{:x}", rd, rs1);
+
+                let a = state.get_r(rs1)?;
+                let val = (0..16).fold(0, |val, i| {
+                    val | (((a >> i) & 1) << (2 * i)) | (((a >> (i + 16)) & 1) << (2 * i + 1))
+                });
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::UNZIP { rd, rs1 } => {
+                log::debug!(target: "exec", "unzip rd:{:x} rs1:{:x}", rd, rs1);
+
+                let a = state.get_r(rs1)?;
+                let val = (0..16).fold(0, |val, i| {
+                    val | (((a >> (2 * i)) & 1) << i) | (((a >> (2 * i + 1)) & 1) << (i + 16))
+                });
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::XPERM4 { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "xperm4 rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = xperm(state.get_r(rs1)?, state.get_r(rs2)?, 4);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::XPERM8 { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "xperm8 rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = xperm(state.get_r(rs1)?, state.get_r(rs2)?, 8);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::AES32ESI { rd, rs1, rs2, bs } => {
+                log::debug!(target: "exec", "aes32esi rd:{:x} rs1:{:x} rs2:{:x} bs:{:x}", rd, rs1, rs2, bs);
+                let val = crypto::aes32(state.get_r(rs1)?, state.get_r(rs2)?, bs, true, false);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::AES32ESMI { rd, rs1, rs2, bs } => {
+                log::debug!(target: "exec", "aes32esmi rd:{:x} rs1:{:x} rs2:{:x} bs:{:x}", rd, rs1, rs2, bs);
+                let val = crypto::aes32(state.get_r(rs1)?, state.get_r(rs2)?, bs, true, true);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::AES32DSI { rd, rs1, rs2, bs } => {
+                log::debug!(target: "exec", "aes32dsi rd:{:x} rs1:{:x} rs2:{:x} bs:{:x}", rd, rs1, rs2, bs);
+                let val = crypto::aes32(state.get_r(rs1)?, state.get_r(rs2)?, bs, false, false);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::AES32DSMI { rd, rs1, rs2, bs } => {
+                log::debug!(target: "exec", "aes32dsmi rd:{:x} rs1:{:x} rs2:{:x} bs:{:x}", rd, rs1, rs2, bs);
+                let val = crypto::aes32(state.get_r(rs1)?, state.get_r(rs2)?, bs, false, true);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA256SIG0 { rd, rs1 } => {
+                log::debug!(target: "exec", "sha256sig0 rd:{:x} rs1:{:x}", rd, rs1);
+                let val = crypto::sha256_sig0(state.get_r(rs1)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA256SIG1 { rd, rs1 } => {
+                log::debug!(target: "exec", "sha256sig1 rd:{:x} rs1:{:x}", rd, rs1);
+                let val = crypto::sha256_sig1(state.get_r(rs1)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA256SUM0 { rd, rs1 } => {
+                log::debug!(target: "exec", "sha256sum0 rd:{:x} rs1:{:x}", rd, rs1);
+                let val = crypto::sha256_sum0(state.get_r(rs1)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA256SUM1 { rd, rs1 } => {
+                log::debug!(target: "exec", "sha256sum1 rd:{:x} rs1:{:x}", rd, rs1);
+                let val = crypto::sha256_sum1(state.get_r(rs1)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA512SIG0H { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sha512sig0h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = crypto::sha512_sig0h(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA512SIG0L { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sha512sig0l rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = crypto::sha512_sig0l(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA512SIG1H { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sha512sig1h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = crypto::sha512_sig1h(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA512SIG1L { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sha512sig1l rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = crypto::sha512_sig1l(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA512SUM0R { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sha512sum0r rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = crypto::sha512_sum0r(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SHA512SUM1R { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "sha512sum1r rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = crypto::sha512_sum1r(state.get_r(rs1)?, state.get_r(rs2)?);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            // Conditional operations.
+            Inst::CZEROEQZ { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "czero.eqz rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = match state.get_x(rs2)? {
+                    0 => 0,
+                    _ => state.get_x(rs1)?,
+                };
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::CZERONEZ { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "czero.nez rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let val = match state.get_x(rs2)? {
+                    0 => state.get_x(rs1)?,
+                    _ => 0,
+                };
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            // Atomics.
+            Inst::LRW { rd, rs1, aqrl } => {
+                log::debug!(target: "exec", "lr.w rd:{:x} rs1:{:x} aqrl:{:b}", rd, rs1, aqrl);
+
+                let addr = aligned(state, rs1, 4, Access::Load)?;
+                let val = state.get_mem_u32(addr)?;
+                state.set_reservation(addr);
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SCW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "sc.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+
+                let addr = aligned(state, rs1, 4, Access::Store)?;
+                if state.take_reservation() == Some(addr) {
+                    state.set_mem_u32(addr, state.get_r(rs2)?)?;
+                    state.set_r(rd, 0)?;
+                } else {
+                    state.set_r(rd, 1)?;
+                }
+
+                Ok(None)
+            }
+
+            Inst::AMOSWAPW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoswap.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |_, b| b)
+            }
+
+            Inst::AMOADDW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoadd.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |a, b| add!(a, b))
+            }
+
+            Inst::AMOXORW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoxor.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |a, b| a ^ b)
+            }
+
+            Inst::AMOANDW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoand.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |a, b| a & b)
+            }
+
+            Inst::AMOORW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoor.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |a, b| a | b)
+            }
+
+            Inst::AMOMINW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomin.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(
+                    state,
+                    rd,
+                    rs1,
+                    rs2,
+                    |a, b| if signed_cmp_lt(a, b) { a } else { b },
+                )
+            }
+
+            Inst::AMOMAXW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomax.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(
+                    state,
+                    rd,
+                    rs1,
+                    rs2,
+                    |a, b| if signed_cmp_gt(a, b) { a } else { b },
+                )
+            }
+
+            Inst::AMOMINUW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amominu.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |a, b| a.min(b))
+            }
+
+            Inst::AMOMAXUW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomaxu.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo(state, rd, rs1, rs2, |a, b| a.max(b))
+            }
+
+            Inst::LRD { rd, rs1, aqrl } => {
+                log::debug!(target: "exec", "lr.d rd:{:x} rs1:{:x} aqrl:{:b}", rd, rs1, aqrl);
+
+                let addr = aligned(state, rs1, 8, Access::Load)?;
+                let val = state.get_mem_u64(addr)?;
+                state.set_reservation(addr);
+                state.set_x(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::SCD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "sc.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+
+                let addr = aligned(state, rs1, 8, Access::Store)?;
+                if state.take_reservation() == Some(addr) {
+                    state.set_mem_u64(addr, state.get_x(rs2)?)?;
+                    state.set_x(rd, 0)?;
+                } else {
+                    state.set_x(rd, 1)?;
+                }
+
+                Ok(None)
+            }
+
+            Inst::AMOSWAPD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoswap.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |_, b| b)
+            }
+
+            Inst::AMOADDD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoadd.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| a.wrapping_add(b))
+            }
+
+            Inst::AMOXORD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoxor.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| a ^ b)
+            }
+
+            Inst::AMOANDD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoand.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| a & b)
+            }
+
+            Inst::AMOORD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoor.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| a | b)
+            }
+
+            Inst::AMOMIND { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomin.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| {
+                    if (a as i64) < (b as i64) { a } else { b }
+                })
+            }
+
+            Inst::AMOMAXD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomax.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| {
+                    if (a as i64) > (b as i64) { a } else { b }
+                })
+            }
+
+            Inst::AMOMINUD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amominu.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| a.min(b))
+            }
+
+            Inst::AMOMAXUD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomaxu.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_d(state, rd, rs1, rs2, |a, b| a.max(b))
+            }
+
+            Inst::AMOCASW { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amocas.w rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+
+                let addr = aligned(state, rs1, 4, Access::Store)?;
+                let val = state.cas_mem_u32(addr, state.get_r(rd)?, state.get_r(rs2)?)?;
+                state.set_r(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::AMOCASD { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amocas.d rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+
+                let addr = aligned(state, rs1, 8, Access::Store)?;
+                let val = state.cas_mem_u64(addr, get_pair(state, rd)?, get_pair(state, rs2)?)?;
+                set_pair(state, rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::AMOSWAPB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoswap.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |_, b| b)
+            }
+
+            Inst::AMOADDB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoadd.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| add!(a, b))
+            }
+
+            Inst::AMOXORB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoxor.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| a ^ b)
+            }
+
+            Inst::AMOANDB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoand.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| a & b)
+            }
+
+            Inst::AMOORB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoor.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| a | b)
+            }
+
+            Inst::AMOMINB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomin.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| {
+                    if (a as i8) < (b as i8) { a } else { b }
+                })
+            }
+
+            Inst::AMOMAXB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomax.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| {
+                    if (a as i8) > (b as i8) { a } else { b }
+                })
+            }
+
+            Inst::AMOMINUB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amominu.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| a.min(b))
+            }
+
+            Inst::AMOMAXUB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomaxu.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_b(state, rd, rs1, rs2, |a, b| a.max(b))
+            }
+
+            Inst::AMOCASB { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amocas.b rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+
+                let addr = state.get_x(rs1)?;
+                let val =
+                    state.cas_mem_u8(addr, state.get_r(rd)? as u8, state.get_r(rs2)? as u8)?;
+                state.set_r(rd, val as i8 as u32)?;
+
+                Ok(None)
+            }
+
+            Inst::AMOSWAPH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoswap.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |_, b| b)
+            }
+
+            Inst::AMOADDH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoadd.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| add!(a, b))
+            }
+
+            Inst::AMOXORH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoxor.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| a ^ b)
+            }
+
+            Inst::AMOANDH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoand.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| a & b)
+            }
+
+            Inst::AMOORH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amoor.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| a | b)
+            }
+
+            Inst::AMOMINH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomin.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| {
+                    if (a as i16) < (b as i16) { a } else { b }
+                })
+            }
+
+            Inst::AMOMAXH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomax.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| {
+                    if (a as i16) > (b as i16) { a } else { b }
+                })
+            }
+
+            Inst::AMOMINUH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amominu.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| a.min(b))
+            }
+
+            Inst::AMOMAXUH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amomaxu.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+                amo_h(state, rd, rs1, rs2, |a, b| a.max(b))
+            }
+
+            Inst::AMOCASH { rd, rs1, rs2, aqrl } => {
+                log::debug!(target: "exec", "amocas.h rd:{:x} rs1:{:x} rs2:{:x} aqrl:{:b}", rd, rs1, rs2, aqrl);
+
+                let addr = aligned(state, rs1, 2, Access::Store)?;
+                let val =
+                    state.cas_mem_u16(addr, state.get_r(rd)? as u16, state.get_r(rs2)? as u16)?;
+                state.set_r(rd, val as i16 as u32)?;
+
+                Ok(None)
+            }
+
+            Inst::WRSNTO => {
+                log::debug!(target: "exec", "wrs.nto");
+
+                trap::wait_on_reservation(state)?;
+                state.hint_yield();
+                Ok(None)
+            }
+
+            // The short timeout is already over by the next step.
+            Inst::WRSSTO => {
+                log::debug!(target: "exec", "wrs.sto");
+
+                state.hint_yield();
+                Ok(None)
+            }
+
+            // Single precision floating point.
+            Inst::FLW { rd, rs1, imm } => {
+                log::debug!(target: "exec", "flw rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u32(base_addr)?;
+                set_float(state, F32, rd, val as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::FSW { rs1, rs2, imm } => {
+                log::debug!(target: "exec", "fsw rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_mem_u32(base_addr, state.get_f(rs2)? as u32)?;
+
+                Ok(None)
+            }
+
+            Inst::FMADDS(r4) => {
+                log::debug!(target: "exec", "fmadd.s rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F32, r4, false, false)
+            }
+
+            Inst::FMSUBS(r4) => {
+                log::debug!(target: "exec", "fmsub.s rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F32, r4, false, true)
+            }
+
+            Inst::FNMSUBS(r4) => {
+                log::debug!(target: "exec", "fnmsub.s rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F32, r4, true, false)
+            }
+
+            Inst::FNMADDS(r4) => {
+                log::debug!(target: "exec", "fnmadd.s rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F32, r4, true, true)
+            }
+
+            Inst::FADDS { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fadd.s rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F32, rd, rs1, rs2, rm, |sf, a, b| sf.add(F32, a, b))
+            }
+
+            Inst::FSUBS { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fsub.s rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F32, rd, rs1, rs2, rm, |sf, a, b| sf.sub(F32, a, b))
+            }
+
+            Inst::FMULS { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fmul.s rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F32, rd, rs1, rs2, rm, |sf, a, b| sf.mul(F32, a, b))
+            }
+
+            Inst::FDIVS { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fdiv.s rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F32, rd, rs1, rs2, rm, |sf, a, b| sf.div(F32, a, b))
+            }
+
+            Inst::FSQRTS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fsqrt.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F32, rd, rs1, 0, rm, |sf, a, _| sf.sqrt(F32, a))
+            }
+
+            Inst::FSGNJS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnj.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F32, a, b, |_, b| b)
+                })
+            }
+
+            Inst::FSGNJNS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnjn.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F32, a, b, |_, b| !b)
+                })
+            }
+
+            Inst::FSGNJXS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnjx.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F32, a, b, |a, b| a != b)
+                })
+            }
+
+            Inst::FMINS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmin.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.min_max(F32, a, b, false)
+                })
+            }
+
+            Inst::FMAXS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmax.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.min_max(F32, a, b, true)
+                })
+            }
+
+            Inst::FCVTWS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.w.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_to_int(state, F32, rd, rs1, rm, |sf, a| {
+                    sf.float_to_int(F32, a, true, 32) as u32
+                })
+            }
+
+            Inst::FCVTWUS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.wu.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_to_int(state, F32, rd, rs1, rm, |sf, a| {
+                    sf.float_to_int(F32, a, false, 32) as u32
+                })
+            }
+
+            Inst::FMVXW { rd, rs1 } => {
+                log::debug!(target: "exec", "fmv.x.w rd:{:x} rs1:{:x}", rd, rs1);
+
+                state.set_r(rd, state.get_f(rs1)? as u32)?;
+
+                Ok(None)
+            }
+
+            Inst::FEQS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "feq.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F32, rs2)?;
+                float_to_int(state, F32, rd, rs1, RM_NONE, |sf, a| {
+                    sf.eq(F32, a, b) as u32
+                })
+            }
+
+            Inst::FLTS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "flt.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F32, rs2)?;
+                float_to_int(state, F32, rd, rs1, RM_NONE, |sf, a| {
+                    sf.lt(F32, a, b) as u32
+                })
+            }
+
+            Inst::FLES { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fle.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F32, rs2)?;
+                float_to_int(state, F32, rd, rs1, RM_NONE, |sf, a| {
+                    sf.le(F32, a, b) as u32
+                })
+            }
+
+            Inst::FCLASSS { rd, rs1 } => {
+                log::debug!(target: "exec", "fclass.s rd:{:x} rs1:{:x}", rd, rs1);
+                float_to_int(state, F32, rd, rs1, RM_NONE, |_, a| {
+                    Softfloat::classify(F32, a)
+                })
+            }
+
+            Inst::FCVTSW { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.s.w rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                int_to_float(state, F32, rd, rs1, rm, |sf, a| {
+                    sf.int_to_float(F32, a, true, 32)
+                })
+            }
+
+            Inst::FCVTSWU { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.s.wu rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                int_to_float(state, F32, rd, rs1, rm, |sf, a| {
+                    sf.int_to_float(F32, a, false, 32)
+                })
+            }
+
+            Inst::FMVWX { rd, rs1 } => {
+                log::debug!(target: "exec", "fmv.w.x rd:{:x} rs1:{:x}", rd, rs1);
+
+                set_float(state, F32, rd, state.get_r(rs1)? as u64)?;
+
+                Ok(None)
+            }
+
+            // Double precision floating point.
+            Inst::FLD { rd, rs1, imm } => {
+                log::debug!(target: "exec", "fld rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u64(base_addr)?;
+                state.set_f(rd, val)?;
+
+                Ok(None)
+            }
+
+            Inst::FSD { rs1, rs2, imm } => {
+                log::debug!(target: "exec", "fsd rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_mem_u64(base_addr, state.get_f(rs2)?)?;
+
+                Ok(None)
+            }
+
+            Inst::FMADDD(r4) => {
+                log::debug!(target: "exec", "fmadd.d rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F64, r4, false, false)
+            }
+
+            Inst::FMSUBD(r4) => {
+                log::debug!(target: "exec", "fmsub.d rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F64, r4, false, true)
+            }
+
+            Inst::FNMSUBD(r4) => {
+                log::debug!(target: "exec", "fnmsub.d rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F64, r4, true, false)
+            }
+
+            Inst::FNMADDD(r4) => {
+                log::debug!(target: "exec", "fnmadd.d rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F64, r4, true, true)
+            }
+
+            Inst::FADDD { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fadd.d rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F64, rd, rs1, rs2, rm, |sf, a, b| sf.add(F64, a, b))
+            }
+
+            Inst::FSUBD { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fsub.d rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F64, rd, rs1, rs2, rm, |sf, a, b| sf.sub(F64, a, b))
+            }
+
+            Inst::FMULD { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fmul.d rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F64, rd, rs1, rs2, rm, |sf, a, b| sf.mul(F64, a, b))
+            }
+
+            Inst::FDIVD { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fdiv.d rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F64, rd, rs1, rs2, rm, |sf, a, b| sf.div(F64, a, b))
+            }
+
+            Inst::FSQRTD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fsqrt.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F64, rd, rs1, 0, rm, |sf, a, _| sf.sqrt(F64, a))
+            }
+
+            Inst::FSGNJD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnj.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F64, a, b, |_, b| b)
+                })
+            }
+
+            Inst::FSGNJND { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnjn.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F64, a, b, |_, b| !b)
+                })
+            }
+
+            Inst::FSGNJXD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnjx.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F64, a, b, |a, b| a != b)
+                })
+            }
+
+            Inst::FMIND { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmin.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.min_max(F64, a, b, false)
+                })
+            }
+
+            Inst::FMAXD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmax.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.min_max(F64, a, b, true)
+                })
+            }
+
+            Inst::FCVTSD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.s.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                convert_float(state, F64, F32, rd, rs1, rm)
+            }
+
+            Inst::FCVTDS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.d.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                convert_float(state, F32, F64, rd, rs1, rm)
+            }
+
+            Inst::FEQD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "feq.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F64, rs2)?;
+                float_to_int(state, F64, rd, rs1, RM_NONE, |sf, a| {
+                    sf.eq(F64, a, b) as u32
+                })
+            }
+
+            Inst::FLTD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "flt.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F64, rs2)?;
+                float_to_int(state, F64, rd, rs1, RM_NONE, |sf, a| {
+                    sf.lt(F64, a, b) as u32
+                })
+            }
+
+            Inst::FLED { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fle.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F64, rs2)?;
+                float_to_int(state, F64, rd, rs1, RM_NONE, |sf, a| {
+                    sf.le(F64, a, b) as u32
+                })
+            }
+
+            Inst::FCLASSD { rd, rs1 } => {
+                log::debug!(target: "exec", "fclass.d rd:{:x} rs1:{:x}", rd, rs1);
+                float_to_int(state, F64, rd, rs1, RM_NONE, |_, a| {
+                    Softfloat::classify(F64, a)
+                })
+            }
+
+            Inst::FCVTWD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.w.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_to_int(state, F64, rd, rs1, rm, |sf, a| {
+                    sf.float_to_int(F64, a, true, 32) as u32
+                })
+            }
+
+            Inst::FCVTWUD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.wu.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_to_int(state, F64, rd, rs1, rm, |sf, a| {
+                    sf.float_to_int(F64, a, false, 32) as u32
+                })
+            }
+
+            Inst::FCVTDW { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.d.w rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                int_to_float(state, F64, rd, rs1, rm, |sf, a| {
+                    sf.int_to_float(F64, a, true, 32)
+                })
+            }
+
+            Inst::FCVTDWU { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.d.wu rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                int_to_float(state, F64, rd, rs1, rm, |sf, a| {
+                    sf.int_to_float(F64, a, false, 32)
+                })
+            }
+
+            // Half precision floating point.
+            Inst::FLH { rd, rs1, imm } => {
+                log::debug!(target: "exec", "flh rd:{:x} rs1:{:x} imm:{:x}", rd, rs1, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                let val = state.get_mem_u16(base_addr)?;
+                set_float(state, F16, rd, val as u64)?;
+
+                Ok(None)
+            }
+
+            Inst::FSH { rs1, rs2, imm } => {
+                log::debug!(target: "exec", "fsh rs1:{:x} rs2:{:x} imm:{:x}", rs1, rs2, imm);
+
+                let base_addr = add!(state.get_x(rs1)?, sign_extend_x!(12, imm));
+                state.set_mem_u16(base_addr, state.get_f(rs2)? as u16)?;
+
+                Ok(None)
+            }
+
+            Inst::FMADDH(r4) => {
+                log::debug!(target: "exec", "fmadd.h rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F16, r4, false, false)
+            }
+
+            Inst::FMSUBH(r4) => {
+                log::debug!(target: "exec", "fmsub.h rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F16, r4, false, true)
+            }
+
+            Inst::FNMSUBH(r4) => {
+                log::debug!(target: "exec", "fnmsub.h rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F16, r4, true, false)
+            }
+
+            Inst::FNMADDH(r4) => {
+                log::debug!(target: "exec", "fnmadd.h rd:{:x} rs1:{:x} rs2:{:x} rs3:{:x} rm:{:x}", r4.rd, r4.rs1, r4.rs2, r4.rs3, r4.rm);
+                mul_add(state, F16, r4, true, true)
+            }
+
+            Inst::FADDH { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fadd.h rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F16, rd, rs1, rs2, rm, |sf, a, b| sf.add(F16, a, b))
+            }
+
+            Inst::FSUBH { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fsub.h rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F16, rd, rs1, rs2, rm, |sf, a, b| sf.sub(F16, a, b))
+            }
+
+            Inst::FMULH { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fmul.h rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F16, rd, rs1, rs2, rm, |sf, a, b| sf.mul(F16, a, b))
+            }
+
+            Inst::FDIVH { rd, rs1, rs2, rm } => {
+                log::debug!(target: "exec", "fdiv.h rd:{:x} rs1:{:x} rs2:{:x} rm:{:x}", rd, rs1, rs2, rm);
+                float_op(state, F16, rd, rs1, rs2, rm, |sf, a, b| sf.div(F16, a, b))
+            }
+
+            Inst::FSQRTH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fsqrt.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F16, rd, rs1, 0, rm, |sf, a, _| sf.sqrt(F16, a))
+            }
+
+            Inst::FSGNJH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnj.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F16, a, b, |_, b| b)
+                })
+            }
+
+            Inst::FSGNJNH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnjn.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F16, a, b, |_, b| !b)
+                })
+            }
+
+            Inst::FSGNJXH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fsgnjx.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |_, a, b| {
+                    Softfloat::sign_inject(F16, a, b, |a, b| a != b)
+                })
+            }
+
+            Inst::FMINH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmin.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.min_max(F16, a, b, false)
+                })
+            }
+
+            Inst::FMAXH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmax.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.min_max(F16, a, b, true)
+                })
+            }
+
+            Inst::FCVTSH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.s.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                convert_float(state, F16, F32, rd, rs1, rm)
+            }
+
+            Inst::FCVTHS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.h.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                convert_float(state, F32, F16, rd, rs1, rm)
+            }
+
+            Inst::FCVTDH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.d.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                convert_float(state, F16, F64, rd, rs1, rm)
+            }
+
+            Inst::FCVTHD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.h.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                convert_float(state, F64, F16, rd, rs1, rm)
+            }
+
+            Inst::FEQH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "feq.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F16, rs2)?;
+                float_to_int(state, F16, rd, rs1, RM_NONE, |sf, a| {
+                    sf.eq(F16, a, b) as u32
+                })
+            }
+
+            Inst::FLTH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "flt.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F16, rs2)?;
+                float_to_int(state, F16, rd, rs1, RM_NONE, |sf, a| {
+                    sf.lt(F16, a, b) as u32
+                })
+            }
+
+            Inst::FLEH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fle.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F16, rs2)?;
+                float_to_int(state, F16, rd, rs1, RM_NONE, |sf, a| {
+                    sf.le(F16, a, b) as u32
+                })
+            }
+
+            Inst::FCLASSH { rd, rs1 } => {
+                log::debug!(target: "exec", "fclass.h rd:{:x} rs1:{:x}", rd, rs1);
+                float_to_int(state, F16, rd, rs1, RM_NONE, |_, a| {
+                    Softfloat::classify(F16, a)
+                })
+            }
+
+            Inst::FCVTWH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.w.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_to_int(state, F16, rd, rs1, rm, |sf, a| {
+                    sf.float_to_int(F16, a, true, 32) as u32
+                })
+            }
+
+            Inst::FCVTWUH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.wu.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_to_int(state, F16, rd, rs1, rm, |sf, a| {
+                    sf.float_to_int(F16, a, false, 32) as u32
+                })
+            }
+
+            Inst::FCVTHW { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.h.w rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                int_to_float(state, F16, rd, rs1, rm, |sf, a| {
+                    sf.int_to_float(F16, a, true, 32)
+                })
+            }
+
+            Inst::FCVTHWU { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fcvt.h.wu rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                int_to_float(state, F16, rd, rs1, rm, |sf, a| {
+                    sf.int_to_float(F16, a, false, 32)
+                })
+            }
+
+            Inst::FMVXH { rd, rs1 } => {
+                log::debug!(target: "exec", "fmv.x.h rd:{:x} rs1:{:x}", rd, rs1);
+
+                state.set_r(rd, sign_extend!(16, state.get_f(rs1)? as u16))?;
+
+                Ok(None)
+            }
+
+            Inst::FMVHX { rd, rs1 } => {
+                log::debug!(target: "exec", "fmv.h.x rd:{:x} rs1:{:x}", rd, rs1);
+
+                set_float(state, F16, rd, state.get_r(rs1)? as u16 as u64)?;
+
+                Ok(None)
+            }
+
+            // Zfa, the additional floating point instructions.
+            Inst::FLIS { rd, index } => {
+                log::debug!(target: "exec", "fli.s rd:{:x} index:{:x}", rd, index);
+
+                set_float(state, F32, rd, Softfloat::constant(F32, index))?;
+
+                Ok(None)
+            }
+
+            Inst::FLID { rd, index } => {
+                log::debug!(target: "exec", "fli.d rd:{:x} index:{:x}", rd, index);
+
+                set_float(state, F64, rd, Softfloat::constant(F64, index))?;
+
+                Ok(None)
+            }
+
+            Inst::FLIH { rd, index } => {
+                log::debug!(target: "exec", "fli.h rd:{:x} index:{:x}", rd, index);
+
+                set_float(state, F16, rd, Softfloat::constant(F16, index))?;
+
+                Ok(None)
+            }
+
+            Inst::FMINMS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fminm.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.minimum_maximum(F32, a, b, false)
+                })
+            }
+
+            Inst::FMAXMS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmaxm.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F32, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.minimum_maximum(F32, a, b, true)
+                })
+            }
+
+            Inst::FMINMD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fminm.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.minimum_maximum(F64, a, b, false)
+                })
+            }
+
+            Inst::FMAXMD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmaxm.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F64, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.minimum_maximum(F64, a, b, true)
+                })
+            }
+
+            Inst::FMINMH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fminm.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.minimum_maximum(F16, a, b, false)
+                })
+            }
+
+            Inst::FMAXMH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmaxm.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                float_op(state, F16, rd, rs1, rs2, RM_NONE, |sf, a, b| {
+                    sf.minimum_maximum(F16, a, b, true)
+                })
+            }
+
+            Inst::FROUNDS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fround.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F32, rd, rs1, 0, rm, |sf, a, _| {
+                    sf.round_to_integral(F32, a, false)
+                })
+            }
+
+            Inst::FROUNDNXS { rd, rs1, rm } => {
+                log::debug!(target: "exec", "froundnx.s rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F32, rd, rs1, 0, rm, |sf, a, _| {
+                    sf.round_to_integral(F32, a, true)
+                })
+            }
+
+            Inst::FROUNDD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fround.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F64, rd, rs1, 0, rm, |sf, a, _| {
+                    sf.round_to_integral(F64, a, false)
+                })
+            }
+
+            Inst::FROUNDNXD { rd, rs1, rm } => {
+                log::debug!(target: "exec", "froundnx.d rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F64, rd, rs1, 0, rm, |sf, a, _| {
+                    sf.round_to_integral(F64, a, true)
+                })
+            }
+
+            Inst::FROUNDH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "fround.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F16, rd, rs1, 0, rm, |sf, a, _| {
+                    sf.round_to_integral(F16, a, false)
+                })
+            }
+
+            Inst::FROUNDNXH { rd, rs1, rm } => {
+                log::debug!(target: "exec", "froundnx.h rd:{:x} rs1:{:x} rm:{:x}", rd, rs1, rm);
+                float_op(state, F16, rd, rs1, 0, rm, |sf, a, _| {
+                    sf.round_to_integral(F16, a, true)
+                })
+            }
+
+            Inst::FLTQS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fltq.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F32, rs2)?;
+                float_to_int(state, F32, rd, rs1, RM_NONE, |sf, a| {
+                    sf.lt_quiet(F32, a, b) as u32
+                })
+            }
+
+            Inst::FLEQS { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fleq.s rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F32, rs2)?;
+                float_to_int(state, F32, rd, rs1, RM_NONE, |sf, a| {
+                    sf.le_quiet(F32, a, b) as u32
+                })
+            }
+
+            Inst::FLTQD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fltq.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F64, rs2)?;
+                float_to_int(state, F64, rd, rs1, RM_NONE, |sf, a| {
+                    sf.lt_quiet(F64, a, b) as u32
+                })
+            }
+
+            Inst::FLEQD { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fleq.d rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F64, rs2)?;
+                float_to_int(state, F64, rd, rs1, RM_NONE, |sf, a| {
+                    sf.le_quiet(F64, a, b) as u32
+                })
+            }
+
+            Inst::FLTQH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fltq.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F16, rs2)?;
+                float_to_int(state, F16, rd, rs1, RM_NONE, |sf, a| {
+                    sf.lt_quiet(F16, a, b) as u32
+                })
+            }
+
+            Inst::FLEQH { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fleq.h rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let b = get_float(state, F16, rs2)?;
+                float_to_int(state, F16, rd, rs1, RM_NONE, |sf, a| {
+                    sf.le_quiet(F16, a, b) as u32
+                })
+            }
+
+            Inst::FCVTMODWD { rd, rs1 } => {
+                log::debug!(target: "exec", "fcvtmod.w.d rd:{:x} rs1:{:x}", rd, rs1);
+                float_to_int(state, F64, rd, rs1, RM_NONE, |sf, a| {
+                    sf.float_to_int_modular(F64, a, 32) as u32
+                })
+            }
+
+            Inst::FMVHXD { rd, rs1 } => {
+                log::debug!(target: "exec", "fmvh.x.d rd:{:x} rs1:{:x}", rd, rs1);
+
+                state.set_r(rd, (state.get_f(rs1)? >> 32) as u32)?;
+
+                Ok(None)
+            }
+
+            Inst::FMVPDX { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "fmvp.d.x rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+
+                let val = ((state.get_r(rs2)? as u64) << 32) | state.get_r(rs1)? as u64;
+                state.set_f(rd, val)?;
+
+                Ok(None)
+            }
+
+            // Vector.
+            Inst::VSETVLI { rd, rs1, vtypei } => {
+                log::debug!(target: "exec", "vsetvli rd:{:x} rs1:{:x} vtypei:{:x}", rd, rs1, vtypei);
+                vector::configure(state, rd, vector::avl(state, rd, rs1)?, vtypei)
+            }
+
+            Inst::VSETIVLI { rd, uimm, vtypei } => {
+                log::debug!(target: "exec", "vsetivli rd:{:x} uimm:{:x} vtypei:{:x}", rd, uimm, vtypei);
+                vector::configure(state, rd, Avl::Value(uimm), vtypei)
+            }
+
+            Inst::VSETVL { rd, rs1, rs2 } => {
+                log::debug!(target: "exec", "vsetvl rd:{:x} rs1:{:x} rs2:{:x}", rd, rs1, rs2);
+                let vtype = state.get_r(rs2)?;
+                vector::configure(state, rd, vector::avl(state, rd, rs1)?, vtype)
+            }
+
+            Inst::VLE(m) => {
+                log::debug!(target: "exec", "vle {}", m);
+                vector::load(state, m, Addressing::UnitStride)
+            }
+
+            Inst::VLEFF(m) => {
+                log::debug!(target: "exec", "vleff {}", m);
+                vector::load(state, m, Addressing::FaultOnlyFirst)
+            }
+
+            Inst::VLSE(m) => {
+                log::debug!(target: "exec", "vlse {}", m);
+                vector::load(state, m, Addressing::Strided)
+            }
+
+            Inst::VLUXEI(m) => {
+                log::debug!(target: "exec", "vluxei {}", m);
+                vector::load(state, m, Addressing::Indexed)
+            }
+
+            Inst::VLOXEI(m) => {
+                log::debug!(target: "exec", "vloxei {}", m);
+                vector::load(state, m, Addressing::Indexed)
+            }
+
+            Inst::VLR(m) => {
+                log::debug!(target: "exec", "vlr {}", m);
+                vector::load(state, m, Addressing::WholeRegister)
+            }
+
+            Inst::VLM(m) => {
+                log::debug!(target: "exec", "vlm {}", m);
+                vector::load(state, m, Addressing::Mask)
+            }
+
+            Inst::VSE(m) => {
+                log::debug!(target: "exec", "vse {}", m);
+                vector::store(state, m, Addressing::UnitStride)
+            }
+
+            Inst::VSSE(m) => {
+                log::debug!(target: "exec", "vsse {}", m);
+                vector::store(state, m, Addressing::Strided)
+            }
+
+            Inst::VSUXEI(m) => {
+                log::debug!(target: "exec", "vsuxei {}", m);
+                vector::store(state, m, Addressing::Indexed)
+            }
+
+            Inst::VSOXEI(m) => {
+                log::debug!(target: "exec", "vsoxei {}", m);
+                vector::store(state, m, Addressing::Indexed)
+            }
+
+            Inst::VSR(m) => {
+                log::debug!(target: "exec", "vsr {}", m);
+                vector::store(state, m, Addressing::WholeRegister)
+            }
+
+            Inst::VSM(m) => {
+                log::debug!(target: "exec", "vsm {}", m);
+                vector::store(state, m, Addressing::Mask)
+            }
+
+            Inst::VADD(v) => {
+                log::debug!(target: "exec", "vadd {}", v);
+                vector::binary(state, v, |a, b, _, _| a.wrapping_add(b))
+            }
+
+            Inst::VSUB(v) => {
+                log::debug!(target: "exec", "vsub {}", v);
+                vector::binary(state, v, |a, b, _, _| a.wrapping_sub(b))
+            }
+
+            Inst::VRSUB(v) => {
+                log::debug!(target: "exec", "vrsub {}", v);
+                vector::binary(state, v, |a, b, _, _| b.wrapping_sub(a))
+            }
+
+            Inst::VWADDU(v) => {
+                log::debug!(target: "exec", "vwaddu {}", v);
+                vector::widening(state, v, |a, b, _, _| a + b)
+            }
+
+            Inst::VWADD(v) => {
+                log::debug!(target: "exec", "vwadd {}", v);
+                vector::widening(state, v, |a, b, _, sew| {
+                    (signed(a, sew) + signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VWSUBU(v) => {
+                log::debug!(target: "exec", "vwsubu {}", v);
+                vector::widening(state, v, |a, b, _, _| a.wrapping_sub(b))
+            }
+
+            Inst::VWSUB(v) => {
+                log::debug!(target: "exec", "vwsub {}", v);
+                vector::widening(state, v, |a, b, _, sew| {
+                    (signed(a, sew) - signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VWADDUW(v) => {
+                log::debug!(target: "exec", "vwaddu.w {}", v);
+                vector::widening_wide(state, v, |a, b, _, _| a.wrapping_add(b))
+            }
+
+            Inst::VWADDW(v) => {
+                log::debug!(target: "exec", "vwadd.w {}", v);
+                vector::widening_wide(state, v, |a, b, _, sew| {
+                    a.wrapping_add(signed(b, sew) as u64)
+                })
+            }
+
+            Inst::VWSUBUW(v) => {
+                log::debug!(target: "exec", "vwsubu.w {}", v);
+                vector::widening_wide(state, v, |a, b, _, _| a.wrapping_sub(b))
+            }
+
+            Inst::VWSUBW(v) => {
+                log::debug!(target: "exec", "vwsub.w {}", v);
+                vector::widening_wide(state, v, |a, b, _, sew| {
+                    a.wrapping_sub(signed(b, sew) as u64)
+                })
+            }
+
+            Inst::VZEXT { vd, vs2, vm, frac } => {
+                log::debug!(target: "exec", "vzext vd:{:x} vs2:{:x} vm:{} frac:{:x}", vd, vs2, vm as u8, frac);
+                vector::extend(state, vd, vs2, vm, frac, false)
+            }
+
+            Inst::VSEXT { vd, vs2, vm, frac } => {
+                log::debug!(target: "exec", "vsext vd:{:x} vs2:{:x} vm:{} frac:{:x}", vd, vs2, vm as u8, frac);
+                vector::extend(state, vd, vs2, vm, frac, true)
+            }
+
+            Inst::VADC(v) => {
+                log::debug!(target: "exec", "vadc {}", v);
+                vector::carry(state, v, |a, b, d, _| a.wrapping_add(b).wrapping_add(d))
+            }
+
+            Inst::VMADC(v) => {
+                log::debug!(target: "exec", "vmadc {}", v);
+                vector::carry_out(state, v, |a, b, d, sew| (a + b + d) >> sew)
+            }
+
+            Inst::VSBC(v) => {
+                log::debug!(target: "exec", "vsbc {}", v);
+                vector::carry(state, v, |a, b, d, _| a.wrapping_sub(b).wrapping_sub(d))
+            }
+
+            Inst::VMSBC(v) => {
+                log::debug!(target: "exec", "vmsbc {}", v);
+                vector::carry_out(state, v, |a, b, d, _| (a < b + d) as u64)
+            }
+
+            Inst::VAND(v) => {
+                log::debug!(target: "exec", "vand {}", v);
+                vector::binary(state, v, |a, b, _, _| a & b)
+            }
+
+            Inst::VOR(v) => {
+                log::debug!(target: "exec", "vor {}", v);
+                vector::binary(state, v, |a, b, _, _| a | b)
+            }
+
+            Inst::VXOR(v) => {
+                log::debug!(target: "exec", "vxor {}", v);
+                vector::binary(state, v, |a, b, _, _| a ^ b)
+            }
+
+            Inst::VSLL(v) => {
+                log::debug!(target: "exec", "vsll {}", v);
+                vector::binary(state, v, |a, b, _, sew| a << (b & (sew as u64 - 1)))
+            }
+
+            Inst::VSRL(v) => {
+                log::debug!(target: "exec", "vsrl {}", v);
+                vector::binary(state, v, |a, b, _, sew| a >> (b & (sew as u64 - 1)))
+            }
+
+            Inst::VSRA(v) => {
+                log::debug!(target: "exec", "vsra {}", v);
+                vector::binary(state, v, |a, b, _, sew| {
+                    (signed(a, sew) >> (b & (sew as u64 - 1))) as u64
+                })
+            }
+
+            Inst::VNSRL(v) => {
+                log::debug!(target: "exec", "vnsrl {}", v);
+                vector::narrowing(state, v, |a, b, _, sew| a >> (b & (sew as u64 * 2 - 1)))
+            }
+
+            Inst::VNSRA(v) => {
+                log::debug!(target: "exec", "vnsra {}", v);
+                vector::narrowing(state, v, |a, b, _, sew| {
+                    (signed(a, sew * 2) >> (b & (sew as u64 * 2 - 1))) as u64
+                })
+            }
+
+            Inst::VMSEQ(v) => {
+                log::debug!(target: "exec", "vmseq {}", v);
+                vector::compare(state, v, |a, b, _, _| (a == b) as u64)
+            }
+
+            Inst::VMSNE(v) => {
+                log::debug!(target: "exec", "vmsne {}", v);
+                vector::compare(state, v, |a, b, _, _| (a != b) as u64)
+            }
+
+            Inst::VMSLTU(v) => {
+                log::debug!(target: "exec", "vmsltu {}", v);
+                vector::compare(state, v, |a, b, _, _| (a < b) as u64)
+            }
+
+            Inst::VMSLT(v) => {
+                log::debug!(target: "exec", "vmslt {}", v);
+                vector::compare(state, v, |a, b, _, sew| {
+                    (signed(a, sew) < signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VMSLEU(v) => {
+                log::debug!(target: "exec", "vmsleu {}", v);
+                vector::compare(state, v, |a, b, _, _| (a <= b) as u64)
+            }
+
+            Inst::VMSLE(v) => {
+                log::debug!(target: "exec", "vmsle {}", v);
+                vector::compare(state, v, |a, b, _, sew| {
+                    (signed(a, sew) <= signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VMSGTU(v) => {
+                log::debug!(target: "exec", "vmsgtu {}", v);
+                vector::compare(state, v, |a, b, _, _| (a > b) as u64)
+            }
+
+            Inst::VMSGT(v) => {
+                log::debug!(target: "exec", "vmsgt {}", v);
+                vector::compare(state, v, |a, b, _, sew| {
+                    (signed(a, sew) > signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VMINU(v) => {
+                log::debug!(target: "exec", "vminu {}", v);
+                vector::binary(state, v, |a, b, _, _| a.min(b))
+            }
+
+            Inst::VMIN(v) => {
+                log::debug!(target: "exec", "vmin {}", v);
+                vector::binary(state, v, |a, b, _, sew| {
+                    signed(a, sew).min(signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VMAXU(v) => {
+                log::debug!(target: "exec", "vmaxu {}", v);
+                vector::binary(state, v, |a, b, _, _| a.max(b))
+            }
+
+            Inst::VMAX(v) => {
+                log::debug!(target: "exec", "vmax {}", v);
+                vector::binary(state, v, |a, b, _, sew| {
+                    signed(a, sew).max(signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VMUL(v) => {
+                log::debug!(target: "exec", "vmul {}", v);
+                vector::binary(state, v, |a, b, _, _| a * b)
+            }
+
+            Inst::VMULH(v) => {
+                log::debug!(target: "exec", "vmulh {}", v);
+                vector::binary(state, v, |a, b, _, sew| {
+                    ((signed(a, sew) * signed(b, sew)) >> sew) as u64
+                })
+            }
+
+            Inst::VMULHU(v) => {
+                log::debug!(target: "exec", "vmulhu {}", v);
+                vector::binary(state, v, |a, b, _, sew| (a * b) >> sew)
+            }
+
+            Inst::VMULHSU(v) => {
+                log::debug!(target: "exec", "vmulhsu {}", v);
+                vector::binary(state, v, |a, b, _, sew| {
+                    ((signed(a, sew) * b as i64) >> sew) as u64
+                })
+            }
+
+            Inst::VDIVU(v) => {
+                log::debug!(target: "exec", "vdivu {}", v);
+                vector::binary(state, v, |a, b, _, _| a.checked_div(b).unwrap_or(u64::MAX))
+            }
+
+            Inst::VDIV(v) => {
+                log::debug!(target: "exec", "vdiv {}", v);
+                vector::binary(state, v, |a, b, _, sew| match signed(b, sew) {
+                    0 => u64::MAX,
+                    b => signed(a, sew).wrapping_div(b) as u64,
+                })
+            }
+
+            Inst::VREMU(v) => {
+                log::debug!(target: "exec", "vremu {}", v);
+                vector::binary(state, v, |a, b, _, _| a.checked_rem(b).unwrap_or(a))
+            }
+
+            Inst::VREM(v) => {
+                log::debug!(target: "exec", "vrem {}", v);
+                vector::binary(state, v, |a, b, _, sew| match signed(b, sew) {
+                    0 => a,
+                    b => signed(a, sew).wrapping_rem(b) as u64,
+                })
+            }
+
+            Inst::VWMUL(v) => {
+                log::debug!(target: "exec", "vwmul {}", v);
+                vector::widening(state, v, |a, b, _, sew| {
+                    (signed(a, sew) * signed(b, sew)) as u64
+                })
+            }
+
+            Inst::VWMULU(v) => {
+                log::debug!(target: "exec", "vwmulu {}", v);
+                vector::widening(state, v, |a, b, _, _| a * b)
+            }
+
+            Inst::VWMULSU(v) => {
+                log::debug!(target: "exec", "vwmulsu {}", v);
+                vector::widening(state, v, |a, b, _, sew| (signed(a, sew) * b as i64) as u64)
+            }
+
+            Inst::VMACC(v) => {
+                log::debug!(target: "exec", "vmacc {}", v);
+                vector::binary(state, v, |a, b, d, _| d.wrapping_add(a * b))
+            }
+
+            Inst::VNMSAC(v) => {
+                log::debug!(target: "exec", "vnmsac {}", v);
+                vector::binary(state, v, |a, b, d, _| d.wrapping_sub(a * b))
+            }
+
+            Inst::VMADD(v) => {
+                log::debug!(target: "exec", "vmadd {}", v);
+                vector::binary(state, v, |a, b, d, _| (b * d).wrapping_add(a))
+            }
+
+            Inst::VNMSUB(v) => {
+                log::debug!(target: "exec", "vnmsub {}", v);
+                vector::binary(state, v, |a, b, d, _| a.wrapping_sub(b * d))
+            }
+
+            Inst::VWMACCU(v) => {
+                log::debug!(target: "exec", "vwmaccu {}", v);
+                vector::widening(state, v, |a, b, d, _| d.wrapping_add(a * b))
+            }
+
+            Inst::VWMACC(v) => {
+                log::debug!(target: "exec", "vwmacc {}", v);
+                vector::widening(state, v, |a, b, d, sew| {
+                    d.wrapping_add((signed(a, sew) * signed(b, sew)) as u64)
+                })
+            }
+
+            Inst::VWMACCSU(v) => {
+                log::debug!(target: "exec", "vwmaccsu {}", v);
+                vector::widening(state, v, |a, b, d, sew| {
+                    d.wrapping_add((signed(b, sew) * a as i64) as u64)
+                })
+            }
+
+            Inst::VWMACCUS(v) => {
+                log::debug!(target: "exec", "vwmaccus {}", v);
+                vector::widening(state, v, |a, b, d, sew| {
+                    d.wrapping_add((signed(a, sew) * b as i64) as u64)
+                })
+            }
+
+            Inst::VMERGE(v) => {
+                log::debug!(target: "exec", "vmerge {}", v);
+                vector::carry(state, v, |a, b, d, _| if d == 1 { b } else { a })
+            }
+
+            Inst::VMV(v) => {
+                log::debug!(target: "exec", "vmv.v {}", v);
+                vector::binary(state, v, |_, b, _, _| b)
+            }
+
+            Inst::VSADDU(v) => {
+                log::debug!(target: "exec", "vsaddu {}", v);
+                vector::fixed_point(state, v, |a, b, sew, _| {
+                    clip_unsigned(a as i128 + b as i128, sew)
+                })
+            }
+
+            Inst::VSADD(v) => {
+                log::debug!(target: "exec", "vsadd {}", v);
+                vector::fixed_point(state, v, |a, b, sew, _| {
+                    clip_signed((signed(a, sew) + signed(b, sew)) as i128, sew)
+                })
+            }
+
+            Inst::VSSUBU(v) => {
+                log::debug!(target: "exec", "vssubu {}", v);
+                vector::fixed_point(state, v, |a, b, sew, _| {
+                    clip_unsigned(a as i128 - b as i128, sew)
+                })
+            }
+
+            Inst::VSSUB(v) => {
+                log::debug!(target: "exec", "vssub {}", v);
+                vector::fixed_point(state, v, |a, b, sew, _| {
+                    clip_signed((signed(a, sew) - signed(b, sew)) as i128, sew)
+                })
+            }
+
+            Inst::VAADDU(v) => {
+                log::debug!(target: "exec", "vaaddu {}", v);
+                vector::fixed_point(state, v, |a, b, _, vxrm| {
+                    (round_shift(a as i128 + b as i128, 1, vxrm) as u64, false)
+                })
+            }
+
+            Inst::VAADD(v) => {
+                log::debug!(target: "exec", "vaadd {}", v);
+                vector::fixed_point(state, v, |a, b, sew, vxrm| {
+                    let sum = signed(a, sew) as i128 + signed(b, sew) as i128;
+                    (round_shift(sum, 1, vxrm) as u64, false)
+                })
+            }
+
+            Inst::VASUBU(v) => {
+                log::debug!(target: "exec", "vasubu {}", v);
+                vector::fixed_point(state, v, |a, b, _, vxrm| {
+                    (round_shift(a as i128 - b as i128, 1, vxrm) as u64, false)
+                })
+            }
+
+            Inst::VASUB(v) => {
+                log::debug!(target: "exec", "vasub {}", v);
+                vector::fixed_point(state, v, |a, b, sew, vxrm| {
+                    let diff = signed(a, sew) as i128 - signed(b, sew) as i128;
+                    (round_shift(diff, 1, vxrm) as u64, false)
+                })
+            }
+
+            Inst::VSMUL(v) => {
+                log::debug!(target: "exec", "vsmul {}", v);
+                vector::fixed_point(state, v, |a, b, sew, vxrm| {
+                    let product = signed(a, sew) as i128 * signed(b, sew) as i128;
+                    clip_signed(round_shift(product, sew - 1, vxrm), sew)
+                })
+            }
+
+            Inst::VSSRL(v) => {
+                log::debug!(target: "exec", "vssrl {}", v);
+                vector::fixed_point(state, v, |a, b, sew, vxrm| {
+                    let shift = b as u32 & (sew - 1);
+                    (round_shift(a as i128, shift, vxrm) as u64, false)
+                })
+            }
+
+            Inst::VSSRA(v) => {
+                log::debug!(target: "exec", "vssra {}", v);
+                vector::fixed_point(state, v, |a, b, sew, vxrm| {
+                    let shift = b as u32 & (sew - 1);
+                    (
+                        round_shift(signed(a, sew) as i128, shift, vxrm) as u64,
+                        false,
+                    )
+                })
+            }
+
+            Inst::VNCLIPU(v) => {
+                log::debug!(target: "exec", "vnclipu {}", v);
+                vector::narrowing_fixed_point(state, v, |a, b, sew, vxrm| {
+                    let shift = b as u32 & (sew * 2 - 1);
+                    clip_unsigned(round_shift(a as i128, shift, vxrm), sew)
+                })
+            }
+
+            Inst::VNCLIP(v) => {
+                log::debug!(target: "exec", "vnclip {}", v);
+                vector::narrowing_fixed_point(state, v, |a, b, sew, vxrm| {
+                    let shift = b as u32 & (sew * 2 - 1);
+                    let val = signed(a, sew * 2) as i128;
+                    clip_signed(round_shift(val, shift, vxrm), sew)
+                })
+            }
+
+            Inst::VREDSUM(v) => {
+                log::debug!(target: "exec", "vredsum {}", v);
+                vector::reduction(state, v, false, |acc, e, _| acc.wrapping_add(e))
+            }
+
+            Inst::VREDMAXU(v) => {
+                log::debug!(target: "exec", "vredmaxu {}", v);
+                vector::reduction(state, v, false, |acc, e, _| acc.max(e))
+            }
+
+            Inst::VREDMAX(v) => {
+                log::debug!(target: "exec", "vredmax {}", v);
+                vector::reduction(state, v, false, |acc, e, sew| {
+                    signed(acc, sew).max(signed(e, sew)) as u64
+                })
+            }
+
+            Inst::VREDMINU(v) => {
+                log::debug!(target: "exec", "vredminu {}", v);
+                vector::reduction(state, v, false, |acc, e, _| acc.min(e))
+            }
+
+            Inst::VREDMIN(v) => {
+                log::debug!(target: "exec", "vredmin {}", v);
+                vector::reduction(state, v, false, |acc, e, sew| {
+                    signed(acc, sew).min(signed(e, sew)) as u64
+                })
+            }
+
+            Inst::VREDAND(v) => {
+                log::debug!(target: "exec", "vredand {}", v);
+                vector::reduction(state, v, false, |acc, e, _| acc & e)
+            }
+
+            Inst::VREDOR(v) => {
+                log::debug!(target: "exec", "vredor {}", v);
+                vector::reduction(state, v, false, |acc, e, _| acc | e)
+            }
+
+            Inst::VREDXOR(v) => {
+                log::debug!(target: "exec", "vredxor {}", v);
+                vector::reduction(state, v, false, |acc, e, _| acc ^ e)
+            }
+
+            Inst::VWREDSUMU(v) => {
+                log::debug!(target: "exec", "vwredsumu {}", v);
+                vector::reduction(state, v, true, |acc, e, _| acc.wrapping_add(e))
+            }
+
+            Inst::VWREDSUM(v) => {
+                log::debug!(target: "exec", "vwredsum {}", v);
+                vector::reduction(state, v, true, |acc, e, sew| {
+                    acc.wrapping_add(signed(e, sew) as u64)
+                })
+            }
+
+            Inst::VMAND { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmand vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| a & b)
+            }
+
+            Inst::VMNAND { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmnand vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| !(a & b))
+            }
+
+            Inst::VMANDN { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmandn vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| a & !b)
+            }
+
+            Inst::VMXOR { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmxor vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| a ^ b)
+            }
+
+            Inst::VMOR { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmor vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| a | b)
+            }
+
+            Inst::VMNOR { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmnor vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| !(a | b))
+            }
+
+            Inst::VMORN { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmorn vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| a | !b)
+            }
+
+            Inst::VMXNOR { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vmxnor vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::mask_logical(state, vd, vs2, vs1, |a, b| !(a ^ b))
+            }
+
+            Inst::VCPOP { rd, vs2, vm } => {
+                log::debug!(target: "exec", "vcpop rd:{:x} vs2:{:x} vm:{}", rd, vs2, vm as u8);
+                vector::count(state, rd, vs2, vm)
+            }
+
+            Inst::VFIRST { rd, vs2, vm } => {
+                log::debug!(target: "exec", "vfirst rd:{:x} vs2:{:x} vm:{}", rd, vs2, vm as u8);
+                vector::first(state, rd, vs2, vm)
+            }
+
+            Inst::VMSBF { vd, vs2, vm } => {
+                log::debug!(target: "exec", "vmsbf vd:{:x} vs2:{:x} vm:{}", vd, vs2, vm as u8);
+                vector::set_first(state, vd, vs2, vm, First::Before)
+            }
+
+            Inst::VMSIF { vd, vs2, vm } => {
+                log::debug!(target: "exec", "vmsif vd:{:x} vs2:{:x} vm:{}", vd, vs2, vm as u8);
+                vector::set_first(state, vd, vs2, vm, First::Including)
+            }
+
+            Inst::VMSOF { vd, vs2, vm } => {
+                log::debug!(target: "exec", "vmsof vd:{:x} vs2:{:x} vm:{}", vd, vs2, vm as u8);
+                vector::set_first(state, vd, vs2, vm, First::Only)
+            }
+
+            Inst::VIOTA { vd, vs2, vm } => {
+                log::debug!(target: "exec", "viota vd:{:x} vs2:{:x} vm:{}", vd, vs2, vm as u8);
+                vector::iota(state, vd, vs2, vm)
+            }
+
+            Inst::VID { vd, vm } => {
+                log::debug!(target: "exec", "vid vd:{:x} vm:{}", vd, vm as u8);
+                vector::id(state, vd, vm)
+            }
+
+            Inst::VMVXS { rd, vs2 } => {
+                log::debug!(target: "exec", "vmv.x.s rd:{:x} vs2:{:x}", rd, vs2);
+                vector::move_to_scalar(state, rd, vs2)
+            }
+
+            Inst::VMVSX { vd, rs1 } => {
+                log::debug!(target: "exec", "vmv.s.x vd:{:x} rs1:{:x}", vd, rs1);
+                vector::move_from_scalar(state, vd, rs1)
+            }
+
+            Inst::VSLIDEUP(v) => {
+                log::debug!(target: "exec", "vslideup {}", v);
+                vector::slide(state, v, true)
+            }
+
+            Inst::VSLIDEDOWN(v) => {
+                log::debug!(target: "exec", "vslidedown {}", v);
+                vector::slide(state, v, false)
+            }
+
+            Inst::VSLIDE1UP(v) => {
+                log::debug!(target: "exec", "vslide1up {}", v);
+                vector::slide1(state, v, true)
+            }
+
+            Inst::VSLIDE1DOWN(v) => {
+                log::debug!(target: "exec", "vslide1down {}", v);
+                vector::slide1(state, v, false)
+            }
+
+            Inst::VRGATHER(v) => {
+                log::debug!(target: "exec", "vrgather {}", v);
+                vector::gather(state, v, None)
+            }
+
+            Inst::VRGATHEREI16(v) => {
+                log::debug!(target: "exec", "vrgatherei16 {}", v);
+                vector::gather(state, v, Some(16))
+            }
+
+            Inst::VCOMPRESS { vd, vs2, vs1 } => {
+                log::debug!(target: "exec", "vcompress vd:{:x} vs2:{:x} vs1:{:x}", vd, vs2, vs1);
+                vector::compress(state, vd, vs2, vs1)
+            }
+
+            Inst::VMVR { vd, vs2, nr } => {
+                log::debug!(target: "exec", "vmvr vd:{:x} vs2:{:x} nr:{}", vd, vs2, nr);
+                vector::move_whole(state, vd, vs2, nr)
+            }
+
+            // Control and status registers.
+            Inst::CSRRW { rd, rs1, csr } => {
+                log::debug!(target: "exec", "csrrw rd:{:x} rs1:{:x} csr:{:x}", rd, rs1, csr);
+                csr_write(state, rd, csr, state.get_x(rs1)?)
+            }
+
+            Inst::CSRRS { rd, rs1, csr } => {
+                log::debug!(target: "exec", "csrrs rd:{:x} rs1:{:x} csr:{:x}", rd, rs1, csr);
+
+                let mask = state.get_x(rs1)?;
+                csr_modify(state, rd, csr, rs1 != 0, |val| val | mask)
+            }
+
+            Inst::CSRRC { rd, rs1, csr } => {
+                log::debug!(target: "exec", "csrrc rd:{:x} rs1:{:x} csr:{:x}", rd, rs1, csr);
+
+                let mask = state.get_x(rs1)?;
+                csr_modify(state, rd, csr, rs1 != 0, |val| val & !mask)
+            }
+
+            Inst::CSRRWI { rd, uimm, csr } => {
+                log::debug!(target: "exec", "csrrwi rd:{:x} uimm:{:x} csr:{:x}", rd, uimm, csr);
+                csr_write(state, rd, csr, uimm as u64)
+            }
+
+            Inst::CSRRSI { rd, uimm, csr } => {
+                log::debug!(target: "exec", "csrrsi rd:{:x} uimm:{:x} csr:{:x}", rd, uimm, csr);
+                csr_modify(state, rd, csr, uimm != 0, |val| val | uimm as u64)
+            }
+
+            Inst::CSRRCI { rd, uimm, csr } => {
+                log::debug!(target: "exec", "csrrci rd:{:x} uimm:{:x} csr:{:x}", rd, uimm, csr);
+                csr_modify(state, rd, csr, uimm != 0, |val| val & !(uimm as u64))
+            }
+
+            Inst::ECALL => {
+                log::debug!(target: "exec", "ecall");
+                Err(Exception::environment_call(state.get_privilege()).into())
+            }
+
+            Inst::EBREAK => {
+                log::debug!(target: "exec", "ebreak");
+                Err(Exception::Breakpoint(state.get_pc()).into())
+            }
+
+            Inst::MRET => {
+                log::debug!(target: "exec", "mret");
+                Ok(Some(trap::mret(state)?))
+            }
+
+            Inst::SRET => {
+                log::debug!(target: "exec", "sret");
+                Ok(Some(trap::sret(state)?))
+            }
+
+            Inst::SFENCEVMA { rs1, rs2 } => {
+                log::debug!(target: "exec", "sfence.vma rs1:{:x} rs2:{:x}", rs1, rs2);
+
+                mmu::fence(state)?;
+                Ok(None)
+            }
+
+            Inst::WFI => {
+                log::debug!(target: "exec", "wfi");
+
+                trap::wait(state)?;
+                Ok(None)
+            }
+
+            Inst::PAUSE => {
+                log::debug!(target: "exec", "pause");
+
+                state.hint_yield();
+                Ok(None)
+            }
+
+            // Cache block operations, there are no caches, so, only the permission
+            // checks are left of the management ones.
+            Inst::CBOCLEAN { rs1 } => {
+                log::debug!(target: "exec", "cbo.clean rs1:{:x}", rs1);
+
+                check_envcfg(state, csr::ENVCFG_CBCFE)?;
+                state.check_block(state.get_x(rs1)?)?;
+                Ok(None)
+            }
+
+            Inst::CBOFLUSH { rs1 } => {
+                log::debug!(target: "exec", "cbo.flush rs1:{:x}", rs1);
+
+                check_envcfg(state, csr::ENVCFG_CBCFE)?;
+                state.check_block(state.get_x(rs1)?)?;
+                Ok(None)
+            }
+
+            Inst::CBOINVAL { rs1 } => {
+                log::debug!(target: "exec", "cbo.inval rs1:{:x}", rs1);
+
+                check_envcfg(state, csr::ENVCFG_CBIE)?;
+                state.check_block(state.get_x(rs1)?)?;
+                Ok(None)
+            }
+
+            Inst::CBOZERO { rs1 } => {
+                log::debug!(target: "exec", "cbo.zero rs1:{:x}", rs1);
+
+                check_envcfg(state, csr::ENVCFG_CBZE)?;
+                state.zero_block(state.get_x(rs1)?)?;
+                Ok(None)
+            }
+
+            // Compressed pushes, pops and moves.
+            Inst::CMPUSH { rlist, stack_adj } => {
+                log::debug!(target: "exec", "cm.push rlist:{:x} stack_adj:{:x}", rlist, stack_adj);
+
+                let sp = state.get_r(2)?;
+                let mut addr = sp;
+                for reg in push_list(rlist) {
+                    addr = addr.wrapping_sub(4);
+                    state.set_mem_u32(addr as u64, state.get_r(reg)?)?;
+                }
+
+                state.set_r(2, sp.wrapping_sub(stack_adj))?;
+                Ok(None)
+            }
+
+            Inst::CMPOP { rlist, stack_adj } => {
+                log::debug!(target: "exec", "cm.pop rlist:{:x} stack_adj:{:x}", rlist, stack_adj);
+
+                pop(state, rlist, stack_adj)?;
+                Ok(None)
+            }
+
+            Inst::CMPOPRET { rlist, stack_adj } => {
+                log::debug!(target: "exec", "cm.popret rlist:{:x} stack_adj:{:x}", rlist, stack_adj);
+
+                pop(state, rlist, stack_adj)?;
+                Ok(Some(jump(state, state.get_x(1)? >> 1 << 1)?))
+            }
+
+            Inst::CMPOPRETZ { rlist, stack_adj } => {
+                log::debug!(target: "exec", "cm.popretz rlist:{:x} stack_adj:{:x}", rlist, stack_adj);
+
+                pop(state, rlist, stack_adj)?;
+                state.set_r(10, 0)?;
+                Ok(Some(jump(state, state.get_x(1)? >> 1 << 1)?))
+            }
+
+            Inst::CMMVSA01 { r1s, r2s } => {
+                log::debug!(target: "exec", "cm.mvsa01 r1s:{:x} r2s:{:x}", r1s, r2s);
+
+                let (a0, a1) = (state.get_r(10)?, state.get_r(11)?);
+                state.set_r(r1s, a0)?;
+                state.set_r(r2s, a1)?;
+                Ok(None)
+            }
+
+            Inst::CMMVA01S { r1s, r2s } => {
+                log::debug!(target: "exec", "cm.mva01s r1s:{:x} r2s:{:x}", r1s, r2s);
+
+                let (s1, s2) = (state.get_r(r1s)?, state.get_r(r2s)?);
+                state.set_r(10, s1)?;
+                state.set_r(11, s2)?;
+                Ok(None)
+            }
+
+            // Table jumps.
+            Inst::CMJT { index } => {
+                log::debug!(target: "exec", "cm.jt index:{:x}", index);
+
+                Ok(Some(table_jump(state, index)?))
+            }
+
+            Inst::CMJALT { index } => {
+                log::debug!(target: "exec", "cm.jalt index:{:x}", index);
+
+                let target = table_jump(state, index)?;
+                state.set_x(1, add!(state.get_pc(), len as u64))?;
+                Ok(Some(target))
+            }
+
+            // FENCE & FENCE.I
+            Inst::IGNORE => {
+                log::debug!(target: "exec", "ignore");
+                Ok(None)
+            }
+        }
+    }
+}
+
+// The rounding mode field of the instructions that do not have one, they never
+// round.
+const RM_NONE: u8 = 0;
+
+// The rounding mode of an instruction, the one in its rm field or the one in frm for
+// the dynamic rounding mode (0b111). The reserved rounding modes are illegal.
+fn rounding<const M: usize>(state: &State<M>, rm: u8) -> Result<Rounding, InstError> {
+    let rm = match rm {
+        0b111 => state.csrs().get(csr::FRM),
+        rm => rm as u32,
+    };
+
+    Ok(Rounding::from_bits(rm).ok_or(state::Error::IllegalOperation)?)
+}
+
+// Reads the value of the format in a floating point register, a narrower value that
+// is not NaN-boxed reads as the canonical NaN.
+fn get_float<const M: usize>(state: &State<M>, fmt: Format, name: u8) -> Result<u64, InstError> {
+    Ok(fmt.unbox(state.get_f(name)?))
+}
+
+// Writes a value of the format to a floating point register, NaN-boxing it when it
+// is narrower than the register.
+fn set_float<const M: usize>(
+    state: &mut State<M>,
+    fmt: Format,
+    name: u8,
+    val: u64,
+) -> Result<(), InstError> {
+    Ok(state.set_f(name, fmt.nan_box(val))?)
+}
+
+// Places the result of op on rs1 and rs2 in the floating point register rd, all of
+// them in the format, accruing the exception flags it raises.
+fn float_op<const M: usize, O: FnOnce(&mut Softfloat, u64, u64) -> u64>(
+    state: &mut State<M>,
+    fmt: Format,
+    rd: u8,
+    rs1: u8,
+    rs2: u8,
+    rm: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let mut sf = Softfloat::new(rounding(state, rm)?);
+    let val = op(
+        &mut sf,
+        get_float(state, fmt, rs1)?,
+        get_float(state, fmt, rs2)?,
+    );
+
+    state.raise_fp_flags(sf.flags);
+    set_float(state, fmt, rd, val)?;
+
+    Ok(None)
+}
+
+// The fused multiply adds of the format, with the product and the addend negated as
+// asked.
+fn mul_add<const M: usize>(
+    state: &mut State<M>,
+    fmt: Format,
+    r4: R4,
+    negate_product: bool,
+    negate_addend: bool,
+) -> Result<Option<u64>, InstError> {
+    let R4 {
+        rd,
+        rs1,
+        rs2,
+        rs3,
+        rm,
+    } = r4;
+    let c = get_float(state, fmt, rs3)?;
+    float_op(state, fmt, rd, rs1, rs2, rm, |sf, a, b| {
+        sf.mul_add(fmt, a, b, c, negate_product, negate_addend)
+    })
+}
+
+// Places the result of op on the rs1 of the format in the integer register rd,
+// accruing the exception flags it raises.
+fn float_to_int<const M: usize, O: FnOnce(&mut Softfloat, u64) -> u32>(
+    state: &mut State<M>,
+    fmt: Format,
+    rd: u8,
+    rs1: u8,
+    rm: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let mut sf = Softfloat::new(rounding(state, rm)?);
+    let val = op(&mut sf, get_float(state, fmt, rs1)?);
+
+    state.raise_fp_flags(sf.flags);
+    state.set_r(rd, val)?;
+
+    Ok(None)
+}
+
+// Places the result of op on the integer register rs1 in the floating point register
+// rd of the format, accruing the exception flags it raises.
+fn int_to_float<const M: usize, O: FnOnce(&mut Softfloat, u64) -> u64>(
+    state: &mut State<M>,
+    fmt: Format,
+    rd: u8,
+    rs1: u8,
+    rm: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let mut sf = Softfloat::new(rounding(state, rm)?);
+    let val = op(&mut sf, state.get_r(rs1)? as u64);
+
+    state.raise_fp_flags(sf.flags);
+    set_float(state, fmt, rd, val)?;
+
+    Ok(None)
+}
+
+// Converts the floating point rs1 from a format to another one in rd, accruing the
+// exception flags it raises.
+fn convert_float<const M: usize>(
+    state: &mut State<M>,
+    from: Format,
+    to: Format,
+    rd: u8,
+    rs1: u8,
+    rm: u8,
+) -> Result<Option<u64>, InstError> {
+    let mut sf = Softfloat::new(rounding(state, rm)?);
+    let val = sf.convert(from, to, get_float(state, from, rs1)?);
+
+    state.raise_fp_flags(sf.flags);
+    set_float(state, to, rd, val)?;
+
+    Ok(None)
+}
+
+// Jumps to the target if the branch will be taken.
+fn branch<const M: usize, C: Fn(u64, u64) -> bool>(
+    state: &mut State<M>,
+    rs1: u8,
+    rs2: u8,
+    imm: u16,
+    cmp: C,
+) -> Result<Option<u64>, InstError> {
+    let a = state.get_x(rs1)?;
+    let b = state.get_x(rs2)?;
+    if cmp(a, b) {
+        let addr = add!(state.get_pc(), sign_extend_x!(13, imm));
+        let target = jump(state, addr)?;
+        state.record_event(counters::EVENT_BRANCH_TAKEN);
+        Ok(Some(target))
+    } else {
+        Ok(None)
+    }
+}
+
+// The registers of a Zcmp register list in the order they are pushed, from the top of
+// the stack down. The lists 4 to 14 hold ra and the first rlist - 4 of s0 to s11, the
+// list 15 holds all of them, s10 never goes without s11.
+fn push_list(rlist: u8) -> impl Iterator<Item = u8> {
+    const SAVED: [u8; 12] = [8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+
+    let count = match rlist {
+        15 => 12,
+        rlist => rlist as usize - 4,
+    };
+
+    SAVED[..count]
+        .iter()
+        .rev()
+        .copied()
+        .chain(std::iter::once(1))
+}
+
+// Loads the registers of the list from the top of the stack frame of stack_adj bytes
+// at sp and frees the frame. The registers are loaded first, so, a fault leaves sp
+// as it was.
+fn pop<const M: usize>(state: &mut State<M>, rlist: u8, stack_adj: u32) -> Result<(), InstError> {
+    let top = state.get_r(2)?.wrapping_add(stack_adj);
+    let mut addr = top;
+    for reg in push_list(rlist) {
+        addr = addr.wrapping_sub(4);
+        let val = state.get_mem_u32(addr as u64)?;
+        state.set_r(reg, val)?;
+    }
+
+    state.set_r(2, top)?;
+    Ok(())
+}
+
+// Fetches the entry of the jump table at jvt and returns it as the target of the
+// jump, without its lowest bit.
+fn table_jump<const M: usize>(state: &mut State<M>, index: u8) -> Result<u64, InstError> {
+    let base = state.csrs().get(csr::JVT) & csr::JVT_BASE;
+    let entry = state.fetch_mem_u32(base.wrapping_add(index as u32 * 4) as u64)?;
+
+    jump(state, entry as u64 >> 1 << 1)
+}
+
+// Checks the target of a jump or a taken branch, truncated to XLEN, against the
+// alignment of the instructions and returns it. A misaligned target is reported on
+// the jump itself.
+fn jump<const M: usize>(state: &State<M>, target: u64) -> Result<u64, InstError> {
+    let target = state.to_xlen(target);
+    if !target.is_multiple_of(state.instruction_alignment() as u64) {
+        return Err(Exception::InstructionAddressMisaligned(target).into());
+    }
+
+    Ok(target)
+}
+
+// Loads the word at rs1 into rd and stores the result of applying op to the loaded
+// value and rs2 in its place. There is only ever a single hart running, so, doing
+// the load and the store back to back is atomic.
+fn amo<const M: usize, O: Fn(u32, u32) -> u32>(
+    state: &mut State<M>,
+    rd: u8,
+    rs1: u8,
+    rs2: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let addr = aligned(state, rs1, 4, Access::Store)?;
+    let b = state.get_r(rs2)?;
+    let val = state.amo_mem_u32(addr, |a| op(a, b))?;
+    state.set_r(rd, val)?;
+
+    Ok(None)
+}
+
+// Like amo, on the byte at the address in rs1. The loaded byte is sign extended.
+fn amo_b<const M: usize, O: Fn(u8, u8) -> u8>(
+    state: &mut State<M>,
+    rd: u8,
+    rs1: u8,
+    rs2: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let addr = state.get_x(rs1)?;
+    let b = state.get_x(rs2)? as u8;
+    let val = state.amo_mem_u8(addr, |a| op(a, b))?;
+    state.set_x(rd, val as i8 as u64)?;
+
+    Ok(None)
+}
+
+// Like amo, on the halfword at the address in rs1. The loaded halfword is sign
+// extended.
+fn amo_h<const M: usize, O: Fn(u16, u16) -> u16>(
+    state: &mut State<M>,
+    rd: u8,
+    rs1: u8,
+    rs2: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let addr = aligned(state, rs1, 2, Access::Store)?;
+    let b = state.get_x(rs2)? as u16;
+    let val = state.amo_mem_u16(addr, |a| op(a, b))?;
+    state.set_x(rd, val as i16 as u64)?;
+
+    Ok(None)
+}
+
+// Like amo, on the doubleword at the address in rs1.
+fn amo_d<const M: usize, O: Fn(u64, u64) -> u64>(
+    state: &mut State<M>,
+    rd: u8,
+    rs1: u8,
+    rs2: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let addr = aligned(state, rs1, 8, Access::Store)?;
+    let b = state.get_x(rs2)?;
+    let val = state.amo_mem_u64(addr, |a| op(a, b))?;
+    state.set_x(rd, val)?;
+
+    Ok(None)
+}
+
+// Gets the doubleword in the register pair starting at the even register, the low
+// word in it and the high word in the next one. The pair of x0 reads as 0.
+fn get_pair<const M: usize>(state: &State<M>, name: u8) -> Result<u64, state::Error> {
+    if name == 0 {
+        return Ok(0);
+    }
+
+    Ok((state.get_r(name + 1)? as u64) << 32 | state.get_r(name)? as u64)
+}
+
+// Sets the register pair starting at the even register to the doubleword. Writes to
+// the pair of x0 are discarded as a whole.
+fn set_pair<const M: usize>(state: &mut State<M>, name: u8, val: u64) -> Result<(), state::Error> {
+    if name == 0 {
+        return Ok(());
+    }
+
+    state.set_r(name, val as u32)?;
+    state.set_r(name + 1, (val >> 32) as u32)
+}
+
+// Checks that the address in rs1 of an atomic is aligned to its size and returns it. The
+// atomics are never emulated when misaligned, whatever the machine does for the rest
+// of the loads and stores.
+fn aligned<const M: usize>(
+    state: &State<M>,
+    rs1: u8,
+    size: u32,
+    access: Access,
+) -> Result<u64, InstError> {
+    let addr = state.to_xlen(state.get_x(rs1)?);
+    if !addr.is_multiple_of(size as u64) {
+        return Err(access.address_misaligned(addr).into());
+    }
+
+    Ok(addr)
+}
+
+// Checks that the current privilege level can use the cache block operations of the
+// field of menvcfg and senvcfg. Machine mode always can, supervisor mode needs the
+// field set in menvcfg and user mode in senvcfg as well.
+fn check_envcfg<const M: usize>(state: &State<M>, field: u32) -> Result<(), InstError> {
+    let csrs = state.csrs();
+    let enabled = match state.get_privilege() {
+        Privilege::Machine => field,
+        Privilege::Supervisor => csrs.get(csr::MENVCFG),
+        Privilege::User => csrs.get(csr::MENVCFG) & csrs.get(csr::SENVCFG),
+    };
+
+    if enabled & field == 0 {
+        return Err(state::Error::IllegalOperation.into());
+    }
+
+    Ok(())
+}
+
+// Writes the value to the CSR and places its old value in rd. The CSR is only read
+// when rd is not x0, so, the side effects of reading it are skipped otherwise. seed
+// is the exception, a write to it always polls the entropy source.
+fn csr_write<const M: usize>(
+    state: &mut State<M>,
+    rd: u8,
+    csr: u16,
+    val: u64,
+) -> Result<Option<u64>, InstError> {
+    let old = match csr {
+        csr::SEED => state.read_seed()?,
+        _ if rd != 0 => state.get_csr(csr)?,
+        _ => 0,
+    };
+    state.set_csr(csr, val)?;
+    state.set_x(rd, old)?;
+
+    Ok(None)
+}
+
+// Places the value of the CSR in rd and writes back the result of applying op to it
+// only if write is set. A read only CSR can be read this way as long as it is not
+// written to.
+fn csr_modify<const M: usize, O: Fn(u64) -> u64>(
+    state: &mut State<M>,
+    rd: u8,
+    csr: u16,
+    write: bool,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let old = match csr {
+        // Reading seed without writing to it is illegal.
+        csr::SEED if !write => return Err(state::Error::IllegalOperation.into()),
+        csr::SEED => state.read_seed()?,
+        _ => state.get_csr(csr)?,
+    };
+    if write {
+        state.set_csr(csr, op(old))?;
+    }
+    state.set_x(rd, old)?;
+
+    Ok(None)
+}
+
+// Even in case of negative numbers, the two's complement of a smaller number
+// will still be smaller than the other number.
+#[inline]
+fn signed_cmp_lt(a: u32, b: u32) -> bool {
+    if (a >> 31) == (b >> 31) {
+        a < b
+    } else {
+        (a >> 31) == 1
+    }
+}
+
+// Even in case of negative numbers, the two's complement of a smaller number
+// will still be smaller than the other number.
+#[inline]
+fn signed_cmp_gt(a: u32, b: u32) -> bool {
+    if (a >> 31) == (b >> 31) {
+        a >= b
+    } else {
+        (a >> 31) == 0
+    }
+}
+
+// TODO: I bet this can be optimized more.
+// Right shift the value while moving in sign bit of the original value instead.
+#[inline]
+fn right_shift_arithmetic(val: u32, amount: u32) -> u32 {
+    let amount = amount % 32;
+
+    if ((val >> 31) == 1) && (amount > 0) {
+        (val >> amount) | (u32::MAX << (32 - amount))
+    } else {
+        val >> amount
+    }
+}
+
+// The single bit indexed by the lowest 5 bits of index.
+#[inline]
+fn bit(index: u32) -> u32 {
+    1 << (index & 0b11_111)
+}
+
+// Replaces every element of the width in bits of indices by the element of vals it
+// indexes, 0 when the index is past the last element.
+fn xperm(vals: u32, indices: u32, width: u32) -> u32 {
+    let mask = (1 << width) - 1;
+    (0..32).step_by(width as usize).fold(0, |acc, i| {
+        let index = (indices >> i) & mask;
+        match index.checked_mul(width) {
+            Some(pos) if pos < 32 => acc | (((vals >> pos) & mask) << i),
+            _ => acc,
+        }
+    })
+}
+
+// The 64 bit carry-less product of a and b, the sum of the shifted copies of a is
+// taken with XOR instead of an addition.
+fn clmul(a: u32, b: u32) -> u64 {
+    (0..32)
+        .filter(|i| (b >> i) & 1 == 1)
+        .fold(0, |acc, i| acc ^ ((a as u64) << i))
 }
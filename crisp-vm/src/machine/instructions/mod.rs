@@ -1,5 +1,10 @@
+pub mod compressed;
 pub mod decode;
+#[allow(clippy::module_inception)]
 mod instructions;
+pub mod vector;
 
+pub use compressed::decode as decode_compressed;
 pub use decode::decode;
-pub use instructions::{Inst, InstError};
+pub use instructions::{Inst, InstError, R4};
+pub use vector::{V, VMem, VSrc};
@@ -0,0 +1,1102 @@
+use std::fmt;
+
+use crate::machine::{
+    csr,
+    instructions::InstError,
+    state::{self, Access, State},
+    vector::{self, ELEN, VRegisters, VTYPE_VILL, Vtype},
+};
+
+// The second source of the vector arithmetic instructions, a vector register, a
+// scalar register or an immediate that is already sign or zero extended as the
+// instruction needs.
+#[derive(Debug, Clone, Copy)]
+pub enum VSrc {
+    Vector(u8),
+    Scalar(u8),
+    Imm(u32),
+}
+
+// The operands of the vector arithmetic instructions. Unless vm is set, the elements
+// whose bit in v0 is clear are masked off and left to the mask policy.
+#[derive(Debug, Clone, Copy)]
+pub struct V {
+    pub vd: u8,
+    pub vs2: u8,
+    pub src: VSrc,
+    pub vm: bool,
+}
+
+impl fmt::Display for V {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "vd:{:x} vs2:{:x} ", self.vd, self.vs2)?;
+        match self.src {
+            VSrc::Vector(vs1) => write!(f, "vs1:{:x}", vs1)?,
+            VSrc::Scalar(rs1) => write!(f, "rs1:{:x}", rs1)?,
+            VSrc::Imm(imm) => write!(f, "imm:{:x}", imm)?,
+        }
+        write!(f, " vm:{}", self.vm as u8)
+    }
+}
+
+// The operands of the vector loads and stores, vd is the register the data of a
+// store comes from as well. rs2 holds the stride of the strided ones and vs2 the
+// offsets of the indexed ones. eew is the width in bits of the data elements, or of
+// the offsets for the indexed ones, and nf the number of fields of a segment, or of
+// registers for the whole register ones.
+#[derive(Debug, Clone, Copy)]
+pub struct VMem {
+    pub vd: u8,
+    pub rs1: u8,
+    pub rs2: u8,
+    pub eew: u32,
+    pub nf: u8,
+    pub vm: bool,
+}
+
+impl fmt::Display for VMem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "vd:{:x} rs1:{:x} rs2:{:x} eew:{} nf:{} vm:{}",
+            self.vd, self.rs1, self.rs2, self.eew, self.nf, self.vm as u8
+        )
+    }
+}
+
+// How the vector loads and stores find the address of each element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Addressing {
+    // The elements are next to each other, along with the fields of a segment.
+    UnitStride,
+
+    // Like the unit stride, but a fault past the first element cuts vl short instead
+    // of trapping.
+    FaultOnlyFirst,
+
+    // The segments are rs2 bytes apart.
+    Strided,
+
+    // Every segment is at the offset in the element of vs2 from the base address.
+    Indexed,
+
+    // The nf registers as a whole, regardless of vtype and vl.
+    WholeRegister,
+
+    // A mask register, a byte for every 8 elements of vl.
+    Mask,
+}
+
+// Where vset{i}vl{i} take the application vector length from.
+#[derive(Debug, Clone, Copy)]
+pub enum Avl {
+    // The value of a register or the immediate.
+    Value(u32),
+
+    // As many elements as fit in a group.
+    Max,
+
+    // The current vl, for a new vtype with the same VLMAX.
+    Keep,
+}
+
+// The elements vmsbf, vmsif and vmsof set around the first set bit of the source.
+#[derive(Debug, Clone, Copy)]
+pub enum First {
+    Before,
+    Including,
+    Only,
+}
+
+// The state of the vector unit an instruction runs with.
+struct Unit {
+    vtype: Vtype,
+    vl: u32,
+    vstart: u32,
+    vlen: u32,
+}
+
+// The state of the vector unit, the instructions that depend on vtype are illegal
+// while vill is set.
+fn unit<const M: usize>(state: &State<M>) -> Result<Unit, InstError> {
+    let csrs = state.csrs();
+    let vtype = Vtype::from_bits(csrs.get(csr::VTYPE)).ok_or_else(illegal)?;
+
+    Ok(Unit {
+        vtype,
+        vl: csrs.get(csr::VL),
+        vstart: csrs.get(csr::VSTART),
+        vlen: state.config().vlen,
+    })
+}
+
+// A register group operand, its first register, the width of its elements in bits
+// and the log2 of the number of its registers (EMUL). A mask is a single register
+// with elements of a bit.
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    reg: u8,
+    eew: u32,
+    emul: i32,
+}
+
+impl Group {
+    fn new(reg: u8, eew: u32, emul: i32) -> Self {
+        Group { reg, eew, emul }
+    }
+
+    fn mask(reg: u8) -> Self {
+        Group::new(reg, 1, 0)
+    }
+
+    // A group of elements of the width with EMUL scaled along with it from LMUL.
+    fn scaled(reg: u8, eew: u32, vtype: &Vtype) -> Self {
+        let emul = vtype.lmul + eew.ilog2() as i32 - vtype.sew.ilog2() as i32;
+        Group::new(reg, eew, emul)
+    }
+
+    fn regs(&self) -> u8 {
+        1 << self.emul.max(0)
+    }
+
+    // A group is only legal with an element width the unit supports, at most 8
+    // registers and a first register that is a multiple of their number.
+    fn check(self) -> Result<Self, InstError> {
+        if self.eew < 8
+            || self.eew > ELEN
+            || !(-3..=3).contains(&self.emul)
+            || !self.reg.is_multiple_of(self.regs())
+        {
+            return Err(illegal());
+        }
+
+        Ok(self)
+    }
+
+    fn overlaps(&self, other: &Group) -> bool {
+        self.reg < other.reg + other.regs() && other.reg < self.reg + self.regs()
+    }
+}
+
+// The destination can only overlap a source with the same element width, or one with
+// wider elements at its lowest register, or one with narrower elements of at least a
+// whole register at the highest register of the destination.
+fn check_overlap(dest: Group, src: Group) -> Result<(), InstError> {
+    let legal = !dest.overlaps(&src)
+        || dest.eew == src.eew
+        || (dest.eew < src.eew && dest.reg == src.reg)
+        || (dest.eew > src.eew && src.emul >= 0 && dest.reg + dest.regs() == src.reg + src.regs());
+
+    if !legal {
+        return Err(illegal());
+    }
+
+    Ok(())
+}
+
+fn illegal() -> InstError {
+    state::Error::IllegalOperation.into()
+}
+
+// All the bits of a value of the width set.
+fn ones(bits: u32) -> u64 {
+    u64::MAX >> (64 - bits)
+}
+
+// Sign extends the lowest bits of a value.
+pub fn signed(val: u64, bits: u32) -> i64 {
+    ((val << (64 - bits)) as i64) >> (64 - bits)
+}
+
+// Shifts the value right, rounding the result in the fixed point rounding mode of
+// vxrm: to nearest up (rnu), to nearest even (rne), down (rdn) or to odd (rod).
+pub fn round_shift(val: i128, shift: u32, vxrm: u32) -> i128 {
+    if shift == 0 {
+        return val;
+    }
+
+    let bit = |i: u32| (val >> i) & 1;
+    let below = |i: u32| val & ((1 << i) - 1) != 0;
+    let round = match vxrm {
+        0 => bit(shift - 1),
+        1 => bit(shift - 1) & (below(shift - 1) as i128 | bit(shift)),
+        2 => 0,
+        _ => (bit(shift) == 0 && below(shift)) as i128,
+    };
+
+    (val >> shift) + round
+}
+
+// Saturates the value to the signed range of the width, along with whether it had to.
+pub fn clip_signed(val: i128, bits: u32) -> (u64, bool) {
+    let max = (1 << (bits - 1)) - 1;
+    let clipped = val.clamp(-max - 1, max);
+    (clipped as u64, clipped != val)
+}
+
+// Saturates the value to the unsigned range of the width, along with whether it had
+// to.
+pub fn clip_unsigned(val: i128, bits: u32) -> (u64, bool) {
+    let clipped = val.clamp(0, ones(bits) as i128);
+    (clipped as u64, clipped != val)
+}
+
+// Sets vl and vtype for vset{i}vl{i} and places the new vl in rd. An unsupported
+// vtype sets vill along with a vl of 0, and so does keeping vl when VLMAX changes.
+pub fn configure<const M: usize>(
+    state: &mut State<M>,
+    rd: u8,
+    avl: Avl,
+    bits: u32,
+) -> Result<Option<u64>, InstError> {
+    let vlen = state.config().vlen;
+    let old = Vtype::from_bits(state.csrs().get(csr::VTYPE));
+
+    let (vl, bits) = match Vtype::from_bits(bits) {
+        Some(vtype) => {
+            let vlmax = vtype.vlmax(vlen);
+            match avl {
+                Avl::Value(avl) => (avl.min(vlmax), bits),
+                Avl::Max => (vlmax, bits),
+                Avl::Keep if old.is_some_and(|old| old.vlmax(vlen) == vlmax) => {
+                    (state.csrs().get(csr::VL), bits)
+                }
+                Avl::Keep => (0, VTYPE_VILL),
+            }
+        }
+        None => (0, VTYPE_VILL),
+    };
+
+    state.csrs_mut().set_vector_config(vl, bits);
+    state.csrs_mut().set(csr::VSTART, 0);
+    state.set_vector_dirty();
+    state.set_r(rd, vl)?;
+
+    Ok(None)
+}
+
+// Loads the elements of the addressing into the registers from vd on.
+pub fn load<const M: usize>(
+    state: &mut State<M>,
+    m: VMem,
+    addressing: Addressing,
+) -> Result<Option<u64>, InstError> {
+    transfer(state, m, addressing, Access::Load)
+}
+
+// Stores the elements of the registers from vd on with the addressing.
+pub fn store<const M: usize>(
+    state: &mut State<M>,
+    m: VMem,
+    addressing: Addressing,
+) -> Result<Option<u64>, InstError> {
+    transfer(state, m, addressing, Access::Store)
+}
+
+// Moves the elements between the registers and the memory. A fault leaves vstart at
+// the element that faulted, for the access to be resumed from there.
+fn transfer<const M: usize>(
+    state: &mut State<M>,
+    m: VMem,
+    addressing: Addressing,
+    access: Access,
+) -> Result<Option<u64>, InstError> {
+    let vstart = state.csrs().get(csr::VSTART);
+    let vlenb = state.vregs().vlenb();
+
+    // The group of the first field, the number of elements and the policies, along
+    // with the group of the offsets of the indexed accesses.
+    let (data, mut evl, policy, offsets) = match addressing {
+        Addressing::WholeRegister => {
+            let data = Group::new(m.vd, m.eew, m.nf.ilog2() as i32).check()?;
+            (data, m.nf as u32 * vlenb * 8 / m.eew, None, None)
+        }
+        Addressing::Mask => {
+            let unit = unit(state)?;
+            (
+                Group::new(m.vd, 8, 0),
+                unit.vl.div_ceil(8),
+                Some((true, false)),
+                None,
+            )
+        }
+        Addressing::Indexed => {
+            let unit = unit(state)?;
+            let vtype = unit.vtype;
+            let data = Group::new(m.vd, vtype.sew, vtype.lmul).check()?;
+            let offsets = Group::scaled(m.rs2, m.eew, &vtype).check()?;
+            (data, unit.vl, Some((vtype.ta, vtype.ma)), Some(offsets))
+        }
+        _ => {
+            let unit = unit(state)?;
+            let vtype = unit.vtype;
+            let data = Group::scaled(m.vd, m.eew, &vtype).check()?;
+            (data, unit.vl, Some((vtype.ta, vtype.ma)), None)
+        }
+    };
+
+    let fields = match addressing {
+        Addressing::WholeRegister => 1,
+        _ => m.nf,
+    };
+    if data.regs() * fields > 8 || data.reg + data.regs() * fields > 32 {
+        return Err(illegal());
+    }
+
+    if access == Access::Load {
+        if !m.vm && data.reg == 0 {
+            return Err(illegal());
+        }
+
+        // The fields of a segment can not overlap the offsets at all.
+        if let Some(offsets) = offsets {
+            for field in 0..fields {
+                let dest = Group::new(data.reg + field * data.regs(), data.eew, data.emul);
+                match fields {
+                    1 => check_overlap(dest, offsets)?,
+                    _ if dest.overlaps(&offsets) => return Err(illegal()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let bytes = data.eew / 8;
+    let base = state.get_r(m.rs1)?;
+    let stride = match addressing {
+        Addressing::Strided => state.get_r(m.rs2)?,
+        _ => bytes * fields as u32,
+    };
+
+    'elements: for i in vstart..evl {
+        if !m.vm && !state.vregs().mask(0, i) {
+            if let Some((_, true)) = policy
+                && access == Access::Load
+            {
+                for field in 0..fields {
+                    let reg = data.reg + field * data.regs();
+                    state.vregs_mut().set(reg, i, data.eew, u64::MAX);
+                }
+            }
+            continue;
+        }
+
+        let offset = match offsets {
+            Some(offsets) => state.vregs().get(offsets.reg, i, offsets.eew) as u32,
+            None => i.wrapping_mul(stride),
+        };
+
+        for field in 0..fields {
+            let addr = base.wrapping_add(offset).wrapping_add(field as u32 * bytes) as u64;
+            let reg = data.reg + field * data.regs();
+
+            let result = match access {
+                Access::Load => read_element(state, addr, bytes)
+                    .map(|val| state.vregs_mut().set(reg, i, data.eew, val)),
+                _ => {
+                    let val = state.vregs().get(reg, i, data.eew);
+                    write_element(state, addr, bytes, val)
+                }
+            };
+
+            match result {
+                Ok(()) => {}
+                Err(state::Error::Exception(_))
+                    if addressing == Addressing::FaultOnlyFirst && i > 0 =>
+                {
+                    let vtype = state.csrs().get(csr::VTYPE);
+                    state.csrs_mut().set_vector_config(i, vtype);
+                    evl = i;
+                    break 'elements;
+                }
+                Err(err) => {
+                    state.csrs_mut().set(csr::VSTART, i);
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+
+    if let Some((true, _)) = policy
+        && access == Access::Load
+        && vstart < evl
+    {
+        for field in 0..fields {
+            let reg = data.reg + field * data.regs();
+            let end = data.regs() as u32 * vlenb;
+            state.vregs_mut().fill(reg, (evl * bytes).min(end)..end);
+        }
+    }
+
+    state.csrs_mut().set(csr::VSTART, 0);
+    Ok(None)
+}
+
+fn read_element<const M: usize>(
+    state: &mut State<M>,
+    addr: u64,
+    bytes: u32,
+) -> Result<u64, state::Error> {
+    Ok(match bytes {
+        1 => state.get_mem_u8(addr)? as u64,
+        2 => state.get_mem_u16(addr)? as u64,
+        4 => state.get_mem_u32(addr)? as u64,
+        _ => state.get_mem_u64(addr)?,
+    })
+}
+
+fn write_element<const M: usize>(
+    state: &mut State<M>,
+    addr: u64,
+    bytes: u32,
+    val: u64,
+) -> Result<(), state::Error> {
+    match bytes {
+        1 => state.set_mem_u8(addr, val as u8),
+        2 => state.set_mem_u16(addr, val as u16),
+        4 => state.set_mem_u32(addr, val as u32),
+        _ => state.set_mem_u64(addr, val),
+    }
+}
+
+// The widths of the operands of an element-wise operation as the log2 of their ratio
+// to SEW. The destination of the mask ones is a mask, and the carry ones take v0 as
+// the carry in of every element instead of a mask.
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    vd: i32,
+    vs2: i32,
+    src: i32,
+    mask: bool,
+    carry: bool,
+}
+
+const SINGLE: Shape = Shape {
+    vd: 0,
+    vs2: 0,
+    src: 0,
+    mask: false,
+    carry: false,
+};
+
+// Places the result of op on the elements of vs2 and the second source in vd, all of
+// them SEW wide. op also gets the element of vd and SEW.
+pub fn binary<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    elementwise(state, v, SINGLE, op)
+}
+
+// Like binary, with the elements of vd twice as wide as SEW.
+pub fn widening<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape { vd: 1, ..SINGLE };
+    elementwise(state, v, shape, op)
+}
+
+// Like binary, with the elements of vd and vs2 twice as wide as SEW.
+pub fn widening_wide<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape {
+        vd: 1,
+        vs2: 1,
+        ..SINGLE
+    };
+    elementwise(state, v, shape, op)
+}
+
+// Like binary, with the elements of vs2 twice as wide as SEW.
+pub fn narrowing<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape { vs2: 1, ..SINGLE };
+    elementwise(state, v, shape, op)
+}
+
+// Sets the bits of the mask in vd to the lowest bit of the result of op on the
+// elements of vs2 and the second source.
+pub fn compare<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape {
+        mask: true,
+        ..SINGLE
+    };
+    elementwise(state, v, shape, op)
+}
+
+// Like binary, op gets the bit of v0 of each element in place of the element of vd,
+// none of the elements are masked off.
+pub fn carry<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape {
+        carry: true,
+        ..SINGLE
+    };
+    elementwise(state, v, shape, op)
+}
+
+// Like compare, op gets the bit of v0 of each element as the carry in unless vm is
+// set, none of the elements are masked off.
+pub fn carry_out<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape {
+        mask: true,
+        carry: true,
+        ..SINGLE
+    };
+    elementwise(state, v, shape, op)
+}
+
+// Places the elements of vs2 that are 2 to the power of frac narrower than SEW in vd,
+// sign or zero extended to SEW.
+pub fn extend<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    vs2: u8,
+    vm: bool,
+    frac: u8,
+    sign: bool,
+) -> Result<Option<u64>, InstError> {
+    let v = V {
+        vd,
+        vs2,
+        src: VSrc::Imm(0),
+        vm,
+    };
+    let shape = Shape {
+        vs2: -(frac as i32),
+        ..SINGLE
+    };
+
+    elementwise(state, v, shape, |a, _, _, sew| match sign {
+        true => signed(a, sew >> frac) as u64,
+        false => a,
+    })
+}
+
+// Like binary, for the fixed point operations whose op also gets the rounding mode
+// in vxrm and returns whether the result saturated, which sets vxsat.
+pub fn fixed_point<const M: usize, O: FnMut(u64, u64, u32, u32) -> (u64, bool)>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    saturating(state, v, SINGLE, op)
+}
+
+// Like fixed_point, with the elements of vs2 twice as wide as SEW.
+pub fn narrowing_fixed_point<const M: usize, O: FnMut(u64, u64, u32, u32) -> (u64, bool)>(
+    state: &mut State<M>,
+    v: V,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let shape = Shape { vs2: 1, ..SINGLE };
+    saturating(state, v, shape, op)
+}
+
+fn saturating<const M: usize, O: FnMut(u64, u64, u32, u32) -> (u64, bool)>(
+    state: &mut State<M>,
+    v: V,
+    shape: Shape,
+    mut op: O,
+) -> Result<Option<u64>, InstError> {
+    let vxrm = state.csrs().get(csr::VXRM);
+    let mut saturated = false;
+
+    elementwise(state, v, shape, |a, b, _, sew| {
+        let (val, saturate) = op(a, b, sew, vxrm);
+        saturated |= saturate;
+        val
+    })?;
+
+    if saturated {
+        state.csrs_mut().set(csr::VXSAT, 1);
+    }
+
+    Ok(None)
+}
+
+// Runs op on every active element from vstart to vl with the operands of the shape.
+fn elementwise<const M: usize, O: FnMut(u64, u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    shape: Shape,
+    mut op: O,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let vtype = unit.vtype;
+    let group =
+        |reg, width| Group::new(reg, vector::scale(vtype.sew, width), vtype.lmul + width).check();
+
+    let vd = match shape.mask {
+        true => Group::mask(v.vd),
+        false => group(v.vd, shape.vd)?,
+    };
+    let vs2 = group(v.vs2, shape.vs2)?;
+    check_overlap(vd, vs2)?;
+
+    let src_eew = vector::scale(vtype.sew, shape.src);
+    let (vs1, scalar) = match v.src {
+        VSrc::Vector(vs1) => {
+            check_overlap(vd, group(vs1, shape.src)?)?;
+            (vs1, 0)
+        }
+        VSrc::Scalar(rs1) => (0, state.get_r(rs1)? as u64 & ones(src_eew)),
+        VSrc::Imm(imm) => (0, imm as u64 & ones(src_eew)),
+    };
+
+    // v0 can only be overwritten by a mask while it is in use.
+    if !v.vm && !shape.mask && v.vd == 0 {
+        return Err(illegal());
+    }
+
+    let masked = !v.vm && !shape.carry;
+    let carry = !v.vm && shape.carry;
+    write_elements(state, &unit, vd, masked, |regs, i| {
+        let a = regs.get(vs2.reg, i, vs2.eew);
+        let b = match v.src {
+            VSrc::Vector(_) => regs.get(vs1, i, src_eew),
+            _ => scalar,
+        };
+        let d = match (carry, shape.mask) {
+            (true, _) => regs.mask(0, i) as u64,
+            (false, true) => 0,
+            (false, false) => regs.get(vd.reg, i, vd.eew),
+        };
+
+        Some(op(a, b, d, vtype.sew))
+    })
+}
+
+// Writes the value val returns for every active element from vstart to vl to the
+// group, None leaves the element as it is. The masked off elements and the tail are
+// left to the policies, the agnostic ones set all their bits, while the tail of a
+// mask is always agnostic. vstart is reset for the next instruction.
+fn write_elements<const M: usize, F: FnMut(&VRegisters, u32) -> Option<u64>>(
+    state: &mut State<M>,
+    unit: &Unit,
+    vd: Group,
+    masked: bool,
+    mut val: F,
+) -> Result<Option<u64>, InstError> {
+    for i in unit.vstart..unit.vl {
+        let val = match masked && !state.vregs().mask(0, i) {
+            true => unit.vtype.ma.then_some(u64::MAX),
+            false => val(state.vregs(), i),
+        };
+
+        if let Some(val) = val {
+            match vd.eew {
+                1 => state.vregs_mut().set_mask(vd.reg, i, val & 1 != 0),
+                eew => state.vregs_mut().set(vd.reg, i, eew, val),
+            }
+        }
+    }
+
+    if unit.vstart < unit.vl {
+        fill_tail(state.vregs_mut(), vd, unit.vl, unit.vtype.ta);
+    }
+
+    state.csrs_mut().set(csr::VSTART, 0);
+    Ok(None)
+}
+
+// Sets all the bits of the elements of the group from the index on when the tail is
+// agnostic, a mask always has an agnostic tail.
+fn fill_tail(regs: &mut VRegisters, vd: Group, from: u32, ta: bool) {
+    match vd.eew {
+        1 => {
+            for i in from..regs.vlenb() * 8 {
+                regs.set_mask(vd.reg, i, true);
+            }
+        }
+        eew if ta => regs.fill(vd.reg, from * eew / 8..vd.regs() as u32 * regs.vlenb()),
+        _ => {}
+    }
+}
+
+// Folds the active elements of vs2 into the element 0 of vs1 with op, which also gets
+// SEW, and places the result in the element 0 of vd. The widening ones have vd and
+// vs1 twice as wide as SEW.
+pub fn reduction<const M: usize, O: FnMut(u64, u64, u32) -> u64>(
+    state: &mut State<M>,
+    v: V,
+    widening: bool,
+    mut op: O,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let sew = unit.vtype.sew;
+    let width = if widening { sew * 2 } else { sew };
+    let vs2 = Group::new(v.vs2, sew, unit.vtype.lmul).check()?;
+    let VSrc::Vector(vs1) = v.src else {
+        return Err(illegal());
+    };
+
+    if unit.vstart != 0 || width > ELEN {
+        return Err(illegal());
+    }
+
+    if unit.vl == 0 {
+        return Ok(None);
+    }
+
+    let regs = state.vregs();
+    let mut acc = regs.get(vs1, 0, width);
+    for i in 0..unit.vl {
+        if v.vm || regs.mask(0, i) {
+            acc = op(acc, regs.get(vs2.reg, i, sew), sew) & ones(width);
+        }
+    }
+
+    let regs = state.vregs_mut();
+    regs.set(v.vd, 0, width, acc);
+    fill_tail(regs, Group::new(v.vd, width, 0), 1, unit.vtype.ta);
+
+    Ok(None)
+}
+
+// Places the result of op on the bits of the masks in vs2 and vs1 in the mask in vd.
+pub fn mask_logical<const M: usize, O: Fn(bool, bool) -> bool>(
+    state: &mut State<M>,
+    vd: u8,
+    vs2: u8,
+    vs1: u8,
+    op: O,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    write_elements(state, &unit, Group::mask(vd), false, |regs, i| {
+        Some(op(regs.mask(vs2, i), regs.mask(vs1, i)) as u64)
+    })
+}
+
+// The indices of the active elements up to vl whose bit is set in the mask in vs2,
+// for the instructions that can not be resumed from a vstart other than 0.
+fn set_bits<const M: usize>(state: &State<M>, vs2: u8, vm: bool) -> Result<Vec<u32>, InstError> {
+    let unit = unit(state)?;
+    if unit.vstart != 0 {
+        return Err(illegal());
+    }
+
+    let regs = state.vregs();
+    Ok((0..unit.vl)
+        .filter(|&i| (vm || regs.mask(0, i)) && regs.mask(vs2, i))
+        .collect())
+}
+
+// Places the number of active bits set in the mask in vs2 in rd.
+pub fn count<const M: usize>(
+    state: &mut State<M>,
+    rd: u8,
+    vs2: u8,
+    vm: bool,
+) -> Result<Option<u64>, InstError> {
+    let count = set_bits(state, vs2, vm)?.len() as u32;
+    state.set_r(rd, count)?;
+
+    Ok(None)
+}
+
+// Places the index of the first active bit set in the mask in vs2 in rd, or -1.
+pub fn first<const M: usize>(
+    state: &mut State<M>,
+    rd: u8,
+    vs2: u8,
+    vm: bool,
+) -> Result<Option<u64>, InstError> {
+    let first = set_bits(state, vs2, vm)?.first().copied();
+    state.set_r(rd, first.unwrap_or(u32::MAX))?;
+
+    Ok(None)
+}
+
+// Sets the bits of the mask in vd before, up to or only at the first active bit set
+// in the mask in vs2.
+pub fn set_first<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    vs2: u8,
+    vm: bool,
+    which: First,
+) -> Result<Option<u64>, InstError> {
+    let first = set_bits(state, vs2, vm)?.first().copied();
+    if vd == vs2 || (!vm && vd == 0) {
+        return Err(illegal());
+    }
+
+    let unit = unit(state)?;
+    write_elements(state, &unit, Group::mask(vd), !vm, |_, i| {
+        let val = match which {
+            First::Before => first.is_none_or(|first| i < first),
+            First::Including => first.is_none_or(|first| i <= first),
+            First::Only => first == Some(i),
+        };
+        Some(val as u64)
+    })
+}
+
+// Places the number of the active bits set in the mask in vs2 before each active
+// element in vd.
+pub fn iota<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    vs2: u8,
+    vm: bool,
+) -> Result<Option<u64>, InstError> {
+    let bits = set_bits(state, vs2, vm)?;
+    let unit = unit(state)?;
+    let vd = Group::new(vd, unit.vtype.sew, unit.vtype.lmul).check()?;
+    if vd.overlaps(&Group::mask(vs2)) || (!vm && vd.reg == 0) {
+        return Err(illegal());
+    }
+
+    write_elements(state, &unit, vd, !vm, |_, i| {
+        Some(bits.partition_point(|&bit| bit < i) as u64)
+    })
+}
+
+// Places the index of each active element in vd.
+pub fn id<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    vm: bool,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let vd = Group::new(vd, unit.vtype.sew, unit.vtype.lmul).check()?;
+    if !vm && vd.reg == 0 {
+        return Err(illegal());
+    }
+
+    write_elements(state, &unit, vd, !vm, |_, i| Some(i as u64))
+}
+
+// Places the element 0 of vs2 sign extended in rd, regardless of vl and vstart.
+pub fn move_to_scalar<const M: usize>(
+    state: &mut State<M>,
+    rd: u8,
+    vs2: u8,
+) -> Result<Option<u64>, InstError> {
+    let sew = unit(state)?.vtype.sew;
+    let val = signed(state.vregs().get(vs2, 0, sew), sew);
+    state.set_r(rd, val as u32)?;
+    state.csrs_mut().set(csr::VSTART, 0);
+
+    Ok(None)
+}
+
+// Places rs1 in the element 0 of vd, unless vstart is past vl.
+pub fn move_from_scalar<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    rs1: u8,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let val = state.get_r(rs1)?;
+    let unit = Unit {
+        vl: unit.vl.min(1),
+        ..unit
+    };
+
+    write_elements(
+        state,
+        &unit,
+        Group::new(vd, unit.vtype.sew, 0),
+        false,
+        |_, _| Some(val as u64),
+    )
+}
+
+// Slides the elements of vs2 up or down by the offset in rs1 or the immediate into
+// vd. The elements of vd below the offset are left as they are on the way up, while
+// the ones past VLMAX in vs2 read as 0 on the way down.
+pub fn slide<const M: usize>(
+    state: &mut State<M>,
+    v: V,
+    up: bool,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let offset = match v.src {
+        VSrc::Scalar(rs1) => state.get_r(rs1)?,
+        VSrc::Imm(imm) => imm,
+        VSrc::Vector(_) => return Err(illegal()),
+    };
+
+    permute(state, unit, v, up, |src, i| match up {
+        true => i.checked_sub(offset).map(|from| src[from as usize]),
+        false => Some(
+            src.get((i as u64 + offset as u64) as usize)
+                .copied()
+                .unwrap_or(0),
+        ),
+    })
+}
+
+// Slides the elements of vs2 up or down by one into vd, with rs1 in the element
+// left free.
+pub fn slide1<const M: usize>(
+    state: &mut State<M>,
+    v: V,
+    up: bool,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let VSrc::Scalar(rs1) = v.src else {
+        return Err(illegal());
+    };
+    let val = state.get_r(rs1)? as u64;
+    let vl = unit.vl;
+
+    permute(state, unit, v, up, |src, i| match up {
+        true if i == 0 => Some(val),
+        true => Some(src[i as usize - 1]),
+        false if i + 1 == vl => Some(val),
+        false => Some(src[i as usize + 1]),
+    })
+}
+
+// Places the elements of vs2 at the indices in vs1, rs1 or the immediate in vd, the
+// indices past VLMAX read as 0. The indices in vs1 are SEW wide or, with index_eew,
+// of that width.
+pub fn gather<const M: usize>(
+    state: &mut State<M>,
+    v: V,
+    index_eew: Option<u32>,
+) -> Result<Option<u64>, InstError> {
+    let unit = unit(state)?;
+    let vtype = unit.vtype;
+
+    let index = match v.src {
+        VSrc::Vector(vs1) => {
+            let indices = match index_eew {
+                Some(eew) => Group::scaled(vs1, eew, &vtype).check()?,
+                None => Group::new(vs1, vtype.sew, vtype.lmul).check()?,
+            };
+            if Group::new(v.vd, vtype.sew, vtype.lmul).overlaps(&indices) {
+                return Err(illegal());
+            }
+            let regs = state.vregs();
+            (0..unit.vl)
+                .map(|i| regs.get(indices.reg, i, indices.eew))
+                .collect()
+        }
+        VSrc::Scalar(rs1) => vec![state.get_r(rs1)? as u64; unit.vl as usize],
+        VSrc::Imm(imm) => vec![imm as u64; unit.vl as usize],
+    };
+
+    permute(state, unit, v, true, |src, i| {
+        Some(src.get(index[i as usize] as usize).copied().unwrap_or(0))
+    })
+}
+
+// Writes the elements of vd from the elements of vs2 up to VLMAX with val, vd can
+// only overlap vs2 when the elements move down.
+fn permute<const M: usize, F: FnMut(&[u64], u32) -> Option<u64>>(
+    state: &mut State<M>,
+    unit: Unit,
+    v: V,
+    up: bool,
+    mut val: F,
+) -> Result<Option<u64>, InstError> {
+    let vtype = unit.vtype;
+    let vd = Group::new(v.vd, vtype.sew, vtype.lmul).check()?;
+    let vs2 = Group::new(v.vs2, vtype.sew, vtype.lmul).check()?;
+    if (up && vd.overlaps(&vs2)) || (!v.vm && vd.reg == 0) {
+        return Err(illegal());
+    }
+
+    let regs = state.vregs();
+    let src: Vec<u64> = (0..vtype.vlmax(unit.vlen))
+        .map(|i| regs.get(vs2.reg, i, vs2.eew))
+        .collect();
+
+    write_elements(state, &unit, vd, !v.vm, |_, i| val(&src, i))
+}
+
+// Packs the elements of vs2 whose bit is set in the mask in vs1 into the lowest
+// elements of vd, the rest of the elements are the tail.
+pub fn compress<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    vs2: u8,
+    vs1: u8,
+) -> Result<Option<u64>, InstError> {
+    let bits = set_bits(state, vs1, true)?;
+    let unit = unit(state)?;
+    let vtype = unit.vtype;
+    let vd = Group::new(vd, vtype.sew, vtype.lmul).check()?;
+    let vs2 = Group::new(vs2, vtype.sew, vtype.lmul).check()?;
+    if vd.overlaps(&vs2) || vd.overlaps(&Group::mask(vs1)) {
+        return Err(illegal());
+    }
+
+    if unit.vl == 0 {
+        return Ok(None);
+    }
+
+    let regs = state.vregs();
+    let src: Vec<u64> = bits
+        .iter()
+        .map(|&i| regs.get(vs2.reg, i, vtype.sew))
+        .collect();
+
+    let regs = state.vregs_mut();
+    for (i, val) in src.iter().enumerate() {
+        regs.set(vd.reg, i as u32, vtype.sew, *val);
+    }
+    fill_tail(regs, vd, src.len() as u32, vtype.ta);
+
+    Ok(None)
+}
+
+// Copies the nr registers from vs2 on to vd, regardless of vtype and vl. The elements
+// before vstart are left as they are, with elements of SEW or of a byte when vtype is
+// illegal.
+pub fn move_whole<const M: usize>(
+    state: &mut State<M>,
+    vd: u8,
+    vs2: u8,
+    nr: u8,
+) -> Result<Option<u64>, InstError> {
+    if !vd.is_multiple_of(nr) || !vs2.is_multiple_of(nr) {
+        return Err(illegal());
+    }
+
+    let csrs = state.csrs();
+    let eew = Vtype::from_bits(csrs.get(csr::VTYPE)).map_or(8, |vtype| vtype.sew);
+    let start = csrs.get(csr::VSTART) * eew / 8;
+    let end = nr as u32 * state.vregs().vlenb();
+
+    if start < end {
+        state.vregs_mut().copy(vd, vs2, start..end);
+    }
+    state.csrs_mut().set(csr::VSTART, 0);
+
+    Ok(None)
+}
+
+// The application vector length of vsetvli and vsetvl, the value of rs1, VLMAX when
+// rs1 is x0, or the current vl when rd is x0 as well.
+pub fn avl<const M: usize>(state: &State<M>, rd: u8, rs1: u8) -> Result<Avl, InstError> {
+    Ok(match (rd, rs1) {
+        (_, 1..) => Avl::Value(state.get_r(rs1)?),
+        (1.., 0) => Avl::Max,
+        _ => Avl::Keep,
+    })
+}
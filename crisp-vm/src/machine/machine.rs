@@ -1,20 +1,45 @@
 use thiserror::Error;
 
 use crate::machine::{
-    instructions::{self, decode},
+    counters, csr,
+    instructions::{self, InstError, decode},
     state,
+    trap::{self, Exception},
 };
 
+// The names the calling convention gives the general registers.
+const ABI_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
     State(#[from] state::Error),
 
-    #[error(transparent)]
-    Decode(#[from] decode::Error),
-
     #[error(transparent)]
     Execute(#[from] instructions::InstError),
+
+    #[error("stopped at the breakpoint at {0:x}")]
+    Breakpoint(u64),
+
+    #[error("no trap handler for the {0:?} interrupt")]
+    Interrupt(trap::Interrupt),
+}
+
+impl Error {
+    // The architectural exception behind the error if there is one, the rest of the
+    // errors are problems with the machine itself.
+    fn exception(&self) -> Option<Exception> {
+        match self {
+            Error::State(state::Error::Exception(exception))
+            | Error::Execute(InstError::State(state::Error::Exception(exception)))
+            | Error::Execute(InstError::Exception(exception)) => Some(*exception),
+            _ => None,
+        }
+    }
 }
 
 pub struct Machine<const M: usize> {
@@ -26,18 +51,65 @@ impl<const M: usize> Machine<M> {
         Machine { state }
     }
 
-    pub fn fetch_decode(&self) -> Result<instructions::Inst, Error> {
-        let inst = self.state.get_mem_u32(self.state.get_pc())?;
-        Ok(instructions::decode(inst)?)
+    // Fetches the instruction at PC and returns its raw bits along with its length in
+    // bytes. The lowest two bits of a 32 bit instruction are always set, anything else
+    // is a 16 bit compressed instruction.
+    pub fn fetch(&mut self) -> Result<(u32, u32), Error> {
+        let pc = self.state.get_pc();
+
+        let low = self.state.fetch_mem_u16(pc)?;
+        if low & 0b11 != 0b11 {
+            return Ok((low as u32, 2));
+        }
+
+        let high = self
+            .state
+            .fetch_mem_u16(self.state.to_xlen(pc.wrapping_add(2)))?;
+        Ok((((high as u32) << 16) | low as u32, 4))
     }
 
+    // Decodes the raw bits of an instruction of the length in bytes. An instruction
+    // that can not be decoded is an illegal instruction. The compressed instructions
+    // are illegal while the C extension is turned off, and so are the ones that refer
+    // to x16 to x31 on RV32E, which does not have them.
+    pub fn decode(&self, inst: u32, len: u32) -> Result<instructions::Inst, Error> {
+        let illegal = || InstError::Exception(Exception::IllegalInstruction(inst)).into();
+        let decoded = match len {
+            2 if self.state.instruction_alignment() != 2 => return Err(illegal()),
+            2 => {
+                let config = self.state.config();
+                instructions::decode_compressed(inst as u16, config.xlen, &config.extensions)
+            }
+            _ => instructions::decode(inst),
+        };
+
+        match decoded {
+            Ok(decoded) if decoded.max_x_register() < self.state.x_registers() => Ok(decoded),
+            Ok(_) | Err(decode::Error::UnknownInst(_)) => Err(illegal()),
+        }
+    }
+
+    // Logs the values on the general registers along with their ABI names. The ILP32E
+    // ABI of RV32E differs from ILP32 in more than the missing registers, which the
+    // dump spells out.
     pub fn log_r(&self) {
-        for i in 0..31 {
+        if self.state.config().rve {
             log::info!(
                 target: "stat",
-                "x{:02} {:x}",
-                i + 1,
-                self.state.get_r(i + 1).expect("could not fetch value"),
+                "ilp32e: x16 to x31 do not exist, arguments in a0 to a5 only, s0 and s1 \
+                 the only saved registers, sp and 8 byte values 4 byte aligned, no \
+                 floating point arguments"
+            );
+        }
+
+        for i in 1..self.state.x_registers() {
+            log::info!(
+                target: "stat",
+                "x{:02} {:<4} {:x}",
+                i,
+                ABI_NAMES[i as usize],
+                self.state
+                    .to_xlen(self.state.get_x(i).expect("could not fetch value")),
             );
         }
     }
@@ -45,24 +117,108 @@ impl<const M: usize> Machine<M> {
     pub fn run(&mut self) -> Result<(), Error> {
         log::debug!(target: "loop", "running machine",);
 
-        let mut cycles = 0;
         loop {
-            cycles += 1;
-            log::debug!(target: "loop", "--------- {} ---------", cycles);
+            let cycle = counters::get(self.state.csrs(), counters::CYCLE);
+            log::debug!(target: "loop", "--------- {} ---------", cycle);
 
-            let pc = self.state.get_pc();
+            self.step()?;
 
-            log::debug!(target: "loop", "fetch_decode pc:{:x}", pc);
-            let inst = self.fetch_decode()?;
+            // The hart is spinning on something, give the host thread up for a while.
+            if self.state.take_yield() {
+                std::thread::yield_now();
+            }
+        }
+    }
+
+    // Fetches, decodes and executes a single instruction, unless an interrupt is taken
+    // or the hart is parked by a WFI, and counts the step on the counters. An
+    // interrupt or an exception is taken as a trap when the mode it goes to has a trap
+    // handler installed (a non zero xtvec), otherwise it is returned as an error. With a
+    // debugger attached, the breakpoints stop the machine at the EBREAK instead, to be
+    // resumed from there.
+    pub fn step(&mut self) -> Result<(), Error> {
+        self.state.tick();
+
+        let retired = self.advance()?;
+        self.state.count(retired);
+
+        Ok(())
+    }
+
+    // Does the work of a step and returns whether an instruction was retired.
+    fn advance(&mut self) -> Result<bool, Error> {
+        let pc = self.state.get_pc();
+
+        // Interrupts are taken between the instructions, with the next one to run in
+        // xepc.
+        if let Some(interrupt) = trap::pending_interrupt(&self.state) {
+            if !self.trap(pc, interrupt.cause(self.state.xlen()), 0) {
+                return Err(Error::Interrupt(interrupt));
+            }
+
+            self.state.set_waiting(false);
+            return Ok(false);
+        }
 
-            if matches!(inst, instructions::Inst::ECALL) {
-                self.log_r();
+        // A parked hart wakes up on any interrupt that is pending and enabled in mie,
+        // even when the interrupts are disabled globally, and continues past the WFI.
+        if self.state.is_waiting() {
+            let csrs = self.state.csrs();
+            if csrs.get(csr::MIP) & csrs.get(csr::MIE) == 0 {
+                return Ok(false);
             }
 
-            match inst.execute(&mut self.state)? {
-                Some(pc) => self.state.set_pc(pc),
-                None => self.state.set_pc(pc + 4),
+            self.state.set_waiting(false);
+        }
+
+        match self.execute(pc) {
+            Ok(next) => {
+                self.state.set_pc(next);
+                Ok(true)
             }
+            Err(err) => match err.exception() {
+                Some(Exception::Breakpoint(addr)) if self.state.config().debugger => {
+                    Err(Error::Breakpoint(addr))
+                }
+                Some(exception) if self.trap(pc, exception.code() as u64, exception.tval()) => {
+                    Ok(false)
+                }
+                _ => Err(err),
+            },
+        }
+    }
+
+    // Takes a trap and continues at its handler, returns whether there was one.
+    fn trap(&mut self, pc: u64, cause: u64, tval: u64) -> bool {
+        let Some(handler) = trap::enter(&mut self.state, pc, cause, tval) else {
+            return false;
+        };
+
+        self.state.record_event(counters::EVENT_TRAP);
+        self.state.set_pc(handler);
+        true
+    }
+
+    // Executes the instruction at pc and returns the address of the next one. An
+    // operation the instruction is not allowed to do, like accessing a CSR that does
+    // not exist or needs a higher privilege level or a register RV32E does not have,
+    // makes it an illegal instruction.
+    fn execute(&mut self, pc: u64) -> Result<u64, Error> {
+        log::debug!(target: "loop", "fetch_decode pc:{:x}", pc);
+        let (raw, len) = self.fetch()?;
+        let inst = self.decode(raw, len)?;
+
+        if matches!(inst, instructions::Inst::ECALL) {
+            self.log_r();
+        }
+
+        match inst.execute(&mut self.state, len) {
+            Ok(Some(next)) => Ok(next),
+            Ok(None) => Ok(pc.wrapping_add(len as u64)),
+            Err(InstError::State(
+                state::Error::IllegalOperation | state::Error::InvalidRegister,
+            )) => Err(InstError::Exception(Exception::IllegalInstruction(raw)).into()),
+            Err(err) => Err(err.into()),
         }
     }
 }
@@ -0,0 +1,125 @@
+use crate::machine::{
+    csr, pmp,
+    state::{self, Access, Privilege, State},
+    trap::Exception,
+};
+
+const PAGE_SIZE: u64 = 4096;
+
+// Bits of a page table entry.
+const PTE_V: u32 = 1 << 0;
+const PTE_R: u32 = 1 << 1;
+const PTE_W: u32 = 1 << 2;
+const PTE_X: u32 = 1 << 3;
+const PTE_U: u32 = 1 << 4;
+const PTE_A: u32 = 1 << 6;
+const PTE_D: u32 = 1 << 7;
+
+// Translates a virtual address into a physical one (34 bits wide under Sv32) for an
+// access. Machine mode and the bare mode of satp, the only one there on RV64, use
+// the virtual address as is, in Sv32 the two level page table rooted at satp is
+// walked. The accessed and dirty bits of the leaf entry are set by the walk itself.
+// The walk reads and updates the page table with the privilege of supervisor mode as
+// far as PMP is concerned.
+pub fn translate<const M: usize>(
+    state: &mut State<M>,
+    addr: u64,
+    access: Access,
+) -> Result<u64, Exception> {
+    let privilege = state.access_privilege(access);
+    let satp = state.csrs().get(csr::SATP);
+    if privilege == Privilege::Machine || satp & csr::SATP_MODE == 0 {
+        return Ok(addr);
+    }
+
+    let status = state.csrs().get(csr::MSTATUS);
+    let vpn = [(addr >> 12) & 0x3ff, (addr >> 22) & 0x3ff];
+
+    let mut table = (satp & csr::SATP_PPN) as u64 * PAGE_SIZE;
+    for level in (0..2).rev() {
+        let pte_addr = table + vpn[level] * 4;
+        if !pmp::check(
+            state.csrs(),
+            pte_addr,
+            4,
+            Access::Load,
+            Privilege::Supervisor,
+        ) {
+            return Err(access.access_fault(addr));
+        }
+
+        let pte = state
+            .get_phys_mem_u32(pte_addr)
+            .ok_or(access.access_fault(addr))?;
+
+        // An invalid entry, or a writable one that is not readable, is reserved.
+        if pte & PTE_V == 0 || (pte & PTE_R == 0 && pte & PTE_W != 0) {
+            return Err(access.page_fault(addr));
+        }
+
+        let ppn = (pte >> 10) as u64;
+
+        // An entry that is neither readable nor executable points to the next level.
+        if pte & (PTE_R | PTE_X) == 0 {
+            table = ppn * PAGE_SIZE;
+            continue;
+        }
+
+        let permitted = match access {
+            Access::Fetch => pte & PTE_X != 0,
+            // mstatus.MXR makes the executable pages readable as well.
+            Access::Load => {
+                pte & PTE_R != 0 || (status & csr::MSTATUS_MXR != 0 && pte & PTE_X != 0)
+            }
+            Access::Store => pte & PTE_W != 0,
+        };
+
+        // User mode can only access the user pages. Supervisor mode can never execute
+        // them, and can only read and write them when mstatus.SUM is set.
+        let accessible = match privilege {
+            Privilege::User => pte & PTE_U != 0,
+            _ => pte & PTE_U == 0 || (access != Access::Fetch && status & csr::MSTATUS_SUM != 0),
+        };
+
+        // A megapage has to be aligned to its size.
+        let misaligned = level == 1 && ppn & 0x3ff != 0;
+        if !permitted || !accessible || misaligned {
+            return Err(access.page_fault(addr));
+        }
+
+        let updated = pte | PTE_A | if access == Access::Store { PTE_D } else { 0 };
+        if updated != pte {
+            if !pmp::check(
+                state.csrs(),
+                pte_addr,
+                4,
+                Access::Store,
+                Privilege::Supervisor,
+            ) {
+                return Err(access.access_fault(addr));
+            }
+
+            state
+                .set_phys_mem_u32(pte_addr, updated)
+                .ok_or(access.access_fault(addr))?;
+        }
+
+        let offset = addr & ((PAGE_SIZE << (10 * level)) - 1);
+        let base = (ppn >> (10 * level)) << (12 + 10 * level);
+        return Ok(base | offset);
+    }
+
+    Err(access.page_fault(addr))
+}
+
+// Orders the page table updates before the accesses that follow it. Every access
+// walks the page table, so, there is nothing to flush, only the privilege checks
+// remain. It is illegal in user mode, and in supervisor mode when mstatus.TVM is set.
+pub fn fence<const M: usize>(state: &State<M>) -> Result<(), state::Error> {
+    let tvm = state.csrs().get(csr::MSTATUS) & csr::MSTATUS_TVM != 0;
+    match state.get_privilege() {
+        Privilege::User => Err(state::Error::IllegalOperation),
+        Privilege::Supervisor if tvm => Err(state::Error::IllegalOperation),
+        _ => Ok(()),
+    }
+}
@@ -1,5 +1,16 @@
+pub mod clint;
+pub mod config;
+pub mod counters;
+pub mod crypto;
+pub mod csr;
 pub mod instructions;
+#[allow(clippy::module_inception)]
 mod machine;
+pub mod mmu;
+pub mod pmp;
+pub mod softfloat;
 pub mod state;
+pub mod trap;
+pub mod vector;
 
 pub use machine::{Error, Machine};
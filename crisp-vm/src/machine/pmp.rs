@@ -0,0 +1,120 @@
+use crate::machine::{
+    csr::{self, Csrs},
+    state::{Access, Privilege},
+};
+
+// The number of PMP entries, all of them are implemented.
+pub const ENTRIES: u16 = 16;
+
+// Fields of a pmpcfg entry.
+const CFG_R: u8 = 1 << 0;
+const CFG_W: u8 = 1 << 1;
+const CFG_X: u8 = 1 << 2;
+const CFG_A: u8 = 0b11 << 3;
+const CFG_L: u8 = 1 << 7;
+
+// Address matching modes of the A field.
+const OFF: u8 = 0;
+const TOR: u8 = 1;
+const NA4: u8 = 2;
+const NAPOT: u8 = 3;
+
+// Checks whether the access of len bytes at the physical address is permitted. The
+// lowest numbered entry that matches any of the bytes decides, and it has to match
+// all of them. Machine mode is only held to the locked entries and can access what
+// no entry matches, unlike supervisor and user mode.
+pub fn check(csrs: &Csrs, addr: u64, len: u64, access: Access, privilege: Privilege) -> bool {
+    for index in 0..ENTRIES {
+        let cfg = cfg(csrs, index);
+        let Some((start, end)) = range(csrs, index, cfg) else {
+            continue;
+        };
+
+        if addr >= end || addr + len <= start {
+            continue;
+        }
+
+        if addr < start || addr + len > end {
+            return false;
+        }
+
+        if privilege == Privilege::Machine && cfg & CFG_L == 0 {
+            return true;
+        }
+
+        let permission = match access {
+            Access::Fetch => CFG_X,
+            Access::Load => CFG_R,
+            Access::Store => CFG_W,
+        };
+        return cfg & permission != 0;
+    }
+
+    privilege == Privilege::Machine
+}
+
+// Legalizes a write to a pmpcfg CSR. The bytes of the locked entries keep their old
+// value, the reserved bits are hardwired to zero, and the reserved write only
+// permission (W without R) drops the W.
+pub fn legalize_cfg(old: u32, new: u32) -> u32 {
+    let old = old.to_le_bytes();
+    let new = new.to_le_bytes();
+
+    let cfgs: [u8; 4] = std::array::from_fn(|i| {
+        if old[i] & CFG_L != 0 {
+            return old[i];
+        }
+
+        let cfg = new[i] & (CFG_L | CFG_A | CFG_X | CFG_W | CFG_R);
+        if cfg & CFG_R == 0 { cfg & !CFG_W } else { cfg }
+    });
+
+    u32::from_le_bytes(cfgs)
+}
+
+// Whether the pmpaddr of an entry is locked, either by its own lock or by the lock of
+// the next entry when it uses this one as the bottom of its TOR range.
+pub fn is_addr_locked(csrs: &Csrs, index: u16) -> bool {
+    if cfg(csrs, index) & CFG_L != 0 {
+        return true;
+    }
+
+    if index + 1 == ENTRIES {
+        return false;
+    }
+
+    let next = cfg(csrs, index + 1);
+    next & CFG_L != 0 && (next & CFG_A) >> 3 == TOR
+}
+
+// The configuration byte of an entry.
+fn cfg(csrs: &Csrs, index: u16) -> u8 {
+    let val = csrs.get(csr::PMPCFG0 + index / 4);
+    (val >> (8 * (index % 4))) as u8
+}
+
+// The physical address range [start, end) matched by an entry, None if it is off.
+// pmpaddr holds the bits 33 to 2 of the address.
+fn range(csrs: &Csrs, index: u16, cfg: u8) -> Option<(u64, u64)> {
+    let addr = csrs.get(csr::PMPADDR0 + index) as u64;
+
+    match (cfg & CFG_A) >> 3 {
+        OFF => None,
+        TOR => {
+            let start = match index {
+                0 => 0,
+                index => (csrs.get(csr::PMPADDR0 + index - 1) as u64) << 2,
+            };
+            Some((start, addr << 2))
+        }
+        NA4 => Some((addr << 2, (addr << 2) + 4)),
+        // The trailing ones of the address encode the size of the range, starting
+        // from 8 bytes.
+        NAPOT => {
+            let ones = addr.trailing_ones();
+            let start = (addr & !((1 << ones) - 1)) << 2;
+            Some((start, start + (1 << (ones + 3))))
+        }
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,822 @@
+// IEEE 754 binary floating point arithmetic done with integers, so that the results
+// and the exception flags are the same whatever the host FPU does. The values are
+// passed around as their raw bits in the format they are in, and every result is
+// rounded once, with the tininess of the results detected after rounding as RISC-V
+// does. A NaN result is always the canonical NaN of its format.
+//
+// The registers are 64 bits wide, a narrower value is NaN-boxed in them with all of
+// the bits above it set. A value that is not boxed like that reads as the canonical
+// NaN of its format.
+
+// The exception flags, in the layout of fflags.
+pub const NX: u32 = 1 << 0;
+pub const UF: u32 = 1 << 1;
+pub const OF: u32 = 1 << 2;
+pub const DZ: u32 = 1 << 3;
+pub const NV: u32 = 1 << 4;
+
+// A binary interchange format, by the widths of its exponent and its fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    exp: u32,
+    frac: u32,
+}
+
+pub const F16: Format = Format { exp: 5, frac: 10 };
+pub const F32: Format = Format { exp: 8, frac: 23 };
+pub const F64: Format = Format { exp: 11, frac: 52 };
+
+impl Format {
+    // The width of the format in bits.
+    pub const fn width(self) -> u32 {
+        1 + self.exp + self.frac
+    }
+
+    pub const fn sign(self) -> u64 {
+        1 << (self.exp + self.frac)
+    }
+
+    // The quiet NaN with a positive sign and an empty payload.
+    pub const fn canonical_nan(self) -> u64 {
+        (self.exp_max() << self.frac) | (1 << (self.frac - 1))
+    }
+
+    pub fn is_nan(self, bits: u64) -> bool {
+        matches!(unpack(self, bits).kind, Kind::NaN { .. })
+    }
+
+    // The value NaN-boxed in a 64 bit register.
+    pub fn nan_box(self, bits: u64) -> u64 {
+        match self.width() {
+            64 => bits,
+            width => (u64::MAX << width) | bits,
+        }
+    }
+
+    // The value held in a 64 bit register, the canonical NaN unless it is properly
+    // NaN-boxed.
+    pub fn unbox(self, bits: u64) -> u64 {
+        match self.width() {
+            64 => bits,
+            width if bits >> width == u64::MAX >> width => bits & (u64::MAX >> (64 - width)),
+            _ => self.canonical_nan(),
+        }
+    }
+
+    const fn exp_max(self) -> u64 {
+        (1 << self.exp) - 1
+    }
+
+    const fn bias(self) -> i32 {
+        (1 << (self.exp - 1)) - 1
+    }
+
+    // The exponent of the smallest normal number.
+    const fn emin(self) -> i32 {
+        1 - self.bias()
+    }
+
+    // The number of bits of precision, including the implicit one.
+    const fn precision(self) -> i32 {
+        self.frac as i32 + 1
+    }
+}
+
+// The rounding modes, encoded as they are in the rm fields and frm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    // Round to nearest, ties to even.
+    NearestEven = 0,
+    // Round towards zero.
+    TowardsZero = 1,
+    // Round down, towards negative infinity.
+    Down = 2,
+    // Round up, towards positive infinity.
+    Up = 3,
+    // Round to nearest, ties to max magnitude.
+    NearestMaxMagnitude = 4,
+}
+
+impl Rounding {
+    // The rounding mode of an encoding, None for the reserved ones.
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            0 => Some(Rounding::NearestEven),
+            1 => Some(Rounding::TowardsZero),
+            2 => Some(Rounding::Down),
+            3 => Some(Rounding::Up),
+            4 => Some(Rounding::NearestMaxMagnitude),
+            _ => None,
+        }
+    }
+}
+
+// The classes reported by FCLASS, as the bits of its result.
+const CLASS_NEG_INF: u32 = 1 << 0;
+const CLASS_NEG_NORMAL: u32 = 1 << 1;
+const CLASS_NEG_SUBNORMAL: u32 = 1 << 2;
+const CLASS_NEG_ZERO: u32 = 1 << 3;
+const CLASS_POS_ZERO: u32 = 1 << 4;
+const CLASS_POS_SUBNORMAL: u32 = 1 << 5;
+const CLASS_POS_NORMAL: u32 = 1 << 6;
+const CLASS_POS_INF: u32 = 1 << 7;
+const CLASS_SNAN: u32 = 1 << 8;
+const CLASS_QNAN: u32 = 1 << 9;
+
+// A value taken apart. A finite value is sig * 2^exp, exactly.
+#[derive(Debug, Clone, Copy)]
+struct Unpacked {
+    sign: bool,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Zero,
+    Finite { exp: i32, sig: u128 },
+    Inf,
+    NaN { signaling: bool },
+}
+
+fn unpack(fmt: Format, bits: u64) -> Unpacked {
+    let sign = bits & fmt.sign() != 0;
+    let exp = (bits >> fmt.frac) & fmt.exp_max();
+    let frac = bits & ((1 << fmt.frac) - 1);
+
+    let kind = match (exp, frac) {
+        (0, 0) => Kind::Zero,
+        (0, frac) => Kind::Finite {
+            exp: fmt.emin() - fmt.frac as i32,
+            sig: frac as u128,
+        },
+        (exp, 0) if exp == fmt.exp_max() => Kind::Inf,
+        (exp, frac) if exp == fmt.exp_max() => Kind::NaN {
+            signaling: frac >> (fmt.frac - 1) == 0,
+        },
+        (exp, frac) => Kind::Finite {
+            exp: exp as i32 - fmt.bias() - fmt.frac as i32,
+            sig: frac as u128 | (1 << fmt.frac),
+        },
+    };
+
+    Unpacked { sign, kind }
+}
+
+fn zero(fmt: Format, sign: bool) -> u64 {
+    if sign { fmt.sign() } else { 0 }
+}
+
+fn inf(fmt: Format, sign: bool) -> u64 {
+    zero(fmt, sign) | (fmt.exp_max() << fmt.frac)
+}
+
+// The largest finite value.
+fn max_finite(fmt: Format, sign: bool) -> u64 {
+    inf(fmt, sign) - 1
+}
+
+// The number of bits needed to hold the value.
+fn bit_len(val: u128) -> i32 {
+    128 - val.leading_zeros() as i32
+}
+
+// Shifts the significand so that its leading one is at the bit, keeping the value.
+fn normalize(exp: i32, sig: u128, bit: i32) -> (i32, u128) {
+    let shift = bit - (bit_len(sig) - 1);
+    if shift >= 0 {
+        (exp - shift, sig << shift)
+    } else {
+        let shift = -shift as u32;
+        let sticky = sig & ((1 << shift) - 1) != 0;
+        (exp + shift as i32, (sig >> shift) | sticky as u128)
+    }
+}
+
+// The integer square root, rounded down.
+fn isqrt(val: u128) -> u128 {
+    let mut root = 0;
+    let mut rem = val;
+    let mut bit = 1 << ((bit_len(val) - 1) & !1);
+
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+
+    root
+}
+
+// Carries out the operations with a rounding mode and accrues the exception flags
+// they raise.
+pub struct Softfloat {
+    rounding: Rounding,
+    pub flags: u32,
+}
+
+impl Softfloat {
+    pub fn new(rounding: Rounding) -> Self {
+        Softfloat { rounding, flags: 0 }
+    }
+
+    pub fn add(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        self.sum(fmt, unpack(fmt, a), unpack(fmt, b))
+    }
+
+    pub fn sub(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        let mut b = unpack(fmt, b);
+        b.sign = !b.sign;
+        self.sum(fmt, unpack(fmt, a), b)
+    }
+
+    pub fn mul(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        let (a, b) = (unpack(fmt, a), unpack(fmt, b));
+        let sign = a.sign != b.sign;
+
+        match (a.kind, b.kind) {
+            (Kind::NaN { .. }, _) | (_, Kind::NaN { .. }) => self.nan(fmt, &[a, b]),
+            (Kind::Inf, Kind::Zero) | (Kind::Zero, Kind::Inf) => self.invalid(fmt),
+            (Kind::Inf, _) | (_, Kind::Inf) => inf(fmt, sign),
+            (Kind::Zero, _) | (_, Kind::Zero) => zero(fmt, sign),
+            (Kind::Finite { exp: ea, sig: ma }, Kind::Finite { exp: eb, sig: mb }) => {
+                self.round_pack(fmt, sign, ea + eb, ma * mb, false)
+            }
+        }
+    }
+
+    pub fn div(&mut self, fmt: Format, a: u64, b: u64) -> u64 {
+        let (a, b) = (unpack(fmt, a), unpack(fmt, b));
+        let sign = a.sign != b.sign;
+
+        match (a.kind, b.kind) {
+            (Kind::NaN { .. }, _) | (_, Kind::NaN { .. }) => self.nan(fmt, &[a, b]),
+            (Kind::Inf, Kind::Inf) | (Kind::Zero, Kind::Zero) => self.invalid(fmt),
+            (Kind::Inf, _) => inf(fmt, sign),
+            (_, Kind::Inf) | (Kind::Zero, _) => zero(fmt, sign),
+            (_, Kind::Zero) => {
+                self.flags |= DZ;
+                inf(fmt, sign)
+            }
+            (Kind::Finite { exp: ea, sig: ma }, Kind::Finite { exp: eb, sig: mb }) => {
+                // With both significands 61 bits long, the quotient has at least 64
+                // bits, more than enough to round any of the formats.
+                let (ea, ma) = normalize(ea, ma, 60);
+                let (eb, mb) = normalize(eb, mb, 60);
+                let quotient = (ma << 64) / mb;
+                let sticky = (ma << 64) % mb != 0;
+                self.round_pack(fmt, sign, ea - eb - 64, quotient, sticky)
+            }
+        }
+    }
+
+    pub fn sqrt(&mut self, fmt: Format, a: u64) -> u64 {
+        let a = unpack(fmt, a);
+
+        match a.kind {
+            Kind::NaN { .. } => self.nan(fmt, &[a]),
+            Kind::Zero => zero(fmt, a.sign),
+            _ if a.sign => self.invalid(fmt),
+            Kind::Inf => inf(fmt, false),
+            Kind::Finite { exp, sig } => {
+                // An even exponent halves exactly, the root of a 125 or 126 bit
+                // significand has at least 62 bits.
+                let (mut exp, mut sig) = normalize(exp, sig, 60);
+                if exp % 2 != 0 {
+                    exp -= 1;
+                    sig <<= 1;
+                }
+
+                let sig = sig << 64;
+                let root = isqrt(sig);
+                self.round_pack(fmt, false, (exp - 64) / 2, root, root * root != sig)
+            }
+        }
+    }
+
+    // The fused multiply add, a * b + c rounded once. Negating the product or the
+    // addend gives the rest of the family.
+    pub fn mul_add(
+        &mut self,
+        fmt: Format,
+        a: u64,
+        b: u64,
+        c: u64,
+        negate_product: bool,
+        negate_addend: bool,
+    ) -> u64 {
+        let (a, b, mut c) = (unpack(fmt, a), unpack(fmt, b), unpack(fmt, c));
+        c.sign ^= negate_addend;
+        let sign = (a.sign != b.sign) ^ negate_product;
+
+        // The product of infinity and zero is invalid even with a quiet NaN addend.
+        let product = match (a.kind, b.kind) {
+            (Kind::NaN { .. }, _) | (_, Kind::NaN { .. }) => return self.nan(fmt, &[a, b, c]),
+            (Kind::Inf, Kind::Zero) | (Kind::Zero, Kind::Inf) => {
+                self.flags |= NV;
+                return self.nan(fmt, &[c]);
+            }
+            (Kind::Inf, _) | (_, Kind::Inf) => Kind::Inf,
+            (Kind::Zero, _) | (_, Kind::Zero) => Kind::Zero,
+            (Kind::Finite { exp: ea, sig: ma }, Kind::Finite { exp: eb, sig: mb }) => {
+                Kind::Finite {
+                    exp: ea + eb,
+                    sig: ma * mb,
+                }
+            }
+        };
+
+        self.sum(
+            fmt,
+            Unpacked {
+                sign,
+                kind: product,
+            },
+            c,
+        )
+    }
+
+    // Converts a value between the formats, rounding it when the target is the
+    // narrower one.
+    pub fn convert(&mut self, from: Format, to: Format, a: u64) -> u64 {
+        let a = unpack(from, a);
+        match a.kind {
+            Kind::NaN { .. } => self.nan(to, &[a]),
+            Kind::Inf => inf(to, a.sign),
+            Kind::Zero => zero(to, a.sign),
+            Kind::Finite { exp, sig } => self.round_pack(to, a.sign, exp, sig, false),
+        }
+    }
+
+    // The sign injection of FSGNJ, FSGNJN and FSGNJX, the magnitude of a with the
+    // sign picked by op from the signs of a and b.
+    pub fn sign_inject<O: Fn(bool, bool) -> bool>(fmt: Format, a: u64, b: u64, op: O) -> u64 {
+        let sign = op(a & fmt.sign() != 0, b & fmt.sign() != 0);
+        (a & !fmt.sign()) | zero(fmt, sign)
+    }
+
+    // The minimum or the maximum of FMIN and FMAX, -0 is less than +0. A NaN operand is
+    // ignored unless both of them are.
+    pub fn min_max(&mut self, fmt: Format, a: u64, b: u64, max: bool) -> u64 {
+        let (ua, ub) = (unpack(fmt, a), unpack(fmt, b));
+        match (ua.kind, ub.kind) {
+            (Kind::NaN { .. }, Kind::NaN { .. }) => self.nan(fmt, &[ua, ub]),
+            (Kind::NaN { .. }, _) => {
+                self.nan(fmt, &[ua]);
+                b
+            }
+            (_, Kind::NaN { .. }) => {
+                self.nan(fmt, &[ub]);
+                a
+            }
+            _ => {
+                let a_less = total_key(fmt, a) < total_key(fmt, b);
+                if a_less != max { a } else { b }
+            }
+        }
+    }
+
+    // The minimum or the maximum of FMINM and FMAXM, like min_max but a NaN operand
+    // makes the result the canonical NaN.
+    pub fn minimum_maximum(&mut self, fmt: Format, a: u64, b: u64, max: bool) -> u64 {
+        let (ua, ub) = (unpack(fmt, a), unpack(fmt, b));
+        match (ua.kind, ub.kind) {
+            (Kind::NaN { .. }, _) | (_, Kind::NaN { .. }) => self.nan(fmt, &[ua, ub]),
+            _ => self.min_max(fmt, a, b, max),
+        }
+    }
+
+    // Rounds the value to an integer in the format with the rounding mode, for FROUND
+    // and FROUNDNX. Only the latter, the exact one, raises the inexact flag.
+    pub fn round_to_integral(&mut self, fmt: Format, a: u64, exact: bool) -> u64 {
+        let ua = unpack(fmt, a);
+        match ua.kind {
+            Kind::NaN { .. } => self.nan(fmt, &[ua]),
+            Kind::Finite { exp, sig } if exp < 0 => {
+                let (val, inexact) = round(self.rounding, ua.sign, exp, sig, 0, false);
+                if inexact && exact {
+                    self.flags |= NX;
+                }
+
+                match val {
+                    0 => zero(fmt, ua.sign),
+                    val => self.round_pack(fmt, ua.sign, 0, val, false),
+                }
+            }
+            _ => a,
+        }
+    }
+
+    // The comparisons of FEQ, FLT and FLE. Any NaN makes them false, a signaling one
+    // is invalid for all of them and a quiet one for the ordered ones.
+    pub fn eq(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, false)
+            .is_some_and(|order| order == std::cmp::Ordering::Equal)
+    }
+
+    pub fn lt(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, true)
+            .is_some_and(|order| order == std::cmp::Ordering::Less)
+    }
+
+    pub fn le(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, true)
+            .is_some_and(|order| order != std::cmp::Ordering::Greater)
+    }
+
+    // The quiet comparisons of FLTQ and FLEQ, only a signaling NaN is invalid.
+    pub fn lt_quiet(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, false)
+            .is_some_and(|order| order == std::cmp::Ordering::Less)
+    }
+
+    pub fn le_quiet(&mut self, fmt: Format, a: u64, b: u64) -> bool {
+        self.compare(fmt, a, b, false)
+            .is_some_and(|order| order != std::cmp::Ordering::Greater)
+    }
+
+    // The constant of FLI at the index, in the format. The ones that are too small for
+    // the format are subnormal and the ones that are too large are infinite.
+    pub fn constant(fmt: Format, index: u8) -> u64 {
+        // The constants as sig * 2^exp, the rest of the indices are special.
+        let (sig, exp) = match index {
+            0 => return Softfloat::constant(fmt, 16) | fmt.sign(),
+            1 => return 1 << fmt.frac,
+            2 => (1, -16),
+            3 => (1, -15),
+            4 => (1, -8),
+            5 => (1, -7),
+            6 => (1, -4),
+            7 => (1, -3),
+            8 => (1, -2),
+            9 => (5, -4),
+            10 => (3, -3),
+            11 => (7, -4),
+            12 => (1, -1),
+            13 => (5, -3),
+            14 => (3, -2),
+            15 => (7, -3),
+            16 => (1, 0),
+            17 => (5, -2),
+            18 => (3, -1),
+            19 => (7, -2),
+            20 => (1, 1),
+            21 => (5, -1),
+            22 => (3, 0),
+            23 => (1, 2),
+            24 => (1, 3),
+            25 => (1, 4),
+            26 => (1, 7),
+            27 => (1, 8),
+            28 => (1, 15),
+            29 => (1, 16),
+            30 => return inf(fmt, false),
+            _ => return fmt.canonical_nan(),
+        };
+
+        Softfloat::new(Rounding::NearestEven).round_pack(fmt, false, exp, sig, false)
+    }
+
+    // The class of the value as the one hot result of FCLASS.
+    pub fn classify(fmt: Format, a: u64) -> u32 {
+        let a = unpack(fmt, a);
+        let subnormal = |exp| exp == fmt.emin() - fmt.frac as i32;
+
+        match (a.kind, a.sign) {
+            (Kind::Inf, true) => CLASS_NEG_INF,
+            (Kind::Finite { exp, sig }, true) if subnormal(exp) && sig >> fmt.frac == 0 => {
+                CLASS_NEG_SUBNORMAL
+            }
+            (Kind::Finite { .. }, true) => CLASS_NEG_NORMAL,
+            (Kind::Zero, true) => CLASS_NEG_ZERO,
+            (Kind::Zero, false) => CLASS_POS_ZERO,
+            (Kind::Finite { exp, sig }, false) if subnormal(exp) && sig >> fmt.frac == 0 => {
+                CLASS_POS_SUBNORMAL
+            }
+            (Kind::Finite { .. }, false) => CLASS_POS_NORMAL,
+            (Kind::Inf, false) => CLASS_POS_INF,
+            (Kind::NaN { signaling: true }, _) => CLASS_SNAN,
+            (Kind::NaN { signaling: false }, _) => CLASS_QNAN,
+        }
+    }
+
+    // Converts a signed or an unsigned integer of the given width to the format.
+    pub fn int_to_float(&mut self, fmt: Format, val: u64, signed: bool, bits: u32) -> u64 {
+        let negative = signed && (val >> (bits - 1)) & 1 != 0;
+        let val = val & (u64::MAX >> (64 - bits));
+        let magnitude = if negative {
+            (1u128 << bits) - val as u128
+        } else {
+            val as u128
+        };
+
+        match magnitude {
+            0 => zero(fmt, false),
+            magnitude => self.round_pack(fmt, negative, 0, magnitude, false),
+        }
+    }
+
+    // Converts to a signed or an unsigned integer of the given width, returned in the
+    // low bits. The values out of its range, the infinities and the NaNs are invalid
+    // and saturate to the closest end of the range, the NaNs to the top of it.
+    pub fn float_to_int(&mut self, fmt: Format, a: u64, signed: bool, bits: u32) -> u64 {
+        let mask = u64::MAX >> (64 - bits);
+        let (min, max) = if signed {
+            (1 << (bits - 1), mask >> 1)
+        } else {
+            (0, mask)
+        };
+
+        let a = unpack(fmt, a);
+        let (exp, sig) = match a.kind {
+            Kind::NaN { .. } => {
+                self.flags |= NV;
+                return max;
+            }
+            Kind::Inf => {
+                self.flags |= NV;
+                return if a.sign { min } else { max };
+            }
+            Kind::Zero => return 0,
+            Kind::Finite { exp, sig } => (exp, sig),
+        };
+
+        // Anything this large is out of the range of any of the integers.
+        if exp > 64 {
+            self.flags |= NV;
+            return if a.sign { min } else { max };
+        }
+
+        // The largest magnitude that is in range with the sign of the value.
+        let limit = match (signed, a.sign) {
+            (true, true) => 1 << (bits - 1),
+            (_, false) => max as u128,
+            (false, true) => 0,
+        };
+
+        let (val, inexact) = round(self.rounding, a.sign, exp, sig, 0, false);
+        if val > limit {
+            self.flags |= NV;
+            return if a.sign { min } else { max };
+        }
+
+        if inexact {
+            self.flags |= NX;
+        }
+
+        if a.sign {
+            (val as u64).wrapping_neg() & mask
+        } else {
+            val as u64
+        }
+    }
+
+    // Converts to a signed integer of the given width rounding towards zero, like
+    // FCVTMOD. The result is the integer modulo 2^bits, the values out of the range of
+    // the integer are invalid and the infinities and the NaNs are zero.
+    pub fn float_to_int_modular(&mut self, fmt: Format, a: u64, bits: u32) -> u64 {
+        let mask = u64::MAX >> (64 - bits);
+        let a = unpack(fmt, a);
+        let (exp, sig) = match a.kind {
+            Kind::NaN { .. } | Kind::Inf => {
+                self.flags |= NV;
+                return 0;
+            }
+            Kind::Zero => return 0,
+            Kind::Finite { exp, sig } => (exp, sig),
+        };
+
+        // The low bits of anything this large are all zero.
+        if exp >= 64 {
+            self.flags |= NV;
+            return 0;
+        }
+
+        let (val, inexact) = round(Rounding::TowardsZero, a.sign, exp, sig, 0, false);
+        let limit = (1 << (bits - 1)) - !a.sign as u128;
+        if val > limit {
+            self.flags |= NV;
+        } else if inexact {
+            self.flags |= NX;
+        }
+
+        if a.sign {
+            (val as u64).wrapping_neg() & mask
+        } else {
+            val as u64 & mask
+        }
+    }
+
+    // The sum of two unpacked values. The sum of two zeros, or of two opposite values,
+    // is only negative when rounding down or when both of the operands are negative.
+    fn sum(&mut self, fmt: Format, a: Unpacked, b: Unpacked) -> u64 {
+        match (a.kind, b.kind) {
+            (Kind::NaN { .. }, _) | (_, Kind::NaN { .. }) => self.nan(fmt, &[a, b]),
+            (Kind::Inf, Kind::Inf) if a.sign != b.sign => self.invalid(fmt),
+            (Kind::Inf, _) => inf(fmt, a.sign),
+            (_, Kind::Inf) => inf(fmt, b.sign),
+            (Kind::Zero, Kind::Zero) if a.sign == b.sign => zero(fmt, a.sign),
+            (Kind::Zero, Kind::Zero) => zero(fmt, self.rounding == Rounding::Down),
+            (Kind::Zero, Kind::Finite { exp, sig }) => {
+                self.round_pack(fmt, b.sign, exp, sig, false)
+            }
+            (Kind::Finite { exp, sig }, Kind::Zero) => {
+                self.round_pack(fmt, a.sign, exp, sig, false)
+            }
+            (Kind::Finite { exp: ea, sig: ma }, Kind::Finite { exp: eb, sig: mb }) => {
+                // Both of the significands get their leading one at bit 125, which
+                // leaves room for the carry and plenty of guard bits below the ones
+                // that are kept. What is shifted out of the smaller one is jammed
+                // into its lowest bit.
+                let (ea, ma) = normalize(ea, ma, 125);
+                let (eb, mb) = normalize(eb, mb, 125);
+                let ((big_sign, big_exp, big), (small_sign, small_exp, small)) =
+                    if (ea, ma) >= (eb, mb) {
+                        ((a.sign, ea, ma), (b.sign, eb, mb))
+                    } else {
+                        ((b.sign, eb, mb), (a.sign, ea, ma))
+                    };
+
+                let shift = (big_exp - small_exp) as u32;
+                let small = if shift >= 127 {
+                    1
+                } else {
+                    (small >> shift) | (small & ((1 << shift) - 1) != 0) as u128
+                };
+
+                let sig = if big_sign == small_sign {
+                    big + small
+                } else {
+                    big - small
+                };
+
+                match sig {
+                    0 => zero(fmt, self.rounding == Rounding::Down),
+                    sig => self.round_pack(fmt, big_sign, big_exp, sig, false),
+                }
+            }
+        }
+    }
+
+    // The ordering of two values, None when either of them is a NaN.
+    fn compare(
+        &mut self,
+        fmt: Format,
+        a: u64,
+        b: u64,
+        signaling: bool,
+    ) -> Option<std::cmp::Ordering> {
+        let (ua, ub) = (unpack(fmt, a), unpack(fmt, b));
+        for operand in [ua, ub] {
+            match operand.kind {
+                Kind::NaN { signaling: true } => self.flags |= NV,
+                Kind::NaN { signaling: false } if signaling => self.flags |= NV,
+                _ => {}
+            }
+        }
+
+        if fmt.is_nan(a) || fmt.is_nan(b) {
+            return None;
+        }
+
+        Some(numeric_key(fmt, a).cmp(&numeric_key(fmt, b)))
+    }
+
+    // The canonical NaN for an operation with NaN operands, which is invalid if any
+    // of them is a signaling NaN.
+    fn nan(&mut self, fmt: Format, operands: &[Unpacked]) -> u64 {
+        if operands
+            .iter()
+            .any(|operand| matches!(operand.kind, Kind::NaN { signaling: true }))
+        {
+            self.flags |= NV;
+        }
+
+        fmt.canonical_nan()
+    }
+
+    fn invalid(&mut self, fmt: Format) -> u64 {
+        self.flags |= NV;
+        fmt.canonical_nan()
+    }
+
+    // Rounds sig * 2^exp, plus a bit more when sticky is set, to the format and packs
+    // it. The value has to be non zero.
+    fn round_pack(&mut self, fmt: Format, sign: bool, exp: i32, sig: u128, sticky: bool) -> u64 {
+        // Make room for the guard bits, so that the sticky part is always below the
+        // bits that decide the rounding.
+        let (exp, sig) = match bit_len(sig) {
+            len if len < fmt.precision() + 3 => normalize(exp, sig, fmt.precision() + 2),
+            _ => (exp, sig),
+        };
+
+        // The exponent of the leading one and of the last bit that is kept, which
+        // stays at the one of the smallest subnormal for the tiny values.
+        let leading = exp + bit_len(sig) - 1;
+        let mut quantum = leading.max(fmt.emin()) - fmt.frac as i32;
+        let (mut val, inexact) = round(self.rounding, sign, exp, sig, quantum, sticky);
+        if val >> fmt.precision() != 0 {
+            val >>= 1;
+            quantum += 1;
+        }
+
+        // A result is tiny when it would still be below the smallest normal number
+        // after rounding it with an unbounded exponent.
+        let tiny = match leading - fmt.emin() {
+            0.. => false,
+            -1 => {
+                let quantum = leading - fmt.frac as i32;
+                let (val, _) = round(self.rounding, sign, exp, sig, quantum, sticky);
+                val >> fmt.precision() == 0
+            }
+            _ => true,
+        };
+
+        if inexact {
+            self.flags |= NX;
+            if tiny {
+                self.flags |= UF;
+            }
+        }
+
+        if val >> fmt.frac == 0 {
+            return zero(fmt, sign) | val as u64;
+        }
+
+        let biased = (quantum + fmt.frac as i32 + fmt.bias()) as u64;
+        if biased >= fmt.exp_max() {
+            self.flags |= OF | NX;
+            return match (self.rounding, sign) {
+                (Rounding::NearestEven | Rounding::NearestMaxMagnitude, _) => inf(fmt, sign),
+                (Rounding::Down, true) | (Rounding::Up, false) => inf(fmt, sign),
+                _ => max_finite(fmt, sign),
+            };
+        }
+
+        zero(fmt, sign) | (biased << fmt.frac) | (val as u64 & ((1 << fmt.frac) - 1))
+    }
+}
+
+// Rounds sig * 2^exp, plus a bit more when sticky is set, to a multiple of 2^quantum
+// and returns the multiple along with whether it was inexact. The sticky part has to
+// be below half of the quantum.
+fn round(
+    rounding: Rounding,
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    quantum: i32,
+    sticky: bool,
+) -> (u128, bool) {
+    if exp >= quantum {
+        return (sig << (exp - quantum), sticky);
+    }
+
+    let shift = (quantum - exp) as u32;
+    let (val, rem, half) = match shift {
+        128.. => (0, sig, None),
+        shift => (
+            sig >> shift,
+            sig & ((1 << shift) - 1),
+            Some(1 << (shift - 1)),
+        ),
+    };
+
+    let (above, tie) = match half {
+        Some(half) => (
+            rem > half || (rem == half && sticky),
+            rem == half && !sticky,
+        ),
+        None => (false, false),
+    };
+    let inexact = rem != 0 || sticky;
+
+    let up = match rounding {
+        Rounding::NearestEven => above || (tie && val & 1 != 0),
+        Rounding::TowardsZero => false,
+        Rounding::Down => inexact && sign,
+        Rounding::Up => inexact && !sign,
+        Rounding::NearestMaxMagnitude => above || tie,
+    };
+
+    (val + up as u128, inexact)
+}
+
+// A key that orders the values numerically, with both of the zeros equal.
+fn numeric_key(fmt: Format, bits: u64) -> i128 {
+    let magnitude = (bits & !fmt.sign()) as i128;
+    if bits & fmt.sign() != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+// Like numeric_key but with -0 below +0.
+fn total_key(fmt: Format, bits: u64) -> i128 {
+    let key = numeric_key(fmt, bits) * 2;
+    if bits & fmt.sign() != 0 { key - 1 } else { key }
+}
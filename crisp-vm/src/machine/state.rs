@@ -1,5 +1,15 @@
 use thiserror::Error;
 
+use crate::machine::{
+    clint::Clint,
+    config::{Config, Misaligned},
+    counters,
+    csr::{self, Csrs},
+    mmu, pmp,
+    trap::{Exception, Interrupt},
+    vector::{self, VRegisters},
+};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("invalid register")]
@@ -8,37 +18,156 @@ pub enum Error {
     #[error("illegal operation")]
     IllegalOperation,
 
-    #[error("invalid memory access")]
-    InvalidMemoryAccess,
+    #[error(transparent)]
+    Exception(#[from] Exception),
+}
+
+// The kinds of memory accesses, they decide the permissions an access needs and the
+// exceptions raised when it fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Fetch,
+    Load,
+    Store,
+}
+
+impl Access {
+    // The exception raised when the address is not aligned as the access needs.
+    pub fn address_misaligned(self, addr: u64) -> Exception {
+        match self {
+            Access::Fetch => Exception::InstructionAddressMisaligned(addr),
+            Access::Load => Exception::LoadAddressMisaligned(addr),
+            Access::Store => Exception::StoreAddressMisaligned(addr),
+        }
+    }
+
+    // The exception raised when the access is not permitted by the physical memory.
+    pub fn access_fault(self, addr: u64) -> Exception {
+        match self {
+            Access::Fetch => Exception::InstructionAccessFault(addr),
+            Access::Load => Exception::LoadAccessFault(addr),
+            Access::Store => Exception::StoreAccessFault(addr),
+        }
+    }
+
+    // The exception raised when the access is not permitted by the page table.
+    pub fn page_fault(self, addr: u64) -> Exception {
+        match self {
+            Access::Fetch => Exception::InstructionPageFault(addr),
+            Access::Load => Exception::LoadPageFault(addr),
+            Access::Store => Exception::StorePageFault(addr),
+        }
+    }
+}
+
+// The privilege levels a hart can run in, encoded as they are in the CSR addresses
+// and the xPP fields of the status registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Privilege {
+    User = 0b00,
+    Supervisor = 0b01,
+    Machine = 0b11,
+}
+
+impl Privilege {
+    // The privilege level held in an xPP field. The fields are WARL and never hold
+    // the reserved encoding (0b10).
+    pub fn from_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            0b00 => Privilege::User,
+            0b01 => Privilege::Supervisor,
+            _ => Privilege::Machine,
+        }
+    }
 }
 
 pub struct State<const M: usize> {
     // TODO: Does PC have to be aligned?
-    pc: u32,
+    pc: u64,
 
-    // The general purpose 32 bit registers. All the registers are treated equal
+    // The general purpose XLEN bit registers. All the registers are treated equal
     // on the machine level. The compiler is responsible for assigning special
-    // meaning.
-    // registers: [Register; 31],
-    registers: [u32; 31],
+    // meaning. The values are kept sign extended from XLEN to 64 bits. There are 15
+    // of them on RV32E.
+    registers: Vec<u64>,
+
+    // The 64 bit floating point registers, f0 is a register like any other. The
+    // single precision values are NaN-boxed in them.
+    fregisters: [u64; 32],
+
+    // The vector registers, their size is set by the config.
+    vregisters: VRegisters,
 
     // The main memory of the machine in bytes.
     memory: [u8; M],
+
+    // The address reserved by the last LR, if any. Any store that touches the
+    // reserved XLEN wide granule invalidates it.
+    reservation: Option<u64>,
+
+    // The privilege level the hart is currently running in.
+    privilege: Privilege,
+
+    // The control and status registers.
+    csrs: Csrs,
+
+    // The timer and software interrupt registers mapped in the physical address space
+    // next to the memory.
+    clint: Clint,
+
+    // Whether the hart is parked by a WFI until an interrupt becomes pending.
+    waiting: bool,
+
+    // Whether the hart hinted that it is spinning and the host can run something else,
+    // until the loop running the machine takes the hint.
+    yielding: bool,
+
+    // The events that happened during the current step, for the hpmcounters, and the
+    // counters the step wrote to.
+    events: u32,
+    written: u32,
+
+    // The options the machine was built with.
+    config: Config,
+
+    // The state of the pseudo random generator behind the seed CSR. It is
+    // deterministic so that runs of the machine can be reproduced.
+    entropy: u64,
 }
 
 impl<const M: usize> Default for State<M> {
     fn default() -> Self {
         Self {
             pc: 0,
-            registers: [0; 31],
+            registers: vec![0; 31],
+            fregisters: [0; 32],
+            vregisters: VRegisters::default(),
             memory: [0; M],
+            reservation: None,
+            privilege: Privilege::Machine,
+            csrs: Csrs::default(),
+            clint: Clint::default(),
+            waiting: false,
+            yielding: false,
+            events: 0,
+            written: 0,
+            config: Config::default(),
+            entropy: 0x2545_f491_4f6c_dd1d,
         }
+        .with_config(Config::default())
     }
 }
 
 impl<const M: usize> From<&[u8]> for State<M> {
+    // Loads the image at the start of the memory, which has to fit in it.
     fn from(val: &[u8]) -> Self {
-        // TODO: Check for bounds.
+        assert!(
+            val.len() <= M,
+            "the image of {} bytes does not fit in the {} bytes of memory",
+            val.len(),
+            M
+        );
+
         let mut state = State::default();
         state.memory[0..val.len()].copy_from_slice(val);
         state
@@ -46,96 +175,735 @@ impl<const M: usize> From<&[u8]> for State<M> {
 }
 
 impl<const M: usize> State<M> {
+    // Use the options in the config instead of the default ones.
+    pub fn with_config(mut self, config: Config) -> Self {
+        assert!(
+            matches!(config.xlen, 32 | 64),
+            "XLEN must be either 32 or 64"
+        );
+        assert!(
+            !config.rve || config.xlen == 32,
+            "RV32E needs an XLEN of 32"
+        );
+
+        assert!(
+            config.vlen.is_power_of_two()
+                && (vector::MIN_VLEN..=vector::MAX_VLEN).contains(&config.vlen),
+            "VLEN must be a power of two from {} to {}",
+            vector::MIN_VLEN,
+            vector::MAX_VLEN
+        );
+
+        assert!(
+            config.cache_block.is_power_of_two() && (4..=4096).contains(&config.cache_block),
+            "the cache block must be a power of two from 4 to 4096 bytes"
+        );
+
+        // The extensions that are not there for the XLEN are turned off, so that the
+        // config reports what the machine has.
+        let config = Config {
+            extensions: config.extensions.for_xlen(config.xlen),
+            ..config
+        };
+
+        self.config = config;
+        self.vregisters = VRegisters::new(config.vlen);
+        self.csrs
+            .set_vector_unit(config.extensions.zve32x, config.vlen / 8);
+        self.csrs.set_xlen(config.xlen);
+        if config.rve {
+            self.csrs.set_rve();
+        }
+        self.registers = vec![0; self.x_registers() as usize - 1];
+        self
+    }
+
+    // Get the options the machine was built with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    // Get the width of the integer registers in bits (XLEN).
+    pub fn xlen(&self) -> u32 {
+        self.config.xlen
+    }
+
+    // Get the number of general registers, x0 included, 16 on RV32E and 32 otherwise.
+    pub fn x_registers(&self) -> u8 {
+        match self.config.rve {
+            true => 16,
+            false => 32,
+        }
+    }
+
+    // Truncate a value to XLEN bits, as an address or an unsigned integer.
+    pub fn to_xlen(&self, value: u64) -> u64 {
+        match self.config.xlen {
+            32 => value as u32 as u64,
+            _ => value,
+        }
+    }
+
     // Get the program counter.
-    pub fn get_pc(&self) -> u32 {
+    pub fn get_pc(&self) -> u64 {
         self.pc
     }
 
     // Get the program counter.
     // TODO: Should we check for alignment here?
-    pub fn set_pc(&mut self, value: u32) {
-        self.pc = value;
+    pub fn set_pc(&mut self, value: u64) {
+        self.pc = self.to_xlen(value);
+    }
+
+    // Get the value on a floating point register.
+    pub fn get_f(&self, name: u8) -> Result<u64, Error> {
+        self.fregisters
+            .get(name as usize)
+            .copied()
+            .ok_or(Error::InvalidRegister)
+    }
+
+    // Set the value on a floating point register, which makes the floating point
+    // state dirty.
+    pub fn set_f(&mut self, name: u8, value: u64) -> Result<(), Error> {
+        let register = self
+            .fregisters
+            .get_mut(name as usize)
+            .ok_or(Error::InvalidRegister)?;
+        *register = value;
+
+        self.set_fp_dirty();
+        Ok(())
+    }
+
+    // Whether the floating point unit is on, it is off while mstatus.FS is Off and
+    // every floating point instruction and CSR is illegal.
+    pub fn is_fp_enabled(&self) -> bool {
+        self.csrs.get(csr::MSTATUS) & csr::MSTATUS_FS != 0
+    }
+
+    // Accrue the exception flags raised by a floating point operation in fflags.
+    pub fn raise_fp_flags(&mut self, flags: u32) {
+        if flags != 0 {
+            let fflags = self.csrs.get(csr::FFLAGS);
+            self.csrs.set(csr::FFLAGS, fflags | flags);
+            self.set_fp_dirty();
+        }
     }
 
-    // Get the value on a general register.
+    // Mark the floating point state dirty in mstatus.FS, for the context switches to
+    // know that it has to be saved.
+    fn set_fp_dirty(&mut self) {
+        let status = self.csrs.get(csr::MSTATUS);
+        self.csrs.set(csr::MSTATUS, status | csr::MSTATUS_FS);
+    }
+
+    // Whether the vector unit is on, it is off while mstatus.VS is Off and every
+    // vector instruction and CSR is illegal.
+    pub fn is_vector_enabled(&self) -> bool {
+        self.csrs.get(csr::MSTATUS) & csr::MSTATUS_VS != 0
+    }
+
+    // Get the vector registers.
+    pub fn vregs(&self) -> &VRegisters {
+        &self.vregisters
+    }
+
+    // Get the mutable vector registers, which makes the vector state dirty.
+    pub fn vregs_mut(&mut self) -> &mut VRegisters {
+        self.set_vector_dirty();
+        &mut self.vregisters
+    }
+
+    // Mark the vector state dirty in mstatus.VS, for the context switches to know
+    // that it has to be saved.
+    pub fn set_vector_dirty(&mut self) {
+        let status = self.csrs.get(csr::MSTATUS);
+        self.csrs.set(csr::MSTATUS, status | csr::MSTATUS_VS);
+    }
+
+    // Get the lower 32 bits of the value on a general register.
     pub fn get_r(&self, name: u8) -> Result<u32, Error> {
+        self.get_x(name).map(|value| value as u32)
+    }
+
+    // Set the value on a general register to a 32 bit one, which is sign extended to
+    // XLEN like the results of the word instructions of RV64.
+    pub fn set_r(&mut self, name: u8, value: u32) -> Result<(), Error> {
+        self.set_x(name, value as i32 as u64)
+    }
+
+    // Get the XLEN bit value on a general register, sign extended to 64 bits. The
+    // registers past x15 are invalid on RV32E.
+    pub fn get_x(&self, name: u8) -> Result<u64, Error> {
         match name {
             0 => Ok(0),
-            name if name > 31 => Err(Error::InvalidRegister),
-            name => Ok(self.registers[name as usize - 1]),
+            name => self
+                .registers
+                .get(name as usize - 1)
+                .copied()
+                .ok_or(Error::InvalidRegister),
         }
     }
 
-    // Set the value on a general register.
-    pub fn set_r(&mut self, name: u8, value: u32) -> Result<(), Error> {
+    // Set the value on a general register, only its lower XLEN bits are kept.
+    pub fn set_x(&mut self, name: u8, value: u64) -> Result<(), Error> {
+        let value = match self.config.xlen {
+            32 => value as i32 as u64,
+            _ => value,
+        };
+
         match name {
             0 => Ok(()),
-            name if name > 31 => Err(Error::InvalidRegister),
             name => {
-                self.registers[name as usize - 1] = value;
+                let register = self
+                    .registers
+                    .get_mut(name as usize - 1)
+                    .ok_or(Error::InvalidRegister)?;
+                *register = value;
                 Ok(())
             }
         }
     }
 
-    // TODO: Check for bounds.
-    pub fn get_mem_u8(&self, addr: u32) -> Result<u8, Error> {
-        Ok(self.memory[addr as usize])
+    // Get a 2 byte parcel of an instruction from memory for execution.
+    pub fn fetch_mem_u16(&mut self, base_addr: u64) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.read(base_addr, Access::Fetch)?))
+    }
+
+    // Get a 4 byte entry of a jump table from memory, which is fetched like an
+    // instruction.
+    pub fn fetch_mem_u32(&mut self, base_addr: u64) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.read(base_addr, Access::Fetch)?))
+    }
+
+    pub fn get_mem_u8(&mut self, addr: u64) -> Result<u8, Error> {
+        Ok(u8::from_le_bytes(self.read(addr, Access::Load)?))
     }
 
     // Get a 2 byte value from memory starting from the base address assuming
     // little endian-ness.
-    // TODO: Check for alignment.
-    // TODO: Check for bounds.
-    pub fn get_mem_u16(&self, base_addr: u32) -> Result<u16, Error> {
-        Ok(u16::from_le_bytes([
-            self.get_mem_u8(base_addr)?,
-            self.get_mem_u8(base_addr + 1)?,
-        ]))
+    pub fn get_mem_u16(&mut self, base_addr: u64) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.read(base_addr, Access::Load)?))
     }
 
     // Get a 4 byte value from memory starting from the base address assuming
     // little endian-ness.
-    // TODO: Check for alignment.
-    // TODO: Check for bounds.
-    pub fn get_mem_u32(&self, base_addr: u32) -> Result<u32, Error> {
-        Ok(u32::from_le_bytes([
-            self.get_mem_u8(base_addr)?,
-            self.get_mem_u8(base_addr + 1)?,
-            self.get_mem_u8(base_addr + 2)?,
-            self.get_mem_u8(base_addr + 3)?,
-        ]))
-    }
-
-    // TODO: Check for bounds.
-    pub fn set_mem_u8(&mut self, addr: u32, val: u8) -> Result<(), Error> {
-        self.memory[addr as usize] = val;
-        Ok(())
+    pub fn get_mem_u32(&mut self, base_addr: u64) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.read(base_addr, Access::Load)?))
+    }
+
+    // Get an 8 byte value from memory starting from the base address assuming
+    // little endian-ness.
+    pub fn get_mem_u64(&mut self, base_addr: u64) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.read(base_addr, Access::Load)?))
+    }
+
+    pub fn set_mem_u8(&mut self, addr: u64, val: u8) -> Result<(), Error> {
+        self.write(addr, val.to_le_bytes())
     }
 
     // Set a 2 byte value in memory starting at the base address with little
     // endian-ness.
-    // TODO: Check for bounds.
-    pub fn set_mem_u16(&mut self, base_addr: u32, val: u16) -> Result<(), Error> {
-        let [a, b] = val.to_le_bytes();
+    pub fn set_mem_u16(&mut self, base_addr: u64, val: u16) -> Result<(), Error> {
+        self.write(base_addr, val.to_le_bytes())
+    }
+
+    // Set a 4 byte value in memory starting at the base address with little
+    // endian-ness.
+    pub fn set_mem_u32(&mut self, base_addr: u64, val: u32) -> Result<(), Error> {
+        self.write(base_addr, val.to_le_bytes())
+    }
+
+    // Set an 8 byte value in memory starting at the base address with little
+    // endian-ness.
+    pub fn set_mem_u64(&mut self, base_addr: u64, val: u64) -> Result<(), Error> {
+        self.write(base_addr, val.to_le_bytes())
+    }
+
+    // Replace the 1 byte value in memory at the address with the result of applying op
+    // to it and return the value it held.
+    pub fn amo_mem_u8<O: FnOnce(u8) -> u8>(&mut self, addr: u64, op: O) -> Result<u8, Error> {
+        self.amo(addr, |bytes| {
+            Some(op(u8::from_le_bytes(bytes)).to_le_bytes())
+        })
+        .map(u8::from_le_bytes)
+    }
+
+    // Replace the 2 byte value in memory starting at the base address with the result
+    // of applying op to it and return the value it held.
+    pub fn amo_mem_u16<O: FnOnce(u16) -> u16>(
+        &mut self,
+        base_addr: u64,
+        op: O,
+    ) -> Result<u16, Error> {
+        self.amo(base_addr, |bytes| {
+            Some(op(u16::from_le_bytes(bytes)).to_le_bytes())
+        })
+        .map(u16::from_le_bytes)
+    }
+
+    // Replace the 4 byte value in memory starting at the base address with the result
+    // of applying op to it and return the value it held.
+    pub fn amo_mem_u32<O: FnOnce(u32) -> u32>(
+        &mut self,
+        base_addr: u64,
+        op: O,
+    ) -> Result<u32, Error> {
+        self.amo(base_addr, |bytes| {
+            Some(op(u32::from_le_bytes(bytes)).to_le_bytes())
+        })
+        .map(u32::from_le_bytes)
+    }
+
+    // Replace the 8 byte value in memory starting at the base address with the result
+    // of applying op to it and return the value it held.
+    pub fn amo_mem_u64<O: FnOnce(u64) -> u64>(
+        &mut self,
+        base_addr: u64,
+        op: O,
+    ) -> Result<u64, Error> {
+        self.amo(base_addr, |bytes| {
+            Some(op(u64::from_le_bytes(bytes)).to_le_bytes())
+        })
+        .map(u64::from_le_bytes)
+    }
+
+    // Replace the 1 byte value in memory at the address with val if it holds the
+    // expected one and return the value it held.
+    pub fn cas_mem_u8(&mut self, addr: u64, expected: u8, val: u8) -> Result<u8, Error> {
+        self.amo(addr, |bytes| {
+            (bytes == expected.to_le_bytes()).then_some(val.to_le_bytes())
+        })
+        .map(u8::from_le_bytes)
+    }
+
+    // Replace the 2 byte value in memory starting at the base address with val if it
+    // holds the expected one and return the value it held.
+    pub fn cas_mem_u16(&mut self, base_addr: u64, expected: u16, val: u16) -> Result<u16, Error> {
+        self.amo(base_addr, |bytes| {
+            (bytes == expected.to_le_bytes()).then_some(val.to_le_bytes())
+        })
+        .map(u16::from_le_bytes)
+    }
+
+    // Replace the 4 byte value in memory starting at the base address with val if it
+    // holds the expected one and return the value it held.
+    pub fn cas_mem_u32(&mut self, base_addr: u64, expected: u32, val: u32) -> Result<u32, Error> {
+        self.amo(base_addr, |bytes| {
+            (bytes == expected.to_le_bytes()).then_some(val.to_le_bytes())
+        })
+        .map(u32::from_le_bytes)
+    }
+
+    // Replace the 8 byte value in memory starting at the base address with val if it
+    // holds the expected one and return the value it held.
+    pub fn cas_mem_u64(&mut self, base_addr: u64, expected: u64, val: u64) -> Result<u64, Error> {
+        self.amo(base_addr, |bytes| {
+            (bytes == expected.to_le_bytes()).then_some(val.to_le_bytes())
+        })
+        .map(u64::from_le_bytes)
+    }
+
+    // Get a 4 byte value from the physical memory, bypassing the address translation.
+    pub fn get_phys_mem_u32(&self, addr: u64) -> Option<u32> {
+        let start = usize::try_from(addr).ok()?;
+        let bytes = self.memory.get(start..start.checked_add(4)?)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    // Set a 4 byte value in the physical memory, bypassing the address translation.
+    pub fn set_phys_mem_u32(&mut self, addr: u64, val: u32) -> Option<()> {
+        let start = usize::try_from(addr).ok()?;
+        let bytes = self.memory.get_mut(start..start.checked_add(4)?)?;
+        bytes.copy_from_slice(&val.to_le_bytes());
+        Some(())
+    }
+
+    // The privilege level an access is made with. mstatus.MPRV makes the loads and
+    // stores in machine mode use the privilege level in MPP instead.
+    pub fn access_privilege(&self, access: Access) -> Privilege {
+        let status = self.csrs.get(csr::MSTATUS);
+        if access != Access::Fetch
+            && self.privilege == Privilege::Machine
+            && status & csr::MSTATUS_MPRV != 0
+        {
+            Privilege::from_bits(status >> csr::MSTATUS_MPP.trailing_zeros())
+        } else {
+            self.privilege
+        }
+    }
 
-        self.set_mem_u8(base_addr, a)?;
-        self.set_mem_u8(base_addr + 1, b)?;
+    // Read the N bytes starting at the base address and write back what op makes of
+    // them, if anything, as a single atomic access. The whole access counts as a store,
+    // so, it raises a store fault even when the read fails or nothing is written.
+    fn amo<const N: usize, O: FnOnce([u8; N]) -> Option<[u8; N]>>(
+        &mut self,
+        base_addr: u64,
+        op: O,
+    ) -> Result<[u8; N], Error> {
+        let bytes = self.read(base_addr, Access::Store)?;
+        if let Some(val) = op(bytes) {
+            self.write(base_addr, val)?;
+        }
+
+        Ok(bytes)
+    }
+
+    // Get the N bytes starting at the base address. The virtual addresses are XLEN
+    // bits wide, whatever is above them is dropped.
+    fn read<const N: usize>(&mut self, base_addr: u64, access: Access) -> Result<[u8; N], Error> {
+        let base_addr = self.to_xlen(base_addr);
+        let addrs = self.translate::<N>(base_addr, access)?;
+        if !addrs.iter().all(|&addr| self.is_mapped(addr)) {
+            return Err(access.access_fault(base_addr).into());
+        }
+
+        if access == Access::Load {
+            self.record_event(counters::EVENT_LOAD);
+        }
+
+        let mut bytes = [0; N];
+        for (i, addr) in addrs.into_iter().enumerate() {
+            bytes[i] = match self.memory.get(addr as usize) {
+                Some(byte) => *byte,
+                None => self.clint.read(addr),
+            };
+        }
+
+        Ok(bytes)
+    }
+
+    // Set the N bytes starting at the base address. Nothing is written unless all of
+    // the bytes can be. Any store that touches the reserved granule, the XLEN wide
+    // block holding the reserved address, invalidates the reservation.
+    fn write<const N: usize>(&mut self, base_addr: u64, bytes: [u8; N]) -> Result<(), Error> {
+        let base_addr = self.to_xlen(base_addr);
+        let addrs = self.translate::<N>(base_addr, Access::Store)?;
+        if !addrs.iter().all(|&addr| self.is_mapped(addr)) {
+            return Err(Access::Store.access_fault(base_addr).into());
+        }
+
+        self.record_event(counters::EVENT_STORE);
+        for (addr, byte) in addrs.into_iter().zip(bytes) {
+            match self.memory.get_mut(addr as usize) {
+                Some(target) => *target = byte,
+                None => self.clint.write(addr, byte),
+            }
+        }
+
+        let granule = !(self.xlen() as u64 / 8 - 1);
+        let touched = |reserved: u64| {
+            (0..N as u64)
+                .any(|i| self.to_xlen(base_addr.wrapping_add(i)) & granule == reserved & granule)
+        };
+        if self.reservation.is_some_and(touched) {
+            self.reservation = None;
+        }
 
         Ok(())
     }
 
-    // Set a 4 byte value in memory starting at the base address with little
-    // endian-ness.
-    // TODO: Check for bounds.
-    pub fn set_mem_u32(&mut self, base_addr: u32, val: u32) -> Result<(), Error> {
-        let [a, b, c, d] = val.to_le_bytes();
+    // Whether there is memory or a device at the physical address.
+    fn is_mapped(&self, addr: u64) -> bool {
+        addr < M as u64 || Clint::contains(addr)
+    }
+
+    // Translate the addresses of the N bytes of an access starting at the base address
+    // into physical ones. Only an access that crosses a page boundary needs more than
+    // a single translation. Each physically contiguous run of the bytes has to pass
+    // the physical memory protection as a whole. An access that is not aligned to its
+    // size traps before any of that, unless the machine emulates them.
+    fn translate<const N: usize>(
+        &mut self,
+        base_addr: u64,
+        access: Access,
+    ) -> Result<[u64; N], Error> {
+        if self.config.misaligned == Misaligned::Trap && !base_addr.is_multiple_of(N as u64) {
+            return Err(access.address_misaligned(base_addr).into());
+        }
 
-        self.set_mem_u8(base_addr, a)?;
-        self.set_mem_u8(base_addr + 1, b)?;
-        self.set_mem_u8(base_addr + 2, c)?;
-        self.set_mem_u8(base_addr + 3, d)?;
+        let mut addrs = [0; N];
+        let mut page = None;
+
+        for (i, target) in addrs.iter_mut().enumerate() {
+            let addr = self.to_xlen(base_addr.wrapping_add(i as u64));
+            *target = match page {
+                Some((vpn, ppn)) if vpn == addr >> 12 => (ppn << 12) | (addr & 0xfff),
+                _ => mmu::translate(self, addr, access)?,
+            };
+            page = Some((addr >> 12, *target >> 12));
+        }
+
+        let privilege = self.access_privilege(access);
+        let mut start = 0;
+        for end in 1..=N {
+            if end < N && addrs[end] == addrs[end - 1] + 1 {
+                continue;
+            }
+
+            let len = (end - start) as u64;
+            if !pmp::check(&self.csrs, addrs[start], len, access, privilege) {
+                return Err(access.access_fault(base_addr).into());
+            }
+            start = end;
+        }
+
+        Ok(addrs)
+    }
+
+    // Reserve the word or doubleword starting at the address for a later store
+    // conditional.
+    pub fn set_reservation(&mut self, addr: u64) {
+        self.reservation = Some(self.to_xlen(addr));
+    }
+
+    // Remove the current reservation and return the address it was held on.
+    pub fn take_reservation(&mut self) -> Option<u64> {
+        self.reservation.take()
+    }
+
+    // Whether a reservation is held.
+    pub fn is_reserved(&self) -> bool {
+        self.reservation.is_some()
+    }
+
+    // Zero the cache block holding the address as a single store. Nothing is written
+    // unless the whole block can be, and the reservation is lost if it is in there.
+    pub fn zero_block(&mut self, addr: u64) -> Result<(), Error> {
+        let addr = self.to_xlen(addr);
+        let size = self.config.cache_block;
+        let block = self.translate_block(addr, Access::Store)?;
+
+        self.record_event(counters::EVENT_STORE);
+        for addr in block..block + size as u64 {
+            match self.memory.get_mut(addr as usize) {
+                Some(target) => *target = 0,
+                None => self.clint.write(addr, 0),
+            }
+        }
+
+        if self
+            .reservation
+            .is_some_and(|reserved| (reserved ^ addr) & !(size as u64 - 1) == 0)
+        {
+            self.reservation = None;
+        }
 
         Ok(())
     }
+
+    // Check that the cache block holding the address can be managed, which needs it
+    // to be readable or writable. A block that is neither raises a store fault.
+    pub fn check_block(&mut self, addr: u64) -> Result<(), Error> {
+        match self.translate_block(addr, Access::Load) {
+            Ok(_) => Ok(()),
+            Err(_) => self.translate_block(addr, Access::Store).map(|_| ()),
+        }
+    }
+
+    // Translate the address into the physical address of the cache block holding it.
+    // The block is aligned to its size, so, it sits in a single page and has to pass
+    // the physical memory protection as a whole. The faults carry the address itself.
+    fn translate_block(&mut self, addr: u64, access: Access) -> Result<u64, Error> {
+        let addr = self.to_xlen(addr);
+        let size = self.config.cache_block as u64;
+        let block = mmu::translate(self, addr, access)? & !(size - 1);
+
+        let privilege = self.access_privilege(access);
+        if !pmp::check(&self.csrs, block, size, access, privilege)
+            || !(block..block + size).all(|addr| self.is_mapped(addr))
+        {
+            return Err(access.access_fault(addr).into());
+        }
+
+        Ok(block)
+    }
+
+    // Advance the timer by a step and update the pending bits of the interrupts driven
+    // by the CLINT.
+    pub fn tick(&mut self) {
+        self.clint.tick();
+        self.csrs.set_time(self.clint.mtime());
+        self.set_interrupt_pending(Interrupt::MachineTimer, self.clint.mtip());
+        self.set_interrupt_pending(Interrupt::MachineSoftware, self.clint.msip());
+    }
+
+    // Record an event of the current step for the hpmcounters counting it.
+    pub fn record_event(&mut self, event: u32) {
+        self.events |= counters::event(event);
+    }
+
+    // Count the step on the counters, with the events recorded along the way, and
+    // start over for the next one.
+    pub fn count(&mut self, retired: bool) {
+        counters::count(&mut self.csrs, retired, self.events, self.written);
+        self.events = 0;
+        self.written = 0;
+    }
+
+    // Raise or clear the pending bit of an interrupt in mip, for the interrupt sources
+    // outside of the hart.
+    pub fn set_interrupt_pending(&mut self, interrupt: Interrupt, pending: bool) {
+        self.csrs.set_pending(interrupt.bit(), pending);
+    }
+
+    // Whether the hart is parked by a WFI.
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    // Park the hart until an interrupt becomes pending, or wake it up.
+    pub fn set_waiting(&mut self, waiting: bool) {
+        self.waiting = waiting;
+    }
+
+    // Hint that the hart is spinning, for pause and the wait on reservation set
+    // instructions.
+    pub fn hint_yield(&mut self) {
+        self.yielding = true;
+    }
+
+    // Take the hint that the hart is spinning, if there is one.
+    pub fn take_yield(&mut self) -> bool {
+        std::mem::take(&mut self.yielding)
+    }
+
+    // The alignment of the instructions in bytes (IALIGN), 2 with the C extension and
+    // 4 without it.
+    pub fn instruction_alignment(&self) -> u32 {
+        if self.csrs.get(csr::MISA) & csr::MISA_C != 0 {
+            2
+        } else {
+            4
+        }
+    }
+
+    // Get the privilege level the hart is running in.
+    pub fn get_privilege(&self) -> Privilege {
+        self.privilege
+    }
+
+    // Set the privilege level the hart is running in.
+    pub fn set_privilege(&mut self, privilege: Privilege) {
+        self.privilege = privilege;
+    }
+
+    // Get the value of a CSR as an instruction running at the current privilege
+    // level would. Accessing a CSR that does not exist or needs a higher privilege
+    // level is an illegal operation.
+    pub fn get_csr(&self, addr: u16) -> Result<u64, Error> {
+        if !self.is_csr_accessible(addr) {
+            return Err(Error::IllegalOperation);
+        }
+
+        self.csrs.read(addr).ok_or(Error::IllegalOperation)
+    }
+
+    // Set the value of a CSR as an instruction running at the current privilege
+    // level would. On top of the checks done on reads, writing to a read only CSR
+    // is an illegal operation as well. Turning the C extension off is ignored when
+    // the instruction after the write would not be 4 byte aligned.
+    pub fn set_csr(&mut self, addr: u16, val: u64) -> Result<(), Error> {
+        if csr::is_read_only(addr) || !self.is_csr_accessible(addr) {
+            return Err(Error::IllegalOperation);
+        }
+
+        let val = match addr {
+            csr::MISA if self.pc.wrapping_add(4) & 0b11 != 0 => val | csr::MISA_C as u64,
+            _ => val,
+        };
+
+        if let Some(index) = counters::machine_counter(addr) {
+            self.written |= 1 << index;
+        }
+
+        self.csrs.write(addr, val).ok_or(Error::IllegalOperation)?;
+        if matches!(addr, csr::FFLAGS..=csr::FCSR) {
+            self.set_fp_dirty();
+        }
+        if matches!(addr, csr::VSTART..=csr::VCSR) {
+            self.set_vector_dirty();
+        }
+
+        Ok(())
+    }
+
+    // Read the seed CSR, which has to be accessed with a write. Every read polls the
+    // entropy source, which is always ready (ES16) and returns 16 fresh bits.
+    pub fn read_seed(&mut self) -> Result<u64, Error> {
+        if !self.is_csr_accessible(csr::SEED) {
+            return Err(Error::IllegalOperation);
+        }
+
+        // xorshift64*, the upper bits of the product are the best ones.
+        self.entropy ^= self.entropy >> 12;
+        self.entropy ^= self.entropy << 25;
+        self.entropy ^= self.entropy >> 27;
+        let bits = self.entropy.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 48;
+
+        Ok((0b10 << 30) | bits)
+    }
+
+    // Whether the current privilege level is high enough to access the CSR. On top of
+    // the level encoded in the address, mstatus.TVM keeps supervisor mode away from
+    // satp, and mcounteren and scounteren decide which counters the lower levels see.
+    // The floating point and vector CSRs are only there while their unit is on, jvt
+    // only with Zcmt and seed only with Zkr, mseccfg decides whether the lower levels
+    // can access it. On RV64 the upper halves of the 64 bit CSRs are not there, and
+    // neither are the CSRs of the extensions beyond the base instruction set.
+    fn is_csr_accessible(&self, addr: u16) -> bool {
+        if csr::privilege(addr) > self.privilege as u8 {
+            return false;
+        }
+
+        if self.csrs.is_upper_half(addr) {
+            return false;
+        }
+
+        if matches!(addr, csr::FFLAGS..=csr::FCSR) && !self.is_fp_enabled() {
+            return false;
+        }
+
+        if matches!(addr, csr::VSTART..=csr::VCSR | csr::VL..=csr::VLENB)
+            && !(self.config.extensions.zve32x && self.is_vector_enabled())
+        {
+            return false;
+        }
+
+        if !counters::is_accessible(&self.csrs, addr, self.privilege) {
+            return false;
+        }
+
+        if addr == csr::JVT && !self.config.extensions.zcmt {
+            return false;
+        }
+
+        if addr == csr::SEED {
+            let mseccfg = self.csrs.get(csr::MSECCFG);
+            let allowed = match self.privilege {
+                Privilege::User => mseccfg & csr::MSECCFG_USEED != 0,
+                Privilege::Supervisor => mseccfg & csr::MSECCFG_SSEED != 0,
+                Privilege::Machine => true,
+            };
+
+            if !self.config.extensions.zkr || !allowed {
+                return false;
+            }
+        }
+
+        !(addr == csr::SATP
+            && self.privilege == Privilege::Supervisor
+            && self.csrs.get(csr::MSTATUS) & csr::MSTATUS_TVM != 0)
+    }
+
+    // Get the CSR file without any access checks, for use by the machine itself.
+    pub fn csrs(&self) -> &Csrs {
+        &self.csrs
+    }
+
+    // Get the mutable CSR file without any access checks, for use by the machine itself.
+    pub fn csrs_mut(&mut self) -> &mut Csrs {
+        &mut self.csrs
+    }
 }
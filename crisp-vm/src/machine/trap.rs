@@ -0,0 +1,353 @@
+use thiserror::Error;
+
+use crate::machine::{
+    csr,
+    state::{self, Privilege, State},
+};
+
+// The synchronous exceptions, each of them carries the value written to the trap
+// value register (mtval) when it is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Exception {
+    #[error("instruction address misaligned at {0:x}")]
+    InstructionAddressMisaligned(u64),
+
+    #[error("instruction access fault at {0:x}")]
+    InstructionAccessFault(u64),
+
+    #[error("illegal instruction {0:x}")]
+    IllegalInstruction(u32),
+
+    #[error("breakpoint at {0:x}")]
+    Breakpoint(u64),
+
+    #[error("load address misaligned at {0:x}")]
+    LoadAddressMisaligned(u64),
+
+    #[error("load access fault at {0:x}")]
+    LoadAccessFault(u64),
+
+    #[error("store address misaligned at {0:x}")]
+    StoreAddressMisaligned(u64),
+
+    #[error("store access fault at {0:x}")]
+    StoreAccessFault(u64),
+
+    #[error("environment call from user mode")]
+    UserEnvironmentCall,
+
+    #[error("environment call from supervisor mode")]
+    SupervisorEnvironmentCall,
+
+    #[error("environment call from machine mode")]
+    MachineEnvironmentCall,
+
+    #[error("instruction page fault at {0:x}")]
+    InstructionPageFault(u64),
+
+    #[error("load page fault at {0:x}")]
+    LoadPageFault(u64),
+
+    #[error("store page fault at {0:x}")]
+    StorePageFault(u64),
+}
+
+impl Exception {
+    // The exception code written to mcause.
+    pub fn code(&self) -> u32 {
+        match self {
+            Exception::InstructionAddressMisaligned(_) => 0,
+            Exception::InstructionAccessFault(_) => 1,
+            Exception::IllegalInstruction(_) => 2,
+            Exception::Breakpoint(_) => 3,
+            Exception::LoadAddressMisaligned(_) => 4,
+            Exception::LoadAccessFault(_) => 5,
+            Exception::StoreAddressMisaligned(_) => 6,
+            Exception::StoreAccessFault(_) => 7,
+            Exception::UserEnvironmentCall => 8,
+            Exception::SupervisorEnvironmentCall => 9,
+            Exception::MachineEnvironmentCall => 11,
+            Exception::InstructionPageFault(_) => 12,
+            Exception::LoadPageFault(_) => 13,
+            Exception::StorePageFault(_) => 15,
+        }
+    }
+
+    // The value written to mtval.
+    pub fn tval(&self) -> u64 {
+        match *self {
+            Exception::IllegalInstruction(inst) => inst as u64,
+            Exception::InstructionAddressMisaligned(val)
+            | Exception::InstructionAccessFault(val)
+            | Exception::Breakpoint(val)
+            | Exception::LoadAddressMisaligned(val)
+            | Exception::LoadAccessFault(val)
+            | Exception::StoreAddressMisaligned(val)
+            | Exception::StoreAccessFault(val)
+            | Exception::InstructionPageFault(val)
+            | Exception::LoadPageFault(val)
+            | Exception::StorePageFault(val) => val,
+            Exception::UserEnvironmentCall
+            | Exception::SupervisorEnvironmentCall
+            | Exception::MachineEnvironmentCall => 0,
+        }
+    }
+
+    // The environment call exception raised by ecall at a privilege level.
+    pub fn environment_call(privilege: Privilege) -> Self {
+        match privilege {
+            Privilege::User => Exception::UserEnvironmentCall,
+            Privilege::Supervisor => Exception::SupervisorEnvironmentCall,
+            Privilege::Machine => Exception::MachineEnvironmentCall,
+        }
+    }
+}
+
+// The interrupts, numbered by their exception codes which are also their bits in mip
+// and mie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    SupervisorSoftware = 1,
+    MachineSoftware = 3,
+    SupervisorTimer = 5,
+    MachineTimer = 7,
+    SupervisorExternal = 9,
+    MachineExternal = 11,
+}
+
+impl Interrupt {
+    // The interrupts from the highest priority to the lowest.
+    const PRIORITY: [Interrupt; 6] = [
+        Interrupt::MachineExternal,
+        Interrupt::MachineSoftware,
+        Interrupt::MachineTimer,
+        Interrupt::SupervisorExternal,
+        Interrupt::SupervisorSoftware,
+        Interrupt::SupervisorTimer,
+    ];
+
+    // The bit of the interrupt in mip and mie.
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    // The value written to mcause, with the interrupt bit (XLEN - 1) set.
+    pub fn cause(self, xlen: u32) -> u64 {
+        (1 << (xlen - 1)) | self as u64
+    }
+}
+
+// Returns the interrupt to take before the next instruction, if any. An interrupt has
+// to be both pending and enabled in mie to be taken. The interrupts that go to
+// machine mode are taken in any lower privilege level, and in machine mode when
+// mstatus.MIE is set. The ones delegated to supervisor mode are taken in user mode,
+// and in supervisor mode when mstatus.SIE is set, but never in machine mode. The
+// interrupts that go to machine mode come first, in the order of their priority.
+pub fn pending_interrupt<const M: usize>(state: &State<M>) -> Option<Interrupt> {
+    let csrs = state.csrs();
+    let pending = csrs.get(csr::MIP) & csrs.get(csr::MIE);
+    if pending == 0 {
+        return None;
+    }
+
+    let privilege = state.get_privilege();
+    let status = csrs.get(csr::MSTATUS);
+    let mideleg = csrs.get(csr::MIDELEG);
+
+    let enabled = |mode: Mode| match privilege.cmp(&mode.privilege) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Equal => status & mode.ie != 0,
+        std::cmp::Ordering::Greater => false,
+    };
+
+    let machine = if enabled(MACHINE) {
+        pending & !mideleg
+    } else {
+        0
+    };
+    let supervisor = if enabled(SUPERVISOR) {
+        pending & mideleg
+    } else {
+        0
+    };
+
+    [machine, supervisor].into_iter().find_map(|pending| {
+        Interrupt::PRIORITY
+            .into_iter()
+            .find(|interrupt| pending & interrupt.bit() != 0)
+    })
+}
+
+// The CSRs and the mstatus fields used by the traps taken into a privilege level.
+struct Mode {
+    privilege: Privilege,
+    epc: u16,
+    cause: u16,
+    tval: u16,
+    tvec: u16,
+    ie: u32,
+    pie: u32,
+    pp: u32,
+}
+
+const MACHINE: Mode = Mode {
+    privilege: Privilege::Machine,
+    epc: csr::MEPC,
+    cause: csr::MCAUSE,
+    tval: csr::MTVAL,
+    tvec: csr::MTVEC,
+    ie: csr::MSTATUS_MIE,
+    pie: csr::MSTATUS_MPIE,
+    pp: csr::MSTATUS_MPP,
+};
+
+const SUPERVISOR: Mode = Mode {
+    privilege: Privilege::Supervisor,
+    epc: csr::SEPC,
+    cause: csr::SCAUSE,
+    tval: csr::STVAL,
+    tvec: csr::STVEC,
+    ie: csr::MSTATUS_SIE,
+    pie: csr::MSTATUS_SPIE,
+    pp: csr::MSTATUS_SPP,
+};
+
+// Takes a trap for the instruction at pc and returns the address of the trap handler
+// to continue from. The cause has its interrupt bit (XLEN - 1) set for interrupts. Traps
+// taken from supervisor or user mode go to supervisor mode when medeleg (or mideleg
+// for the interrupts) delegates them, traps never go to a lower privilege level. When
+// the mode the trap goes to has no trap handler installed (a zero xtvec), nothing is
+// changed and None is returned.
+pub fn enter<const M: usize>(state: &mut State<M>, pc: u64, cause: u64, tval: u64) -> Option<u64> {
+    log::debug!(target: "trap", "enter pc:{:x} cause:{:x} tval:{:x}", pc, cause, tval);
+
+    let privilege = state.get_privilege();
+    let bit = state.xlen() - 1;
+    let interrupt = cause >> bit == 1;
+    let code = cause & !(1 << bit);
+
+    let deleg = state.csrs().get(if interrupt {
+        csr::MIDELEG
+    } else {
+        csr::MEDELEG
+    });
+    let mode = if privilege <= Privilege::Supervisor && (deleg >> code) & 1 == 1 {
+        SUPERVISOR
+    } else {
+        MACHINE
+    };
+
+    let tvec = state.csrs().get_x(mode.tvec);
+    if tvec == 0 {
+        return None;
+    }
+
+    let csrs = state.csrs_mut();
+    csrs.set_x(mode.epc, pc);
+    csrs.set_x(mode.cause, cause);
+    csrs.set_x(mode.tval, tval);
+
+    // Stack the interrupt enable and the privilege level the trap was taken from, the
+    // handler runs with the interrupts disabled.
+    let status = csrs.get(csr::MSTATUS);
+    let pie = if status & mode.ie != 0 { mode.pie } else { 0 };
+    let pp = (privilege as u32) << mode.pp.trailing_zeros();
+    csrs.set(
+        csr::MSTATUS,
+        (status & !(mode.ie | mode.pie | mode.pp)) | pie | pp,
+    );
+
+    // In vectored mode the interrupts jump to base + 4 * cause, while the exceptions
+    // always jump to the base.
+    let base = tvec & !0b11;
+
+    state.set_privilege(mode.privilege);
+
+    if tvec & 0b11 == 1 && interrupt {
+        Some(state.to_xlen(base.wrapping_add(4 * code)))
+    } else {
+        Some(base)
+    }
+}
+
+// Returns from a trap handled in machine mode and returns the address to continue
+// from. It is only legal in machine mode.
+pub fn mret<const M: usize>(state: &mut State<M>) -> Result<u64, state::Error> {
+    if state.get_privilege() < Privilege::Machine {
+        return Err(state::Error::IllegalOperation);
+    }
+
+    Ok(leave(state, MACHINE))
+}
+
+// Returns from a trap handled in supervisor mode and returns the address to continue
+// from. It is only legal in supervisor mode when mstatus.TSR is clear, and in machine
+// mode.
+pub fn sret<const M: usize>(state: &mut State<M>) -> Result<u64, state::Error> {
+    let tsr = state.csrs().get(csr::MSTATUS) & csr::MSTATUS_TSR != 0;
+    match state.get_privilege() {
+        Privilege::User => return Err(state::Error::IllegalOperation),
+        Privilege::Supervisor if tsr => return Err(state::Error::IllegalOperation),
+        _ => {}
+    }
+
+    Ok(leave(state, SUPERVISOR))
+}
+
+// Parks the hart until an interrupt becomes pending. It is illegal in user mode, and
+// in supervisor mode when mstatus.TW is set, as the wait could go on forever.
+pub fn wait<const M: usize>(state: &mut State<M>) -> Result<(), state::Error> {
+    let tw = state.csrs().get(csr::MSTATUS) & csr::MSTATUS_TW != 0;
+    match state.get_privilege() {
+        Privilege::User => return Err(state::Error::IllegalOperation),
+        Privilege::Supervisor if tw => return Err(state::Error::IllegalOperation),
+        _ => {}
+    }
+
+    state.set_waiting(true);
+    Ok(())
+}
+
+// Stalls the hart for a wrs.nto while a reservation is held, until a pending
+// interrupt wakes it up like it does after a WFI. There is no other hart to take the
+// reservation away, so, below machine mode with mstatus.TW set the stall times out
+// with an illegal instruction exception right away.
+pub fn wait_on_reservation<const M: usize>(state: &mut State<M>) -> Result<(), state::Error> {
+    if !state.is_reserved() {
+        return Ok(());
+    }
+
+    let tw = state.csrs().get(csr::MSTATUS) & csr::MSTATUS_TW != 0;
+    if tw && state.get_privilege() != Privilege::Machine {
+        return Err(state::Error::IllegalOperation);
+    }
+
+    state.set_waiting(true);
+    Ok(())
+}
+
+// Unstacks the interrupt enable and the privilege level of a mode, leaving user mode
+// as the previous privilege level, and returns the address in its xepc. Returning to
+// a privilege level below machine mode clears mstatus.MPRV as well.
+fn leave<const M: usize>(state: &mut State<M>, mode: Mode) -> u64 {
+    let csrs = state.csrs_mut();
+
+    let status = csrs.get(csr::MSTATUS);
+    let ie = if status & mode.pie != 0 { mode.ie } else { 0 };
+    let privilege = Privilege::from_bits((status & mode.pp) >> mode.pp.trailing_zeros());
+    let mprv = if privilege == Privilege::Machine {
+        status & csr::MSTATUS_MPRV
+    } else {
+        0
+    };
+    csrs.set(
+        csr::MSTATUS,
+        (status & !(mode.ie | mode.pp | csr::MSTATUS_MPRV)) | ie | mode.pie | mprv,
+    );
+
+    let epc = csrs.get_x(mode.epc);
+    state.set_privilege(privilege);
+
+    log::debug!(target: "trap", "return pc:{:x} privilege:{:?}", epc, privilege);
+    epc
+}
@@ -0,0 +1,131 @@
+// The vector unit of the V extension as the Zve32x profile implements it, the vector
+// registers along with the settings in vtype that decide how they are split into
+// elements.
+
+// The widest element the vector instructions work on, in bits.
+pub const ELEN: u32 = 32;
+
+// The setting of vtype that marks it as illegal, along with the limits of VLEN.
+pub const VTYPE_VILL: u32 = 1 << 31;
+pub const MIN_VLEN: u32 = ELEN;
+pub const MAX_VLEN: u32 = 1 << 16;
+
+// A legal setting of vtype, the element width (SEW) in bits and the log2 of the
+// number of registers in a group (LMUL), along with the tail and mask agnostic
+// policies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vtype {
+    pub sew: u32,
+    pub lmul: i32,
+    pub ta: bool,
+    pub ma: bool,
+}
+
+impl Vtype {
+    // Parses the value of vtype, None if the setting is not supported and vill has to
+    // be set instead. A fractional LMUL needs SEW to fit in that fraction of ELEN.
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        if bits >> 8 != 0 {
+            return None;
+        }
+
+        let sew = 8 << ((bits >> 3) & 0b111);
+        let lmul = match bits & 0b111 {
+            0b100 => return None,
+            lmul @ 0..=0b011 => lmul as i32,
+            lmul => lmul as i32 - 8,
+        };
+
+        if sew > ELEN || (lmul < 0 && sew > ELEN >> -lmul) {
+            return None;
+        }
+
+        Some(Vtype {
+            sew,
+            lmul,
+            ta: bits & (1 << 6) != 0,
+            ma: bits & (1 << 7) != 0,
+        })
+    }
+
+    // The number of elements in a register group, VLMAX.
+    pub fn vlmax(&self, vlen: u32) -> u32 {
+        scale(vlen / self.sew, self.lmul)
+    }
+}
+
+// Multiplies the value by 2 to the power of the log2.
+pub fn scale(val: u32, log2: i32) -> u32 {
+    if log2 < 0 { val >> -log2 } else { val << log2 }
+}
+
+// The 32 vector registers of VLEN bits. The registers of a group are next to each
+// other, so, the elements of a group are indexed from its first register on. The
+// elements are little endian like the memory.
+#[derive(Debug, Default)]
+pub struct VRegisters {
+    bytes: Vec<u8>,
+    vlenb: u32,
+}
+
+impl VRegisters {
+    pub fn new(vlen: u32) -> Self {
+        VRegisters {
+            bytes: vec![0; 32 * vlen as usize / 8],
+            vlenb: vlen / 8,
+        }
+    }
+
+    // The size of a register in bytes.
+    pub fn vlenb(&self) -> u32 {
+        self.vlenb
+    }
+
+    // Get the element of the width in bits at the index of the group starting at the
+    // register, zero extended.
+    pub fn get(&self, reg: u8, index: u32, eew: u32) -> u64 {
+        let start = self.offset(reg, index, eew);
+        let mut bytes = [0; 8];
+        bytes[..eew as usize / 8].copy_from_slice(&self.bytes[start..start + eew as usize / 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    // Set the element of the width in bits at the index of the group starting at the
+    // register, the value is truncated to the width.
+    pub fn set(&mut self, reg: u8, index: u32, eew: u32, val: u64) {
+        let start = self.offset(reg, index, eew);
+        self.bytes[start..start + eew as usize / 8]
+            .copy_from_slice(&val.to_le_bytes()[..eew as usize / 8]);
+    }
+
+    // Get the bit of a mask register for the element at the index.
+    pub fn mask(&self, reg: u8, index: u32) -> bool {
+        self.get(reg, index / 8, 8) & (1 << (index % 8)) != 0
+    }
+
+    // Set the bit of a mask register for the element at the index.
+    pub fn set_mask(&mut self, reg: u8, index: u32, val: bool) {
+        let byte = self.get(reg, index / 8, 8) & !(1 << (index % 8));
+        self.set(reg, index / 8, 8, byte | ((val as u64) << (index % 8)));
+    }
+
+    // Set all the bits of the bytes in the range of the group starting at the
+    // register, which is what the agnostic policies leave in the elements.
+    pub fn fill(&mut self, reg: u8, bytes: std::ops::Range<u32>) {
+        let start = reg as usize * self.vlenb as usize;
+        self.bytes[start + bytes.start as usize..start + bytes.end as usize].fill(0xff);
+    }
+
+    // Copy the bytes in the range of the group starting at the register from to the
+    // same bytes of the group starting at the register to.
+    pub fn copy(&mut self, to: u8, from: u8, bytes: std::ops::Range<u32>) {
+        let start = from as usize * self.vlenb as usize;
+        let src = start + bytes.start as usize..start + bytes.end as usize;
+        let dest = to as usize * self.vlenb as usize + bytes.start as usize;
+        self.bytes.copy_within(src, dest);
+    }
+
+    fn offset(&self, reg: u8, index: u32, eew: u32) -> usize {
+        reg as usize * self.vlenb as usize + (index * eew / 8) as usize
+    }
+}
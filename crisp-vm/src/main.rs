@@ -1,13 +1,13 @@
-use crate::machine::state::State;
+use crate::machine::{config::Config, state::State};
 
 mod machine;
 
-fn main() {
+fn main() -> Result<(), machine::Error> {
     env_logger::init();
 
-    let state = State::<1_048_576>::default();
+    let state = State::<1_048_576>::default().with_config(Config::default());
     let mut machine = machine::Machine::new(state);
-    machine.run().expect("could not run machine");
+    machine.run()
 }
 
 #[cfg(test)]
@@ -15,24 +15,543 @@ mod tests {
     use rstest::rstest;
     use std::path::PathBuf;
 
-    use crate::machine::{Error, Machine, instructions, state::State};
+    use crate::machine::{
+        Error, Machine,
+        config::{Config, Extensions, Misaligned},
+        csr,
+        instructions::InstError,
+        state::{Privilege, State},
+        trap::{Exception, Interrupt},
+    };
 
-    fn run_riscv_test(bytes: &[u8]) {
-        let state = State::<16_000>::from(bytes);
+    // The number of instructions a test gets to run before it is considered stuck.
+    const MAX_STEPS: usize = 1_000_000;
+
+    // Finds the address of the tohost symbol in the test ELF, a 32 or a 64 bit one. The
+    // test is linked at its entry point but loaded at 0, so, the address is made
+    // relative to it. The fields that are 8 bytes wide in a 64 bit ELF never go over
+    // 32 bits in the tests, only their lower half is read.
+    fn find_tohost(elf: &[u8]) -> u32 {
+        let u16_at = |at: usize| u16::from_le_bytes([elf[at], elf[at + 1]]) as usize;
+        let u32_at = |at: usize| u32::from_le_bytes(elf[at..at + 4].try_into().unwrap());
+
+        let (shoff, shentsize, shnum, offset_at, size_at, link_at, value_at, symsize) = match elf[4]
+        {
+            2 => (0x28, 0x3a, 0x3c, 24, 32, 40, 8, 24),
+            _ => (0x20, 0x2e, 0x30, 16, 20, 24, 4, 16),
+        };
+
+        let entry = u32_at(0x18);
+        let sections = (0..u16_at(shnum)).map(|i| u32_at(shoff) as usize + i * u16_at(shentsize));
+
+        for section in sections.clone().filter(|&section| u32_at(section + 4) == 2) {
+            let strtab = sections
+                .clone()
+                .nth(u32_at(section + link_at) as usize)
+                .unwrap();
+            let names = u32_at(strtab + offset_at) as usize;
+
+            let offset = u32_at(section + offset_at) as usize;
+            let size = u32_at(section + size_at) as usize;
+            for symbol in (offset..offset + size).step_by(symsize) {
+                let name = names + u32_at(symbol) as usize;
+                if elf[name..].starts_with(b"tohost\0") {
+                    return u32_at(symbol + value_at) - entry;
+                }
+            }
+        }
+
+        panic!("could not find tohost");
+    }
+
+    // Runs the test until it reports its result by writing to tohost, 1 is a pass and
+    // anything else is the number of the failing test shifted left by one and or'd
+    // with one.
+    fn run_riscv_test(bytes: &[u8], tohost: u32, config: Config) {
+        let state = State::<65_536>::from(bytes).with_config(config);
         let mut machine = Machine::new(state);
 
+        for _ in 0..MAX_STEPS {
+            machine.step().expect("could not step");
+
+            let result = machine
+                .state
+                .get_phys_mem_u32(tohost as u64)
+                .expect("could not tohost");
+            if result != 0 {
+                assert_eq!(result, 1, "failed test {}", result >> 1);
+                return;
+            }
+        }
+
+        panic!("did not finish in {} steps", MAX_STEPS);
+    }
+
+    // Every test runs with both ways of handling the misaligned loads and stores, the
+    // tests that make them emulate them in their trap handlers. The Zcmp and Zcmt tests
+    // are the only ones with those extensions on, as they take the encodings of the
+    // compressed double precision loads and stores. The rv64 tests run on a 64 bit
+    // machine.
+    #[rstest]
+    fn test_riscv_tests_test(
+        #[files("tests/**/*.bin")] path: PathBuf,
+        #[values(Misaligned::Trap, Misaligned::Emulate)] misaligned: Misaligned,
+    ) {
+        let bin = std::fs::read(&path).expect("could not read bin");
+        let elf = std::fs::read(path.with_extension("")).expect("could not read elf");
+        let name = path.file_name().unwrap().to_string_lossy();
+        let config = Config {
+            xlen: if name.starts_with("rv64") { 64 } else { 32 },
+            misaligned,
+            extensions: Extensions {
+                zcmp: name.contains("zcmp"),
+                zcmt: name.contains("zcmt"),
+                ..Extensions::default()
+            },
+            ..Config::default()
+        };
+        run_riscv_test(bin.as_slice(), find_tohost(elf.as_slice()), config);
+    }
+
+    // A machine with the bytes at the start of its memory, set up with the config.
+    fn machine(bytes: &[u8], config: Config) -> Machine<64> {
+        Machine::new(State::<64>::from(bytes).with_config(config))
+    }
+
+    // Steps the machine and checks that it takes an illegal instruction exception on
+    // the instruction.
+    fn assert_illegal(mut machine: Machine<64>, inst: u32) {
         assert!(matches!(
-            machine.run(),
-            Err(Error::Execute(instructions::InstError::Suspend)),
+            machine.step(),
+            Err(Error::Execute(InstError::Exception(Exception::IllegalInstruction(raw)))) if raw == inst
         ));
-        assert_eq!(machine.state.get_r(3).expect("could not gp"), 1);
-        assert_eq!(machine.state.get_r(10).expect("could not a0"), 0);
-        assert_eq!(machine.state.get_r(17).expect("could not a7"), 93);
     }
 
+    // An image that does not fit in the memory is refused rather than cut short.
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn test_image_too_large() {
+        let _ = State::<4>::from(&[0; 8][..]);
+    }
+
+    // With a debugger attached, an ebreak stops the machine at itself instead of
+    // trapping, even with a trap handler installed.
+    #[rstest]
+    fn test_debugger_breakpoint() {
+        // addi a0, zero, 1; ebreak
+        let bytes = [0x13, 0x05, 0x10, 0x00, 0x73, 0x00, 0x10, 0x00];
+        let config = Config {
+            debugger: true,
+            ..Config::default()
+        };
+        let mut machine = machine(&bytes, config);
+        machine.state.csrs_mut().set(csr::MTVEC, 0x40);
+
+        machine.step().expect("could not step");
+        assert!(matches!(machine.step(), Err(Error::Breakpoint(4))));
+        assert_eq!(machine.state.get_pc(), 4);
+    }
+
+    // A trap delegated to supervisor mode only needs a supervisor trap handler, an
+    // ecall from user mode goes to stvec even with no machine trap handler installed.
+    // PMP gives user mode access to the whole memory (TOR up to the top, RWX).
+    #[test]
+    fn test_delegated_trap() {
+        // ecall
+        let bytes = 0x0000_0073_u32.to_le_bytes();
+        let mut machine = Machine::new(State::<512>::from(&bytes[..]));
+        machine.state.csrs_mut().set(csr::PMPADDR0, u32::MAX);
+        machine.state.csrs_mut().set(csr::PMPCFG0, 0b0000_1111);
+        machine.state.csrs_mut().set(csr::MEDELEG, 1 << 8);
+        machine.state.csrs_mut().set(csr::STVEC, 0x100);
+        machine.state.set_privilege(Privilege::User);
+
+        machine.step().expect("could not step");
+        assert_eq!(machine.state.get_pc(), 0x100);
+        assert_eq!(machine.state.get_privilege(), Privilege::Supervisor);
+        assert_eq!(machine.state.csrs().get(csr::SCAUSE), 8);
+        assert_eq!(machine.state.csrs().get(csr::MCAUSE), 0);
+    }
+
+    // An interrupt is only taken when the mode it goes to has a trap handler, like an
+    // exception, otherwise the machine stops on it.
+    #[rstest]
+    #[case::handler(0x40)]
+    #[case::no_handler(0)]
+    fn test_interrupt_handler(#[case] mtvec: u32) {
+        // nop
+        let bytes = 0x0000_0013_u32.to_le_bytes();
+        let mut machine = Machine::new(State::<128>::from(&bytes[..]));
+        let csrs = machine.state.csrs_mut();
+        csrs.set(csr::MTVEC, mtvec);
+        csrs.set(csr::MSTATUS, csr::MSTATUS_MIE);
+        csrs.set(csr::MIE, csr::MIP_SSIP);
+        csrs.set(csr::MIP, csr::MIP_SSIP);
+
+        match mtvec {
+            0 => {
+                assert!(matches!(
+                    machine.step(),
+                    Err(Error::Interrupt(Interrupt::SupervisorSoftware))
+                ));
+                assert_eq!(machine.state.get_pc(), 0);
+            }
+            _ => {
+                machine.step().expect("could not step");
+                assert_eq!(machine.state.get_pc(), 0x40);
+            }
+        }
+    }
+
+    // The optional extensions are on by default and their instructions are illegal
+    // once they are turned off. The floating point unit is turned on for the ones
+    // that need it.
+    #[rstest]
+    #[case::zba(0x20c5_a533, Extensions { zba: false, ..Extensions::default() })]
+    #[case::zbb(0x6005_9513, Extensions { zbb: false, ..Extensions::default() })]
+    #[case::zbc(0x0ac5_a533, Extensions { zbc: false, ..Extensions::default() })]
+    #[case::zbs(0x28c5_9533, Extensions { zbs: false, ..Extensions::default() })]
+    #[case::zbkb(0x08c5_c533, Extensions { zbkb: false, ..Extensions::default() })]
+    #[case::zbkc(0x0ac5_9533, Extensions { zbc: false, zbkc: false, ..Extensions::default() })]
+    #[case::zbkx(0x28c5_c533, Extensions { zbkx: false, ..Extensions::default() })]
+    #[case::zknd(0x2ac5_8533, Extensions { zknd: false, ..Extensions::default() })]
+    #[case::zkne(0x22c5_8533, Extensions { zkne: false, ..Extensions::default() })]
+    #[case::zknh(0x1005_9513, Extensions { zknh: false, ..Extensions::default() })]
+    #[case::zkr(0x0150_1573, Extensions { zkr: false, ..Extensions::default() })]
+    #[case::zicond(0x0ec5_d533, Extensions { zicond: false, ..Extensions::default() })]
+    #[case::zicbom(0x0010_200f, Extensions { zicbom: false, ..Extensions::default() })]
+    #[case::zicboz(0x0040_200f, Extensions { zicboz: false, ..Extensions::default() })]
+    #[case::zawrs(0x01d0_0073, Extensions { zawrs: false, ..Extensions::default() })]
+    #[case::zacas(0x28c5_a52f, Extensions { zacas: false, ..Extensions::default() })]
+    #[case::zabha(0x00c5_852f, Extensions { zabha: false, ..Extensions::default() })]
+    #[case::zacas_b(0x28c5_852f, Extensions { zacas: false, ..Extensions::default() })]
+    #[case::zabha_cas(0x28c5_852f, Extensions { zabha: false, ..Extensions::default() })]
+    #[case::zfh(0x04c5_f553, Extensions { zfh: false, ..Extensions::default() })]
+    #[case::zfhmin(0x0005_9507, Extensions { zfh: false, zfhmin: false, ..Extensions::default() })]
+    #[case::zfa(0xf018_0553, Extensions { zfa: false, ..Extensions::default() })]
+    #[case::zfa_h(0xf418_0553, Extensions { zfh: false, ..Extensions::default() })]
+    fn test_disabled_extension(#[case] inst: u32, #[case] extensions: Extensions) {
+        let bytes = inst.to_le_bytes();
+        let config = Config {
+            extensions,
+            ..Config::default()
+        };
+
+        let mut disabled = machine(&bytes, config);
+        disabled.state.csrs_mut().set(csr::MSTATUS, csr::MSTATUS_FS);
+        assert_illegal(disabled, inst);
+
+        let mut machine = machine(&bytes, Config::default());
+        machine.state.csrs_mut().set(csr::MSTATUS, csr::MSTATUS_FS);
+        machine.step().expect("could not step");
+        assert_eq!(machine.state.get_pc(), 4);
+    }
+
+    // The vector unit is off at reset, its instructions need mstatus.VS on and are
+    // illegal with Zve32x turned off even then.
+    #[rstest]
+    #[case::on(true)]
+    #[case::off(false)]
+    fn test_disabled_vector_extension(#[case] zve32x: bool) {
+        // vsetvli a0, zero, e8, m1, ta, ma
+        let inst = 0x0c00_7557_u32;
+        let bytes = inst.to_le_bytes();
+        let config = Config {
+            extensions: Extensions {
+                zve32x,
+                ..Extensions::default()
+            },
+            ..Config::default()
+        };
+
+        assert_illegal(machine(&bytes, config), inst);
+
+        let mut machine = machine(&bytes, config);
+        let status = machine.state.csrs().get(csr::MSTATUS);
+        machine
+            .state
+            .csrs_mut()
+            .set(csr::MSTATUS, status | csr::MSTATUS_VS);
+        match zve32x {
+            true => {
+                machine.step().expect("could not step");
+                assert_eq!(machine.state.get_r(10).unwrap(), 16);
+            }
+            false => assert_illegal(machine, inst),
+        }
+    }
+
+    // VLEN is a parameter of the machine, vlenb and VLMAX follow it.
+    #[rstest]
+    #[case(32)]
+    #[case(128)]
+    #[case(1024)]
+    #[case(65_536)]
+    fn test_vlen(#[case] vlen: u32) {
+        // vsetvli a0, zero, e8, m8, ta, ma
+        let bytes = 0x0c30_7557_u32.to_le_bytes();
+        let config = Config {
+            vlen,
+            ..Config::default()
+        };
+
+        let mut machine = machine(&bytes, config);
+        let status = machine.state.csrs().get(csr::MSTATUS);
+        machine
+            .state
+            .csrs_mut()
+            .set(csr::MSTATUS, status | csr::MSTATUS_VS);
+        machine.step().expect("could not step");
+
+        assert_eq!(machine.state.get_r(10).unwrap(), vlen);
+        assert_eq!(machine.state.csrs().get(csr::VLENB), vlen / 8);
+    }
+
+    // Zcmp and Zcmt take the encodings of the compressed double precision loads and
+    // stores, have reserved encodings of their own and are illegal, jvt included,
+    // unless they are turned on.
     #[rstest]
-    fn test_riscv_tests_test(#[files("tests/**/*.bin")] path: PathBuf) {
-        let bin = std::fs::read(path).expect("could not read bin");
-        run_riscv_test(bin.as_slice());
+    #[case::fld(0x2000, true, false)]
+    #[case::fsd(0xa000, false, true)]
+    #[case::fldsp(0x2002, true, true)]
+    #[case::fsdsp(0xa00a, true, false)]
+    #[case::rlist(0xb832, true, false)]
+    #[case::mvsa01(0xaca6, true, false)]
+    #[case::push(0xb842, false, true)]
+    #[case::jalt(0xa082, true, false)]
+    #[case::jvt(0x0170_2573, true, false)]
+    fn test_zcm_illegal(#[case] inst: u32, #[case] zcmp: bool, #[case] zcmt: bool) {
+        let bytes = inst.to_le_bytes();
+        let config = Config {
+            extensions: Extensions {
+                zcmp,
+                zcmt,
+                ..Extensions::default()
+            },
+            ..Config::default()
+        };
+
+        assert_illegal(machine(&bytes, config), inst);
+    }
+
+    // pause hints the loop running the machine to yield to the host, a plain fence
+    // does not.
+    #[rstest]
+    #[case::pause(0x0100_000f, true)]
+    #[case::fence(0x0ff0_000f, false)]
+    fn test_pause_yield(#[case] inst: u32, #[case] yielding: bool) {
+        let bytes = inst.to_le_bytes();
+
+        let mut machine = machine(&bytes, Config::default());
+        machine.step().expect("could not step");
+        assert_eq!(machine.state.take_yield(), yielding);
+        assert!(!machine.state.take_yield());
+    }
+
+    // The reserved fields of FENCE and FENCE.I have to be 0, and FENCE.TSO is the only
+    // FENCE with a fm.
+    #[rstest]
+    #[case::fence(0x0ff0_000f, true)]
+    #[case::fence_tso(0x8330_000f, true)]
+    #[case::fence_i(0x0000_100f, true)]
+    #[case::fence_rd(0x0ff0_008f, false)]
+    #[case::fence_rs1(0x0ff0_800f, false)]
+    #[case::fence_fm(0x1ff0_000f, false)]
+    #[case::fence_tso_rw(0x8ff0_000f, false)]
+    #[case::fence_i_imm(0x0010_100f, false)]
+    #[case::fence_i_rs1(0x0000_900f, false)]
+    fn test_fence_reserved(#[case] inst: u32, #[case] legal: bool) {
+        let bytes = inst.to_le_bytes();
+
+        let mut machine = machine(&bytes, Config::default());
+        match legal {
+            true => {
+                machine.step().expect("could not step");
+                assert_eq!(machine.state.get_pc(), 4);
+            }
+            false => assert_illegal(machine, inst),
+        }
+    }
+
+    // cbo.zero zeroes a whole cache block of the size set in the machine config.
+    #[rstest]
+    #[case(4)]
+    #[case(16)]
+    #[case(64)]
+    fn test_cache_block(#[case] cache_block: u32) {
+        // cbo.zero (zero)
+        let mut bytes = [0xff; 128];
+        bytes[..4].copy_from_slice(&0x0040_200f_u32.to_le_bytes());
+        let config = Config {
+            cache_block,
+            ..Config::default()
+        };
+
+        let mut machine = Machine::new(State::<128>::from(&bytes[..]).with_config(config));
+        machine.step().expect("could not step");
+
+        for addr in (0..128).step_by(4) {
+            let expected = if addr < cache_block { 0 } else { u32::MAX };
+            assert_eq!(machine.state.get_phys_mem_u32(addr as u64), Some(expected));
+        }
+    }
+
+    // The RV64 only instructions and shift amounts of 32 or over are illegal on RV32.
+    // RV64 has neither the upper halves of the CSRs nor the extensions it turns off.
+    #[rstest]
+    #[case::addw(0x00b5_053b, 32)]
+    #[case::addiw(0x0015_051b, 32)]
+    #[case::ld(0x0005_b503, 32)]
+    #[case::lwu(0x0005_e503, 32)]
+    #[case::sd(0x00a5_b023, 32)]
+    #[case::slli(0x0205_1513, 32)]
+    #[case::srai(0x4205_5513, 32)]
+    #[case::mulw(0x02b5_053b, 32)]
+    #[case::lr_d(0x1005_b52f, 32)]
+    #[case::c_addw(0x9d2d, 32)]
+    #[case::sh1add(0x20c5_a533, 64)]
+    #[case::mstatush(0x3100_2573, 64)]
+    #[case::c_addiw_x0(0x2001, 64)]
+    fn test_xlen_illegal(#[case] inst: u32, #[case] xlen: u32) {
+        let bytes = inst.to_le_bytes();
+        let config = Config {
+            xlen,
+            ..Config::default()
+        };
+
+        assert_illegal(machine(&bytes, config), inst);
+    }
+
+    // misa.MXL follows XLEN, and the registers keep the results of the 32 bit
+    // operations sign extended.
+    #[rstest]
+    #[case(32, 1)]
+    #[case(64, 2)]
+    fn test_xlen(#[case] xlen: u32, #[case] mxl: u64) {
+        // csrr a0, misa; addi a1, zero, -1
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&0x3010_2573_u32.to_le_bytes());
+        bytes[4..].copy_from_slice(&0xfff0_0593_u32.to_le_bytes());
+        let config = Config {
+            xlen,
+            ..Config::default()
+        };
+
+        let mut machine = machine(&bytes, config);
+        machine.step().expect("could not step");
+        machine.step().expect("could not step");
+
+        assert_eq!(machine.state.get_x(10).unwrap() >> (xlen - 2) & 0b11, mxl);
+        assert_eq!(machine.state.get_x(11).unwrap(), u64::MAX);
+        assert_eq!(machine.state.get_r(11).unwrap(), u32::MAX);
+    }
+
+    // SD is the top bit of mstatus and sstatus, bit 31 of mstatus is reserved on RV64.
+    #[rstest]
+    #[case(32, csr::MSTATUS_FS as u64 | 1 << 31, 1 << 31)]
+    #[case(64, 1 << 31, 0)]
+    fn test_mstatus_sd(#[case] xlen: u32, #[case] val: u64, #[case] sd: u64) {
+        let config = Config {
+            xlen,
+            ..Config::default()
+        };
+        let mut machine = machine(&[], config);
+        machine.state.csrs_mut().set_x(csr::MSTATUS, val);
+
+        let csrs = machine.state.csrs();
+        assert_eq!(csrs.get_x(csr::MSTATUS) & (1 << 63 | 1 << 31), sd);
+        assert_eq!(csrs.get_x(csr::SSTATUS) & (1 << 63 | 1 << 31), sd);
+    }
+
+    // A 64 bit machine has no virtual memory, so satp ignores the Sv39 mode, as well as
+    // the bit that is the mode on RV32, and the loads that follow are not translated.
+    #[test]
+    fn test_xlen_satp() {
+        // csrw satp, a0; csrr a1, satp; lw a2, 0(a3)
+        let mut bytes = [0; 16];
+        bytes[..4].copy_from_slice(&0x1805_1073_u32.to_le_bytes());
+        bytes[4..8].copy_from_slice(&0x1800_25f3_u32.to_le_bytes());
+        bytes[8..12].copy_from_slice(&0x0006_a603_u32.to_le_bytes());
+        bytes[12..].copy_from_slice(&0x1234_5678_u32.to_le_bytes());
+        let config = Config {
+            xlen: 64,
+            ..Config::default()
+        };
+
+        let mut machine = machine(&bytes, config);
+        machine.state.csrs_mut().set(csr::PMPADDR0, u32::MAX);
+        machine.state.csrs_mut().set(csr::PMPCFG0, 0b0000_1111);
+        machine.state.set_privilege(Privilege::Supervisor);
+        machine.state.set_x(10, 8 << 60 | 1 << 31).unwrap();
+        machine.state.set_x(13, 12).unwrap();
+        for _ in 0..3 {
+            machine.step().expect("could not step");
+        }
+
+        assert_eq!(machine.state.get_x(11).unwrap(), 0);
+        assert_eq!(machine.state.get_x(12).unwrap(), 0x1234_5678);
+    }
+
+    // A 64 bit machine turns off the extensions it does not have, so that its config
+    // and misa report what it runs.
+    #[test]
+    fn test_xlen_extensions() {
+        let config = Config {
+            xlen: 64,
+            ..Config::default()
+        };
+
+        let state = machine(&[], config).state;
+        let extensions = state.config().extensions;
+        assert_eq!(extensions, Extensions::default().for_xlen(64));
+        assert!(!extensions.zba && !extensions.zve32x && !extensions.zfh);
+        assert!(extensions.zicond && extensions.zicboz && extensions.zabha);
+
+        let misa = state.csrs().get(csr::MISA);
+        let has = |ext: u8| misa & (1 << (ext - b'A')) != 0;
+        assert!(b"IMACSU".iter().all(|&ext| has(ext)));
+        assert!(!b"FDV".iter().any(|&ext| has(ext)));
+    }
+
+    // RV32E only has x0 to x15, any instruction that refers to the rest of them is
+    // illegal, the Zcmp register lists that go past s1 included.
+    #[rstest]
+    #[case::rs1(0x00a8_0533)]
+    #[case::rd(0x00a5_0833)]
+    #[case::load(0x0009_2503)]
+    #[case::c_li(0x4805)]
+    #[case::push(0xb872)]
+    fn test_rve_illegal(#[case] inst: u32) {
+        let bytes = inst.to_le_bytes();
+        let config = Config {
+            rve: true,
+            extensions: Extensions {
+                zcmp: true,
+                ..Extensions::default()
+            },
+            ..Config::default()
+        };
+
+        assert_illegal(machine(&bytes, config), inst);
+    }
+
+    // The register file of RV32E shrinks to x0 to x15 and misa reports E in place of I.
+    #[rstest]
+    fn test_rve() {
+        // csrr a5, misa
+        let bytes = 0x3010_27f3_u32.to_le_bytes();
+        let config = Config {
+            rve: true,
+            ..Config::default()
+        };
+
+        let mut machine = machine(&bytes, config);
+        machine.step().expect("could not step");
+
+        let misa = machine.state.get_r(15).unwrap();
+        assert_eq!(misa & 1 << (b'E' - b'A'), 1 << (b'E' - b'A'));
+        assert_eq!(misa & 1 << (b'I' - b'A'), 0);
+        assert!(machine.state.get_r(16).is_err());
+        assert!(machine.state.set_r(16, 1).is_err());
     }
 }
@@ -0,0 +1,47 @@
+#!/usr/bin/env bash
+# Builds the tests whose sources live in this directory. Every test
+# <name>/<name>.S is preprocessed with the environment in env/, assembled with
+# llvm-mc, linked with (rust-)lld and then turned into the flat <name>.bin
+# loaded by the VM along with a <name>.dump for debugging.
+#
+#   ./build.sh [name...]
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+CPP=${CPP:-cpp}
+MC=${MC:-llvm-mc}
+OBJCOPY=${OBJCOPY:-llvm-objcopy}
+OBJDUMP=${OBJDUMP:-llvm-objdump}
+LD=${LD:-$(find "$(rustc --print sysroot)" -name rust-lld | head -n 1)}
+
+build() {
+  local name=$1
+  local src="$name/$name.S"
+  local arch=${name%%-*}
+  local triple=riscv32
+  local defs=()
+  local attrs
+
+  case "$arch" in
+    rv64*) triple=riscv64; defs+=(-D__RISCV64) ;;
+  esac
+  attrs=$(sed -n 's/^# *mattr: *//p' "$src")
+
+  "$CPP" -x assembler-with-cpp -P -Ienv "${defs[@]}" "$src" \
+    | "$MC" -triple="$triple" -mattr="${attrs:-+m}" \
+      -filetype=obj -o "$name/$name.o" -
+  "$LD" -flavor gnu -T env/link.ld --no-relax -o "$name/$name" "$name/$name.o"
+  "$OBJCOPY" -O binary "$name/$name" "$name/$name.bin"
+  "$OBJDUMP" -d --section=.text.init --section=.data \
+    --mattr="${attrs:-+m}" "$name/$name" > "$name/$name.dump"
+  rm "$name/$name.o"
+}
+
+if [ $# -eq 0 ]; then
+  set -- $(ls */*.S | xargs -n 1 dirname)
+fi
+
+for name in "$@"; do
+  build "${name%/}"
+done
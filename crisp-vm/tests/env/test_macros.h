@@ -0,0 +1,556 @@
+// The scalar test macros from riscv-tests/isa/macros/scalar/test_macros.h.
+
+#ifndef __TEST_MACROS_SCALAR_H
+#define __TEST_MACROS_SCALAR_H
+
+#-----------------------------------------------------------------------
+# Helper macros
+#-----------------------------------------------------------------------
+
+#ifdef __RISCV64
+#define MASK_XLEN(x) ((x) & 0xffffffffffffffff)
+#else
+#define MASK_XLEN(x) ((x) & 0xffffffff)
+#endif
+
+#define SEXT_IMM(x) ((x) | (-(((x) >> 11) & 1) << 11))
+
+#define TEST_CASE( testnum, testreg, correctval, code... ) \
+test_ ## testnum: \
+    li  TESTNUM, testnum; \
+    code; \
+    li  x7, MASK_XLEN(correctval); \
+    bne testreg, x7, fail;
+
+#-----------------------------------------------------------------------
+# Tests for instructions with immediate operand
+#-----------------------------------------------------------------------
+
+#define TEST_IMM_OP( testnum, inst, result, val1, imm ) \
+    TEST_CASE( testnum, x14, result, \
+      li  x13, MASK_XLEN(val1); \
+      inst x14, x13, SEXT_IMM(imm); \
+    )
+
+#define TEST_IMM_SRC1_EQ_DEST( testnum, inst, result, val1, imm ) \
+    TEST_CASE( testnum, x11, result, \
+      li  x11, MASK_XLEN(val1); \
+      inst x11, x11, SEXT_IMM(imm); \
+    )
+
+#define TEST_IMM_ZEROSRC1( testnum, inst, result, imm ) \
+    TEST_CASE( testnum, x1, result, \
+      inst x1, x0, SEXT_IMM(imm); \
+    )
+
+#define TEST_IMM_ZERODEST( testnum, inst, val1, imm ) \
+    TEST_CASE( testnum, x0, 0, \
+      li  x1, MASK_XLEN(val1); \
+      inst x0, x1, SEXT_IMM(imm); \
+    )
+
+#-----------------------------------------------------------------------
+# Tests for an instruction with register operands
+#-----------------------------------------------------------------------
+
+#define TEST_R_OP( testnum, inst, result, val1 ) \
+    TEST_CASE( testnum, x14, result, \
+      li  x1, val1; \
+      inst x14, x1; \
+    )
+
+#define TEST_R_SRC1_EQ_DEST( testnum, inst, result, val1 ) \
+    TEST_CASE( testnum, x1, result, \
+      li  x1, val1; \
+      inst x1, x1; \
+    )
+
+#-----------------------------------------------------------------------
+# Tests for an instruction with register-register operands
+#-----------------------------------------------------------------------
+
+#define TEST_RR_OP( testnum, inst, result, val1, val2 ) \
+    TEST_CASE( testnum, x14, result, \
+      li  x11, MASK_XLEN(val1); \
+      li  x12, MASK_XLEN(val2); \
+      inst x14, x11, x12; \
+    )
+
+#define TEST_RR_SRC1_EQ_DEST( testnum, inst, result, val1, val2 ) \
+    TEST_CASE( testnum, x11, result, \
+      li  x11, MASK_XLEN(val1); \
+      li  x12, MASK_XLEN(val2); \
+      inst x11, x11, x12; \
+    )
+
+#define TEST_RR_SRC2_EQ_DEST( testnum, inst, result, val1, val2 ) \
+    TEST_CASE( testnum, x12, result, \
+      li  x11, MASK_XLEN(val1); \
+      li  x12, MASK_XLEN(val2); \
+      inst x12, x11, x12; \
+    )
+
+#define TEST_RR_SRC12_EQ_DEST( testnum, inst, result, val1 ) \
+    TEST_CASE( testnum, x11, result, \
+      li  x11, MASK_XLEN(val1); \
+      inst x11, x11, x11; \
+    )
+
+#define TEST_RR_DEST_BYPASS( testnum, nop_cycles, inst, result, val1, val2 ) \
+    TEST_CASE( testnum, x6, result, \
+      li  x4, 0; \
+1:    li  x1, MASK_XLEN(val1); \
+      li  x2, MASK_XLEN(val2); \
+      inst x14, x1, x2; \
+      TEST_INSERT_NOPS_ ## nop_cycles \
+      addi  x6, x14, 0; \
+      addi  x4, x4, 1; \
+      li  x5, 2; \
+      bne x4, x5, 1b \
+    )
+
+#define TEST_RR_SRC12_BYPASS( testnum, src1_nops, src2_nops, inst, result, val1, val2 ) \
+    TEST_CASE( testnum, x14, result, \
+      li  x4, 0; \
+1:    li  x1, MASK_XLEN(val1); \
+      TEST_INSERT_NOPS_ ## src1_nops \
+      li  x2, MASK_XLEN(val2); \
+      TEST_INSERT_NOPS_ ## src2_nops \
+      inst x14, x1, x2; \
+      addi  x4, x4, 1; \
+      li  x5, 2; \
+      bne x4, x5, 1b \
+    )
+
+#define TEST_RR_SRC21_BYPASS( testnum, src1_nops, src2_nops, inst, result, val1, val2 ) \
+    TEST_CASE( testnum, x14, result, \
+      li  x4, 0; \
+1:    li  x2, MASK_XLEN(val2); \
+      TEST_INSERT_NOPS_ ## src1_nops \
+      li  x1, MASK_XLEN(val1); \
+      TEST_INSERT_NOPS_ ## src2_nops \
+      inst x14, x1, x2; \
+      addi  x4, x4, 1; \
+      li  x5, 2; \
+      bne x4, x5, 1b \
+    )
+
+#define TEST_RR_ZEROSRC1( testnum, inst, result, val ) \
+    TEST_CASE( testnum, x2, result, \
+      li x1, MASK_XLEN(val); \
+      inst x2, x0, x1; \
+    )
+
+#define TEST_RR_ZEROSRC2( testnum, inst, result, val ) \
+    TEST_CASE( testnum, x2, result, \
+      li x1, MASK_XLEN(val); \
+      inst x2, x1, x0; \
+    )
+
+#define TEST_RR_ZEROSRC12( testnum, inst, result ) \
+    TEST_CASE( testnum, x1, result, \
+      inst x1, x0, x0; \
+    )
+
+#define TEST_RR_ZERODEST( testnum, inst, val1, val2 ) \
+    TEST_CASE( testnum, x0, 0, \
+      li x1, MASK_XLEN(val1); \
+      li x2, MASK_XLEN(val2); \
+      inst x0, x1, x2; \
+    )
+
+#-----------------------------------------------------------------------
+# Test memory instructions
+#-----------------------------------------------------------------------
+
+#define TEST_LD_OP( testnum, inst, result, offset, base ) \
+    TEST_CASE( testnum, x14, result, \
+      li  x15, result; /* Tell the exception handler the expected result. */ \
+      la  x2, base; \
+      inst x14, offset(x2); \
+    )
+
+#define TEST_ST_OP( testnum, load_inst, store_inst, result, offset, base ) \
+    TEST_CASE( testnum, x14, result, \
+      la  x2, base; \
+      li  x1, result; \
+      la  x15, 7f; /* Tell the exception handler how to skip this test. */ \
+      store_inst x1, offset(x2); \
+      load_inst x14, offset(x2); \
+      j 8f; \
+      .balign 4; \
+7:    \
+      mv x14, x1; \
+8:    \
+    )
+
+#-----------------------------------------------------------------------
+# Test jump instructions
+#-----------------------------------------------------------------------
+
+#define TEST_BR2_OP_TAKEN( testnum, inst, val1, val2 ) \
+test_ ## testnum: \
+    li  TESTNUM, testnum; \
+    li  x1, val1; \
+    li  x2, val2; \
+    inst x1, x2, 2f; \
+    bne x0, TESTNUM, fail; \
+1:  bne x0, TESTNUM, 3f; \
+2:  inst x1, x2, 1b; \
+    bne x0, TESTNUM, fail; \
+3:
+
+#define TEST_BR2_OP_NOTTAKEN( testnum, inst, val1, val2 ) \
+test_ ## testnum: \
+    li  TESTNUM, testnum; \
+    li  x1, val1; \
+    li  x2, val2; \
+    inst x1, x2, 1f; \
+    bne x0, TESTNUM, 2f; \
+1:  bne x0, TESTNUM, fail; \
+2:  inst x1, x2, 1b; \
+3:
+
+#-----------------------------------------------------------------------
+# Tests floating-point instructions
+#-----------------------------------------------------------------------
+
+# The operands and the results are given by their bits, as llvm-mc has no way
+# of spelling the quiet and signaling NaNs with .float.
+
+#define qNaNf 0x7fc00000
+#define sNaNf 0x7f800001
+
+#define TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, val2, val3, code... ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  a0, test_ ## testnum ## _data ;\
+  flw f0, 0(a0); \
+  flw f1, 4(a0); \
+  flw f2, 8(a0); \
+  lw  a3, 12(a0); \
+  code; \
+  fsflags a1, x0; \
+  li a2, flags; \
+  bne a0, a3, fail; \
+  bne a1, a2, fail; \
+  .pushsection .data; \
+  .align 2; \
+  test_ ## testnum ## _data: \
+  .word val1; \
+  .word val2; \
+  .word val3; \
+  .word result; \
+  .popsection
+
+#define TEST_FP_OP1_S( testnum, inst, flags, result, val1 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst f3, f0; fmv.x.w a0, f3)
+
+#define TEST_FP_OP1_S_RM( testnum, inst, rm, flags, result, val1 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst f3, f0, rm; fmv.x.w a0, f3)
+
+#define TEST_FP_OP2_S( testnum, inst, flags, result, val1, val2 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst f3, f0, f1; fmv.x.w a0, f3)
+
+#define TEST_FP_OP2_S_RM( testnum, inst, rm, flags, result, val1, val2 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst f3, f0, f1, rm; fmv.x.w a0, f3)
+
+#define TEST_FP_OP3_S( testnum, inst, flags, result, val1, val2, val3 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, val2, val3, \
+                    inst f3, f0, f1, f2; fmv.x.w a0, f3)
+
+#define TEST_FP_OP3_S_RM( testnum, inst, rm, flags, result, val1, val2, val3 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, val2, val3, \
+                    inst f3, f0, f1, f2, rm; fmv.x.w a0, f3)
+
+#define TEST_FP_INT_OP_S( testnum, inst, flags, result, val1, rm ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst a0, f0, rm)
+
+#define TEST_FP_CMP_OP_S( testnum, inst, flags, result, val1, val2 ) \
+  TEST_FP_OP_S_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst a0, f0, f1)
+
+#define TEST_FCLASS_S(testnum, correct, input) \
+  TEST_CASE(testnum, a0, correct, li a0, input; fmv.w.x fa0, a0; \
+                    fclass.s a0, fa0)
+
+#define TEST_INT_FP_OP_S( testnum, inst, rm, flags, result, val1 ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  a0, test_ ## testnum ## _data ;\
+  lw  a3, 0(a0); \
+  li  a0, val1; \
+  inst f0, a0, rm; \
+  fsflags a1, x0; \
+  li a2, flags; \
+  fmv.x.w a0, f0; \
+  bne a0, a3, fail; \
+  bne a1, a2, fail; \
+  .pushsection .data; \
+  .align 2; \
+  test_ ## testnum ## _data: \
+  .word result; \
+  .popsection
+
+# The double precision results are checked a word at a time, the low one in
+# a0 and the high one in t2. The integer to double conversions are exact, so
+# they take no rounding mode and raise no flags.
+
+#define qNaN 0x7ff8000000000000
+#define sNaN 0x7ff0000000000001
+
+#define TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, val2, val3, code... ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  a0, test_ ## testnum ## _data ;\
+  fld f0, 0(a0); \
+  fld f1, 8(a0); \
+  fld f2, 16(a0); \
+  lw  a3, 24(a0); \
+  lw  t1, 28(a0); \
+  code; \
+  fsflags a1, x0; \
+  li a2, flags; \
+  bne a0, a3, fail; \
+  bne t2, t1, fail; \
+  bne a1, a2, fail; \
+  .pushsection .data; \
+  .align 3; \
+  test_ ## testnum ## _data: \
+  .dword val1; \
+  .dword val2; \
+  .dword val3; \
+  .dword result; \
+  .popsection
+
+#define TEST_FP_OP1_D( testnum, inst, flags, result, val1 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst f3, f0; fsd f3, 0(a0); lw t2, 4(a0); lw a0, 0(a0))
+
+#define TEST_FP_OP1_D_RM( testnum, inst, rm, flags, result, val1 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst f3, f0, rm; fsd f3, 0(a0); lw t2, 4(a0); lw a0, 0(a0))
+
+#define TEST_FP_OP2_D( testnum, inst, flags, result, val1, val2 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst f3, f0, f1; fsd f3, 0(a0); lw t2, 4(a0); lw a0, 0(a0))
+
+#define TEST_FP_OP2_D_RM( testnum, inst, rm, flags, result, val1, val2 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst f3, f0, f1, rm; fsd f3, 0(a0); lw t2, 4(a0); lw a0, 0(a0))
+
+#define TEST_FP_OP3_D( testnum, inst, flags, result, val1, val2, val3 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, val2, val3, \
+                    inst f3, f0, f1, f2; fsd f3, 0(a0); lw t2, 4(a0); lw a0, 0(a0))
+
+#define TEST_FP_OP3_D_RM( testnum, inst, rm, flags, result, val1, val2, val3 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, val2, val3, \
+                    inst f3, f0, f1, f2, rm; fsd f3, 0(a0); lw t2, 4(a0); lw a0, 0(a0))
+
+#define TEST_FP_INT_OP_D( testnum, inst, flags, result, val1, rm ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst a0, f0, rm; li t2, 0)
+
+#define TEST_FP_CMP_OP_D( testnum, inst, flags, result, val1, val2 ) \
+  TEST_FP_OP_D_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst a0, f0, f1; li t2, 0)
+
+#define TEST_FCLASS_D(testnum, correct, input) \
+  TEST_FP_OP_D_INTERNAL( testnum, 0, correct, input, 0, 0, \
+                    fclass.d a0, f0; li t2, 0)
+
+#define TEST_INT_FP_OP_D( testnum, inst, result, val1 ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  a0, test_ ## testnum ## _data ;\
+  lw  a3, 0(a0); \
+  lw  t1, 4(a0); \
+  li  a1, val1; \
+  inst f0, a1; \
+  fsd f0, 0(a0); \
+  lw  t2, 4(a0); \
+  lw  a0, 0(a0); \
+  fsflags a1, x0; \
+  li a2, 0; \
+  bne a0, a3, fail; \
+  bne t2, t1, fail; \
+  bne a1, a2, fail; \
+  .pushsection .data; \
+  .align 3; \
+  test_ ## testnum ## _data: \
+  .dword result; \
+  .popsection
+
+# The half precision values are given by their bits as well, the results are
+# compared zero extended, as fmv.x.h sign extends them.
+
+#define qNaNh 0x7e00
+#define sNaNh 0x7c01
+
+#define TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, val2, val3, code... ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  a0, test_ ## testnum ## _data ;\
+  flh f0, 0(a0); \
+  flh f1, 2(a0); \
+  flh f2, 4(a0); \
+  lw  a3, 8(a0); \
+  code; \
+  fsflags a1, x0; \
+  li a2, flags; \
+  bne a0, a3, fail; \
+  bne a1, a2, fail; \
+  .pushsection .data; \
+  .align 2; \
+  test_ ## testnum ## _data: \
+  .hword val1; \
+  .hword val2; \
+  .hword val3; \
+  .hword 0; \
+  .word result; \
+  .popsection
+
+#define FMV_X_HU( rd, rs ) \
+  fmv.x.h rd, rs; slli rd, rd, 16; srli rd, rd, 16
+
+#define TEST_FP_OP1_H( testnum, inst, flags, result, val1 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst f3, f0; FMV_X_HU(a0, f3))
+
+#define TEST_FP_OP1_H_RM( testnum, inst, rm, flags, result, val1 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst f3, f0, rm; FMV_X_HU(a0, f3))
+
+#define TEST_FP_OP2_H( testnum, inst, flags, result, val1, val2 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst f3, f0, f1; FMV_X_HU(a0, f3))
+
+#define TEST_FP_OP2_H_RM( testnum, inst, rm, flags, result, val1, val2 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst f3, f0, f1, rm; FMV_X_HU(a0, f3))
+
+#define TEST_FP_OP3_H( testnum, inst, flags, result, val1, val2, val3 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, val2, val3, \
+                    inst f3, f0, f1, f2; FMV_X_HU(a0, f3))
+
+#define TEST_FP_OP3_H_RM( testnum, inst, rm, flags, result, val1, val2, val3 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, val2, val3, \
+                    inst f3, f0, f1, f2, rm; FMV_X_HU(a0, f3))
+
+#define TEST_FP_INT_OP_H( testnum, inst, flags, result, val1, rm ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, 0, 0, \
+                    inst a0, f0, rm)
+
+#define TEST_FP_CMP_OP_H( testnum, inst, flags, result, val1, val2 ) \
+  TEST_FP_OP_H_INTERNAL( testnum, flags, result, val1, val2, 0, \
+                    inst a0, f0, f1)
+
+#define TEST_FCLASS_H(testnum, correct, input) \
+  TEST_CASE(testnum, a0, correct, li a0, input; fmv.h.x fa0, a0; \
+                    fclass.h a0, fa0)
+
+#define TEST_INT_FP_OP_H( testnum, inst, rm, flags, result, val1 ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  a0, test_ ## testnum ## _data ;\
+  lw  a3, 0(a0); \
+  li  a0, val1; \
+  inst f0, a0, rm; \
+  fsflags a1, x0; \
+  li a2, flags; \
+  FMV_X_HU(a0, f0); \
+  bne a0, a3, fail; \
+  bne a1, a2, fail; \
+  .pushsection .data; \
+  .align 2; \
+  test_ ## testnum ## _data: \
+  .word result; \
+  .popsection
+
+#-----------------------------------------------------------------------
+# Tests vector instructions
+#-----------------------------------------------------------------------
+
+# Every vector test starts with v8-v15 loaded from vsrc and v0 from vmask,
+# runs the code and then checks v8-v15 against the 32 words at result, which
+# assumes a VLEN of 128. The code sets vtype itself and can check scalar
+# results on its own with TEST_VECTOR_CHECK, as fail can be too far for a
+# branch in the longer tests. The last register of vsrc holds small values,
+# to be used as indices and offsets.
+
+#define TEST_VECTOR( testnum, result, code... ) \
+test_ ## testnum: \
+  li  TESTNUM, testnum; \
+  la  t0, vsrc; \
+  vl8re8.v v8, (t0); \
+  la  t0, vmask; \
+  vl1re8.v v0, (t0); \
+  code; \
+  la  t0, vres; \
+  vs8r.v v8, (t0); \
+  la  t1, result; \
+  li  t2, 32; \
+1:lw  a0, 0(t0); \
+  lw  a1, 0(t1); \
+  beq a0, a1, 2f; \
+  j   fail; \
+2:addi t0, t0, 4; \
+  addi t1, t1, 4; \
+  addi t2, t2, -1; \
+  bnez t2, 1b;
+
+#define TEST_VECTOR_CHECK( reg, val ) \
+  li  t3, val; \
+  beq reg, t3, 3f; \
+  j   fail; \
+3:
+
+#define TEST_VECTOR_DATA \
+  .align 4; \
+vsrc: \
+  .word 0x82159c07, 0xa1643f01, 0xb632f05c, 0x417feb60; \
+  .word 0xe4712714, 0x18fdf7fc, 0xb3ffeac0, 0x4b45ff42; \
+  .word 0xeab9e7bf, 0x8638b5d8, 0x845a750e, 0x81811085; \
+  .word 0x4ffe28ff, 0x6f2f2f40, 0x75a70157, 0xbb01952f; \
+  .word 0x80dfff8c, 0x1dae0180, 0x4fa019e6, 0x42c93123; \
+  .word 0x8017a047, 0xfe100136, 0x210081ff, 0xbb52895b; \
+  .word 0x01679d1e, 0x4371fee5, 0x8167da90, 0x815affca; \
+  .word 0x00000001, 0x00000016, 0x00000006, 0x0000000d; \
+vmask: \
+  .word 0x691cb8e4, 0x0b18f3b0, 0x85c83a3a, 0xd6b70a4a; \
+vres: \
+  .skip 128;
+
+#-----------------------------------------------------------------------
+# Test nops
+#-----------------------------------------------------------------------
+
+#define TEST_INSERT_NOPS_0
+#define TEST_INSERT_NOPS_1  nop; TEST_INSERT_NOPS_0
+#define TEST_INSERT_NOPS_2  nop; TEST_INSERT_NOPS_1
+#define TEST_INSERT_NOPS_3  nop; TEST_INSERT_NOPS_2
+#define TEST_INSERT_NOPS_4  nop; TEST_INSERT_NOPS_3
+#define TEST_INSERT_NOPS_5  nop; TEST_INSERT_NOPS_4
+#define TEST_INSERT_NOPS_6  nop; TEST_INSERT_NOPS_5
+#define TEST_INSERT_NOPS_7  nop; TEST_INSERT_NOPS_6
+#define TEST_INSERT_NOPS_8  nop; TEST_INSERT_NOPS_7
+#define TEST_INSERT_NOPS_9  nop; TEST_INSERT_NOPS_8
+#define TEST_INSERT_NOPS_10 nop; TEST_INSERT_NOPS_9
+
+#-----------------------------------------------------------------------
+# Pass and fail code (assumes test num is in TESTNUM)
+#-----------------------------------------------------------------------
+
+#define TEST_PASSFAIL \
+        bne x0, TESTNUM, pass; \
+fail: \
+        RVTEST_FAIL; \
+pass: \
+        RVTEST_PASS \
+
+#endif